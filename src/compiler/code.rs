@@ -1,3 +1,5 @@
+use crate::parser::Cast;
+
 #[derive(Debug, Clone)]
 pub enum Code {
     Constant(usize),
@@ -6,13 +8,26 @@ pub enum Code {
     Subtract,
     Multiply,
     Divide,
+    Modulo,
     Concat,
+    Not,
+    Cast(Cast),
     LessThan,
     DeclareConst(String),
     GreaterThan,
+    LessThanOrEqual,
+    GreaterThanOrEqual,
+    Equals,
+    NotEquals,
+    Identical,
+    NotIdentical,
+    Spaceship,
     Assign(String),
     AssignToIndex,
     Get(String),
+    GetSuperglobal(String),
+    AssignSuperglobal(String),
+    AssignToGlobals,
     GetConstant(String),
     Jump(usize),
     JumpIfFalse(usize),
@@ -25,9 +40,17 @@ pub enum Code {
     ReturnWith,
     InitArray,
     AddToArray,
+    AddToArrayWithKey,
     GetArrayItem,
     InitCall(String),
     DoUserCall,
     DoInternalCall,
     SendArg,
+    // <position>, <variable>
+    SendRef(usize, String),
+    InitNew(String),
+    InitMethodCall(String),
+    GetProperty(String),
+    AssignToProperty(String),
+    Exit,
 }
\ No newline at end of file
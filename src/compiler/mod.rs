@@ -1,20 +1,18 @@
 use crate::parser::{Statement, Expression, Op};
-use crate::object::Object;
-use crate::globals::Globals;
+use crate::object::{Object, convert};
+use crate::globals::{Globals, function_name};
+use crate::vm::is_superglobal;
+use crate::error;
 pub use code::Code;
 
 mod code;
 
-macro_rules! binary_op {
-    ($lhs:expr, $op:expr, $rhs:expr) => {
-        match $op {
-            Op::Add => $lhs + $rhs,
-            Op::Subtract => $lhs - $rhs,
-            Op::Multiply => $lhs * $rhs,
-            Op::Divide => $lhs / $rhs,
-            _ => todo!()
-        }
-    };
+fn literal(expression: &Expression) -> Option<Object> {
+    match expression {
+        Expression::Integer(i) => Some(Object::Integer(*i)),
+        Expression::Float(f) => Some(Object::Float(*f)),
+        _ => None,
+    }
 }
 
 struct Scope {
@@ -59,10 +57,12 @@ impl Compiler {
                 // We do this now so that any recursive function calls are aware of the function,
                 // otherwise they'll try to do an internal function call.
                 // TODO: Swap internal decision logic around so that it defaults to a user function.
-                self.globals.create_user_function(name.clone(), Vec::new());
+                self.globals.create_user_function(&name, args.len(), Vec::new());
                 
                 self.enter_scope();
 
+                let parameters = args.len();
+
                 for arg in args {
                     self.emit(Code::Assign(arg));
                     self.emit(Code::Pop);
@@ -77,7 +77,7 @@ impl Compiler {
 
                 let scope = self.leave_scope();
 
-                self.globals.create_user_function(name, scope.instructions);
+                self.globals.create_user_function(&name, parameters, scope.instructions);
             },
             Statement::IfElse(condition, then, otherwise) => {
                 self.expression(condition);
@@ -177,7 +177,6 @@ impl Compiler {
                 self.expression(expression);
                 self.emit(Code::Pop);
             },
-            _ => todo!("{:?}", statement)
         }
     }
 
@@ -193,7 +192,7 @@ impl Compiler {
                 self.emit(Code::Null);
             },
             Expression::String(s) => {
-                self.constant(Object::String(s));
+                self.constant(Object::String(s.into()));
             },
             Expression::Integer(i) => {
                 self.constant(Object::Integer(i));
@@ -201,6 +200,9 @@ impl Compiler {
             Expression::Float(f) => {
                 self.constant(Object::Float(f));
             },
+            Expression::Variable(v) if is_superglobal(&v) => {
+                self.emit(Code::GetSuperglobal(v));
+            },
             Expression::Variable(v) => {
                 self.emit(Code::Get(v));
             },
@@ -211,75 +213,91 @@ impl Compiler {
                 let lhs = *lhs;
                 let rhs = *rhs;
 
-                match op {
-                    Op::LessThan | Op::GreaterThan => {
-                        self.expression(lhs);
-                        self.expression(rhs);
-
-                        match op {
-                            Op::LessThan => self.emit(Code::LessThan),
-                            Op::GreaterThan => self.emit(Code::GreaterThan),
-                            _ => unreachable!()
-                        };
-                    },
-                    _ => {
-                        match (lhs.clone(), rhs.clone()) {
-                            (Expression::Integer(l), Expression::Integer(r)) => {
-                                if op == Op::Divide {
-                                    self.constant(Object::Float(binary_op!(l as f64, op, r as f64)))
-                                } else {
-                                    self.constant(Object::Integer(binary_op!(l, op, r)))
-                                }
-                            },
-                            (Expression::Float(l), Expression::Integer(r)) => {
-                                self.constant(Object::Float(binary_op!(l, op, r as f64)))
-                            },
-                            (Expression::Integer(l), Expression::Float(r)) => {
-                                self.constant(Object::Float(binary_op!(l as f64, op, r)))
-                            },
-                            (Expression::Float(l), Expression::Float(r)) => {
-                                self.constant(Object::Float(binary_op!(l, op, r)))
-                            },
-                            _ => {
-                                self.expression(lhs);
-                                self.expression(rhs);
-        
-                                match op {
-                                    Op::Add => self.emit(Code::Add),
-                                    Op::Subtract => self.emit(Code::Subtract),
-                                    Op::Multiply => self.emit(Code::Multiply),
-                                    Op::Divide => self.emit(Code::Divide),
-                                    Op::Concat => self.emit(Code::Concat),
-                                    _ => unreachable!()
-                                };
-                            },
-                        };
-                    }
+                // Arithmetic on two numeric literals is folded at compile-time, using the same
+                // conversion rules as the VM. Division by zero is left to throw at runtime.
+                let folded = match (literal(&lhs), &op, literal(&rhs)) {
+                    (Some(l), Op::Add, Some(r)) => Some(convert::add(&l, &r)),
+                    (Some(l), Op::Subtract, Some(r)) => Some(convert::subtract(&l, &r)),
+                    (Some(l), Op::Multiply, Some(r)) => Some(convert::multiply(&l, &r)),
+                    (Some(l), Op::Divide, Some(r)) if r.to_bool() => Some(convert::divide(&l, &r)),
+                    (Some(l), Op::Modulo, Some(r)) if convert::to_int(&r) != 0 => Some(convert::modulo(&l, &r)),
+                    _ => None,
                 };
+
+                if let Some(value) = folded {
+                    self.constant(value);
+                    return;
+                }
+
+                self.expression(lhs);
+                self.expression(rhs);
+
+                self.emit(match op {
+                    Op::Add => Code::Add,
+                    Op::Subtract => Code::Subtract,
+                    Op::Multiply => Code::Multiply,
+                    Op::Divide => Code::Divide,
+                    Op::Modulo => Code::Modulo,
+                    Op::Concat => Code::Concat,
+                    Op::LessThan => Code::LessThan,
+                    Op::GreaterThan => Code::GreaterThan,
+                    Op::LessThanOrEqual => Code::LessThanOrEqual,
+                    Op::GreaterThanOrEqual => Code::GreaterThanOrEqual,
+                    Op::Equals => Code::Equals,
+                    Op::NotEquals => Code::NotEquals,
+                    Op::Identical => Code::Identical,
+                    Op::NotIdentical => Code::NotIdentical,
+                    Op::Spaceship => Code::Spaceship,
+                });
             },
             Expression::Assign(target, value) => {
                 self.expression(*value);
 
                 match *target {
+                    Expression::Variable(v) if v == "$GLOBALS" => {
+                        error::fatal("$GLOBALS can only be modified using the $GLOBALS[$name] = $value syntax");
+                    },
+                    Expression::Variable(v) if is_superglobal(&v) => {
+                        self.emit(Code::AssignSuperglobal(v));
+                    },
                     Expression::Variable(v) => {
                         self.emit(Code::Assign(v));
                     },
+                    Expression::Index(target, index) if matches!(&*target, Expression::Variable(v) if v == "$GLOBALS") => {
+                        self.expression(*index);
+                        self.emit(Code::AssignToGlobals);
+                    },
                     Expression::Index(target, index) => {
                         self.expression(*target);
                         self.expression(*index);
                         self.emit(Code::AssignToIndex);
                     },
+                    Expression::Property(target, name) => {
+                        self.expression(*target);
+                        self.emit(Code::AssignToProperty(name));
+                    },
                     _ => unreachable!("Assign to: {:?}", target),
                 };
             },
-            Expression::Call(callable, mut args) => {
-                self.emit(Code::InitCall(callable.clone()));
+            Expression::Call(callable, args) => {
+                let callable = function_name(&callable);
 
-                args.reverse();
+                self.emit(Code::InitCall(callable.clone()));
 
-                for arg in args {
-                    self.expression(arg);
-                    self.emit(Code::SendArg);
+                let references = self.globals.get_references(&callable);
+
+                for (position, arg) in args.into_iter().enumerate() {
+                    match arg {
+                        // Internal functions that take arguments by reference write back
+                        // to the variable once the call is done.
+                        Expression::Variable(v) if references.contains(position) => {
+                            self.emit(Code::SendRef(position, v));
+                        },
+                        arg => {
+                            self.expression(arg);
+                            self.emit(Code::SendArg);
+                        },
+                    }
                 }
 
                 if self.globals.is_user_function(&callable) {
@@ -291,9 +309,18 @@ impl Compiler {
             Expression::Array(items) => {
                 self.emit(Code::InitArray);
 
-                for item in items {
-                    self.expression(item);
-                    self.emit(Code::AddToArray);
+                for (key, value) in items {
+                    match key {
+                        Some(key) => {
+                            self.expression(key);
+                            self.expression(value);
+                            self.emit(Code::AddToArrayWithKey);
+                        },
+                        None => {
+                            self.expression(value);
+                            self.emit(Code::AddToArray);
+                        },
+                    }
                 }
             },
             Expression::Index(target, index) => {
@@ -301,7 +328,51 @@ impl Compiler {
                 self.expression(*index);
                 self.emit(Code::GetArrayItem);
             },
-            _ => todo!("{:?}", expression)
+            Expression::Not(expression) => {
+                self.expression(*expression);
+                self.emit(Code::Not);
+            },
+            Expression::Cast(cast, expression) => {
+                self.expression(*expression);
+                self.emit(Code::Cast(cast));
+            },
+            Expression::Exit(status) => {
+                match status {
+                    Some(status) => self.expression(*status),
+                    None => {
+                        self.emit(Code::Null);
+                    },
+                }
+
+                self.emit(Code::Exit);
+            },
+            // Only internal classes exist, so constructors and methods are always
+            // internal functions that take the object as their first argument.
+            Expression::New(class, args) => {
+                self.emit(Code::InitNew(class));
+
+                for arg in args {
+                    self.expression(arg);
+                    self.emit(Code::SendArg);
+                }
+
+                self.emit(Code::DoInternalCall);
+            },
+            Expression::MethodCall(target, method, args) => {
+                self.expression(*target);
+                self.emit(Code::InitMethodCall(function_name(&method)));
+
+                for arg in args {
+                    self.expression(arg);
+                    self.emit(Code::SendArg);
+                }
+
+                self.emit(Code::DoInternalCall);
+            },
+            Expression::Property(target, name) => {
+                self.expression(*target);
+                self.emit(Code::GetProperty(name));
+            },
         }
     }
 
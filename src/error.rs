@@ -0,0 +1,28 @@
+use std::fmt::Display;
+
+// Diagnostics are written to stdout the same way the PHP CLI does with `display_errors` on.
+
+pub fn warning(message: impl Display) {
+    println!("\nWarning: {}", message);
+}
+
+pub fn notice(message: impl Display) {
+    println!("\nNotice: {}", message);
+}
+
+pub fn deprecated(message: impl Display) {
+    println!("\nDeprecated: {}", message);
+}
+
+/// Ends the script with a fatal error that isn't a throwable, such as a compile error.
+pub fn fatal(message: impl Display) -> ! {
+    println!("\nFatal error: {}", message);
+    std::process::exit(255);
+}
+
+/// Throws an `Error` or `Exception` of the given class. There is no way to catch
+/// anything in userland yet, so this always ends the script as an uncaught throwable.
+pub fn throw(class: &str, message: impl Display) -> ! {
+    println!("\nFatal error: Uncaught {}: {}", class, message);
+    std::process::exit(255);
+}
@@ -4,6 +4,7 @@ use crate::compiler::Code;
 use crate::object::Object;
 use crate::stdlib::*;
 use crate::vm::Machine;
+use std::rc::Rc;
 
 pub type InternalFunctionCallback = fn (&mut Machine, Vec<Object>) -> Object;
 
@@ -11,6 +12,32 @@ pub type InternalFunctionCallback = fn (&mut Machine, Vec<Object>) -> Object;
 pub struct InternalFunction {
     pub name: String,
     pub callback: InternalFunctionCallback,
+    pub references: References,
+}
+
+/// The arguments an internal function takes by reference. Variadic functions like
+/// `array_multisort()` take every argument from a position onwards by reference.
+#[derive(Debug, Clone, Copy)]
+pub enum References {
+    Positions(&'static [usize]),
+    From(usize),
+}
+
+impl References {
+    pub fn contains(&self, position: usize) -> bool {
+        match self {
+            References::Positions(positions) => positions.contains(&position),
+            References::From(from) => position >= *from,
+        }
+    }
+}
+
+/// A function declared in the script. The name is kept as it was declared, for messages.
+#[derive(Debug, Clone)]
+pub struct UserFunction {
+    pub name: String,
+    pub parameters: usize,
+    pub instructions: Vec<Code>,
 }
 
 impl Debug for InternalFunction {
@@ -22,35 +49,667 @@ impl Debug for InternalFunction {
 #[derive(Debug)]
 pub struct Globals {
     functions: HashMap<String, Function>,
+    methods: HashMap<String, InternalFunction>,
     constants: HashMap<String, Object>,
+    wrappers: Wrappers,
 }
 
 impl Globals {
     pub fn new() -> Self {
         let mut s = Self {
             functions: HashMap::new(),
+            methods: HashMap::new(),
             constants: HashMap::new(),
+            wrappers: Wrappers::default(),
         };
 
+        s.register_wrapper("php", PhpWrapper);
+        s.register_wrapper("file", FileWrapper);
+        s.register_wrapper("data", DataWrapper);
+
         macro_rules! internal {
             ($name:ident) => {
-                s.create_internal_function(stringify!($name), $name)
+                s.create_internal_function(stringify!($name), $name, References::Positions(&[]))
+            };
+            ($name:ident, &$references:expr) => {
+                s.create_internal_function(stringify!($name), $name, References::Positions(&$references))
+            };
+            ($name:ident, $from:literal..) => {
+                s.create_internal_function(stringify!($name), $name, References::From($from))
             };
         }
 
         // Type conversions and checkers.
         internal!(is_string);
+        internal!(is_int);
+        internal!(is_integer);
+        internal!(is_long);
+        internal!(is_float);
+        internal!(is_double);
+        internal!(is_bool);
+        internal!(is_array);
+        internal!(is_object);
+        internal!(is_resource);
+        internal!(is_null);
+        internal!(is_numeric);
+        internal!(is_scalar);
+        internal!(is_iterable);
+        internal!(is_callable);
         internal!(strval);
+        internal!(intval);
+        internal!(floatval);
+        internal!(doubleval);
+        internal!(boolval);
+        internal!(gettype);
+        internal!(get_debug_type);
+        internal!(settype, &[0]);
 
         // Filesystem.
         internal!(basename);
+        internal!(dirname);
+        internal!(pathinfo);
+        internal!(realpath);
+        internal!(file_exists);
+        internal!(is_file);
+        internal!(is_dir);
+        internal!(file_get_contents);
+        internal!(file_put_contents);
+        internal!(file);
+        internal!(unlink);
+        internal!(mkdir);
+        internal!(rmdir);
+        internal!(rename);
+        internal!(copy);
+        internal!(filesize);
+        internal!(filemtime);
+        internal!(touch);
+        internal!(scandir);
+        internal!(glob);
+        internal!(tempnam);
+        internal!(sys_get_temp_dir);
+        internal!(opendir);
+        internal!(readdir);
+        internal!(rewinddir);
+        internal!(closedir);
+        internal!(is_readable);
+        internal!(is_writable);
+        internal!(is_link);
+        internal!(stat);
+        internal!(lstat);
+        internal!(fileperms);
+        internal!(chmod);
+        internal!(symlink);
+        internal!(readlink);
+        internal!(disk_free_space);
+        internal!(clearstatcache);
+
+        // Streams.
+        internal!(fopen);
+        internal!(fclose);
+        internal!(fread);
+        internal!(fgets);
+        internal!(fgetc);
+        internal!(fwrite);
+        internal!(fputs);
+        internal!(feof);
+        internal!(fseek);
+        internal!(ftell);
+        internal!(rewind);
+        internal!(fflush);
+        internal!(flock, &[2]);
+        internal!(fgetcsv);
+        internal!(fputcsv);
+        internal!(stream_get_wrappers);
 
         // Misc.
         internal!(define);
+
+        // Variable handling.
         internal!(var_dump);
+        internal!(var_export);
+        internal!(print_r);
+        internal!(serialize);
+        internal!(unserialize);
 
         // Arrays.
         internal!(count);
+        internal!(sizeof);
+        internal!(array_keys);
+        internal!(array_values);
+        internal!(array_merge);
+        internal!(array_combine);
+        internal!(array_flip);
+        internal!(array_slice);
+        internal!(array_splice, &[0]);
+        internal!(array_search);
+        internal!(in_array);
+        internal!(array_key_exists);
+        internal!(key_exists);
+        internal!(array_key_first);
+        internal!(array_key_last);
+        internal!(array_unique);
+        internal!(array_reverse);
+        internal!(array_fill);
+        internal!(array_fill_keys);
+        internal!(array_pad);
+        internal!(array_chunk);
+        internal!(array_column);
+        internal!(range);
+        internal!(compact);
+        internal!(extract);
+        internal!(array_sum);
+        internal!(array_product);
+        internal!(array_push, &[0]);
+        internal!(array_pop, &[0]);
+        internal!(array_shift, &[0]);
+        internal!(array_unshift, &[0]);
+        internal!(array_map);
+        internal!(array_filter);
+        internal!(array_reduce);
+        internal!(array_walk);
+        internal!(usort, &[0]);
+        internal!(uasort, &[0]);
+        internal!(uksort, &[0]);
+        internal!(sort, &[0]);
+        internal!(rsort, &[0]);
+        internal!(asort, &[0]);
+        internal!(arsort, &[0]);
+        internal!(ksort, &[0]);
+        internal!(krsort, &[0]);
+        internal!(natsort, &[0]);
+        internal!(natcasesort, &[0]);
+        internal!(array_multisort, 0..);
+
+        // Math.
+        internal!(abs);
+        internal!(ceil);
+        internal!(floor);
+        internal!(round);
+        internal!(intdiv);
+        internal!(fmod);
+        internal!(fdiv);
+        internal!(sqrt);
+        internal!(pow);
+        internal!(exp);
+        internal!(expm1);
+        internal!(log);
+        internal!(log10);
+        internal!(log1p);
+        internal!(sin);
+        internal!(cos);
+        internal!(tan);
+        internal!(asin);
+        internal!(acos);
+        internal!(atan);
+        internal!(atan2);
+        internal!(sinh);
+        internal!(cosh);
+        internal!(tanh);
+        internal!(asinh);
+        internal!(acosh);
+        internal!(atanh);
+        internal!(hypot);
+        internal!(deg2rad);
+        internal!(rad2deg);
+        internal!(pi);
+        internal!(min);
+        internal!(max);
+        internal!(base_convert);
+        internal!(bindec);
+        internal!(decbin);
+        internal!(hexdec);
+        internal!(dechex);
+        internal!(octdec);
+        internal!(decoct);
+        internal!(is_nan);
+        internal!(is_finite);
+        internal!(is_infinite);
+
+        // Random numbers.
+        internal!(mt_srand);
+        internal!(srand);
+        internal!(mt_rand);
+        internal!(rand);
+        internal!(mt_getrandmax);
+        internal!(getrandmax);
+        internal!(random_int);
+        internal!(random_bytes);
+        internal!(lcg_value);
+        internal!(uniqid);
+        internal!(shuffle, &[0]);
+        internal!(str_shuffle);
+        internal!(array_rand);
+
+        // Function handling.
+        internal!(call_user_func);
+        internal!(call_user_func_array);
+
+        // Strings.
+        internal!(strlen);
+        internal!(substr);
+        internal!(strpos);
+        internal!(stripos);
+        internal!(strrpos);
+        internal!(strripos);
+        internal!(str_contains);
+        internal!(str_starts_with);
+        internal!(str_ends_with);
+        internal!(str_replace, &[3]);
+        internal!(str_ireplace, &[3]);
+        internal!(explode);
+        internal!(implode);
+        internal!(join);
+        internal!(trim);
+        internal!(ltrim);
+        internal!(rtrim);
+        internal!(chop);
+        internal!(strtoupper);
+        internal!(strtolower);
+        internal!(ucfirst);
+        internal!(lcfirst);
+        internal!(ucwords);
+        internal!(str_repeat);
+        internal!(str_pad);
+        internal!(str_split);
+        internal!(strrev);
+        internal!(wordwrap);
+        internal!(nl2br);
+        internal!(strcmp);
+        internal!(strcasecmp);
+        internal!(strncmp);
+        internal!(strncasecmp);
+        internal!(strnatcmp);
+        internal!(strnatcasecmp);
+        internal!(substr_count);
+        internal!(similar_text, &[2]);
+        internal!(levenshtein);
+        internal!(bin2hex);
+        internal!(hex2bin);
+        internal!(addslashes);
+        internal!(stripslashes);
+        internal!(quotemeta);
+        internal!(addcslashes);
+        internal!(chunk_split);
+        internal!(ord);
+        internal!(chr);
+        internal!(strtr);
+        internal!(strspn);
+        internal!(strcspn);
+        internal!(str_word_count);
+        internal!(soundex);
+        internal!(metaphone);
+        internal!(htmlspecialchars);
+        internal!(htmlspecialchars_decode);
+        internal!(html_entity_decode);
+
+        // Character types.
+        internal!(ctype_digit);
+        internal!(ctype_alpha);
+        internal!(ctype_alnum);
+        internal!(ctype_upper);
+        internal!(ctype_lower);
+        internal!(ctype_space);
+        internal!(ctype_punct);
+        internal!(ctype_xdigit);
+        internal!(ctype_cntrl);
+        internal!(ctype_graph);
+        internal!(ctype_print);
+
+        // Formatting.
+        internal!(sprintf);
+        internal!(printf);
+        internal!(vsprintf);
+        internal!(vprintf);
+        internal!(fprintf);
+        internal!(number_format);
+
+        // JSON.
+        internal!(json_encode);
+        internal!(json_decode);
+        internal!(json_last_error);
+        internal!(json_last_error_msg);
+
+        // Regular expressions.
+        internal!(preg_match, &[2]);
+        internal!(preg_match_all, &[2]);
+        internal!(preg_replace, &[4]);
+        internal!(preg_replace_callback, &[4]);
+        internal!(preg_split);
+        internal!(preg_quote);
+        internal!(preg_grep);
+        internal!(preg_last_error);
+        internal!(preg_last_error_msg);
+
+        // Date and time.
+        internal!(time);
+        internal!(microtime);
+        internal!(hrtime);
+        internal!(date);
+        internal!(gmdate);
+        internal!(mktime);
+        internal!(gmmktime);
+        internal!(checkdate);
+        internal!(strtotime);
+        internal!(date_default_timezone_set);
+        internal!(date_default_timezone_get);
+        internal!(date_create);
+        internal!(date_create_immutable);
+        internal!(date_format);
+        internal!(date_modify);
+        internal!(date_add);
+        internal!(date_sub);
+        internal!(date_diff);
+        internal!(date_timestamp_get);
+        internal!(date_timestamp_set);
+        internal!(date_date_set);
+        internal!(date_time_set);
+        internal!(date_timezone_get);
+        internal!(date_timezone_set);
+        internal!(date_interval_create_from_date_string);
+        internal!(date_interval_format);
+        internal!(timezone_open);
+        internal!(timezone_name_get);
+
+        // The date classes are internal, so their methods are the procedural functions
+        // with the object passed as the first argument.
+        macro_rules! method {
+            ($($class:ident)|+, $method:ident, $callback:ident) => {
+                $(s.create_method(stringify!($class), stringify!($method), $callback);)+
+            };
+        }
+
+        method!(DateTime, __construct, datetime_construct);
+        method!(DateTimeImmutable, __construct, datetime_immutable_construct);
+        method!(DateTime | DateTimeImmutable, format, date_format);
+        method!(DateTime | DateTimeImmutable, modify, date_modify);
+        method!(DateTime | DateTimeImmutable, add, date_add);
+        method!(DateTime | DateTimeImmutable, sub, date_sub);
+        method!(DateTime | DateTimeImmutable, diff, date_diff);
+        method!(DateTime | DateTimeImmutable, getTimestamp, date_timestamp_get);
+        method!(DateTime | DateTimeImmutable, setTimestamp, date_timestamp_set);
+        method!(DateTime | DateTimeImmutable, setDate, date_date_set);
+        method!(DateTime | DateTimeImmutable, setTime, date_time_set);
+        method!(DateTime | DateTimeImmutable, getTimezone, date_timezone_get);
+        method!(DateTime | DateTimeImmutable, setTimezone, date_timezone_set);
+
+        method!(DateTimeZone, __construct, timezone_construct);
+        method!(DateTimeZone, getName, timezone_name_get);
+
+        method!(DateInterval, __construct, interval_construct);
+        method!(DateInterval, format, date_interval_format);
+
+        // Hashing.
+        internal!(md5);
+        internal!(sha1);
+        internal!(crc32);
+        internal!(hash);
+        internal!(hash_algos);
+        internal!(hash_hmac);
+        internal!(password_hash);
+        internal!(password_verify);
+        internal!(password_get_info);
+
+        // URLs.
+        internal!(base64_encode);
+        internal!(base64_decode);
+        internal!(urlencode);
+        internal!(rawurlencode);
+        internal!(urldecode);
+        internal!(rawurldecode);
+        internal!(http_build_query);
+        internal!(parse_url);
+        internal!(parse_str, &[1]);
+
+        // Multibyte strings.
+        internal!(mb_strlen);
+        internal!(mb_substr);
+        internal!(mb_strpos);
+        internal!(mb_strtoupper);
+        internal!(mb_strtolower);
+        internal!(mb_convert_case);
+        internal!(mb_str_split);
+        internal!(mb_str_pad);
+        internal!(mb_check_encoding);
+        internal!(mb_convert_encoding);
+        internal!(iconv);
+
+        // Processes.
+        internal!(exec, &[1, 2]);
+        internal!(shell_exec);
+        internal!(system, &[1]);
+        internal!(passthru, &[1]);
+        internal!(escapeshellarg);
+        internal!(escapeshellcmd);
+        internal!(proc_open, &[2]);
+        internal!(proc_close);
+        internal!(proc_get_status);
+        internal!(getenv);
+        internal!(putenv);
+        internal!(getopt, &[2]);
+        internal!(getmypid);
+        internal!(php_uname);
+        internal!(php_sapi_name);
+        internal!(gethostname);
+        internal!(sleep);
+        internal!(usleep);
+
+        macro_rules! constant {
+            ($name:ident, $value:expr) => {
+                s.create_constant(stringify!($name).into(), $value)
+            };
+        }
+
+        // Numbers.
+        constant!(PHP_INT_MAX, Object::Integer(i64::MAX));
+        constant!(PHP_INT_MIN, Object::Integer(i64::MIN));
+        constant!(PHP_INT_SIZE, Object::Integer(8));
+        constant!(PHP_FLOAT_EPSILON, Object::Float(f64::EPSILON));
+        constant!(PHP_FLOAT_MAX, Object::Float(f64::MAX));
+        constant!(PHP_FLOAT_MIN, Object::Float(f64::MIN_POSITIVE));
+        constant!(PHP_FLOAT_DIG, Object::Integer(f64::DIGITS as i64));
+
+
+        // Math.
+        constant!(M_PI, Object::Float(std::f64::consts::PI));
+        constant!(M_E, Object::Float(std::f64::consts::E));
+        constant!(M_LOG2E, Object::Float(std::f64::consts::LOG2_E));
+        constant!(M_LOG10E, Object::Float(std::f64::consts::LOG10_E));
+        constant!(M_LN2, Object::Float(std::f64::consts::LN_2));
+        constant!(M_LN10, Object::Float(std::f64::consts::LN_10));
+        constant!(M_PI_2, Object::Float(std::f64::consts::FRAC_PI_2));
+        constant!(M_PI_4, Object::Float(std::f64::consts::FRAC_PI_4));
+        constant!(M_1_PI, Object::Float(std::f64::consts::FRAC_1_PI));
+        constant!(M_2_PI, Object::Float(std::f64::consts::FRAC_2_PI));
+        constant!(M_2_SQRTPI, Object::Float(std::f64::consts::FRAC_2_SQRT_PI));
+        constant!(M_SQRT2, Object::Float(std::f64::consts::SQRT_2));
+        constant!(M_SQRT1_2, Object::Float(std::f64::consts::FRAC_1_SQRT_2));
+        constant!(M_SQRTPI, Object::Float(std::f64::consts::PI.sqrt()));
+        constant!(M_SQRT3, Object::Float(3f64.sqrt()));
+        constant!(M_LNPI, Object::Float(std::f64::consts::PI.ln()));
+        constant!(M_EULER, Object::Float(0.577_215_664_901_532_9));
+        constant!(NAN, Object::Float(f64::NAN));
+        constant!(INF, Object::Float(f64::INFINITY));
+        constant!(PHP_ROUND_HALF_UP, Object::Integer(PHP_ROUND_HALF_UP));
+        constant!(PHP_ROUND_HALF_DOWN, Object::Integer(PHP_ROUND_HALF_DOWN));
+        constant!(PHP_ROUND_HALF_EVEN, Object::Integer(PHP_ROUND_HALF_EVEN));
+        constant!(PHP_ROUND_HALF_ODD, Object::Integer(PHP_ROUND_HALF_ODD));
+
+        // Filesystem.
+        constant!(DIRECTORY_SEPARATOR, Object::String("/".into()));
+        constant!(PATH_SEPARATOR, Object::String(":".into()));
+        constant!(FILE_USE_INCLUDE_PATH, Object::Integer(FILE_USE_INCLUDE_PATH));
+        constant!(FILE_IGNORE_NEW_LINES, Object::Integer(FILE_IGNORE_NEW_LINES));
+        constant!(FILE_SKIP_EMPTY_LINES, Object::Integer(FILE_SKIP_EMPTY_LINES));
+        constant!(FILE_APPEND, Object::Integer(FILE_APPEND));
+        constant!(PATHINFO_DIRNAME, Object::Integer(PATHINFO_DIRNAME));
+        constant!(PATHINFO_BASENAME, Object::Integer(PATHINFO_BASENAME));
+        constant!(PATHINFO_EXTENSION, Object::Integer(PATHINFO_EXTENSION));
+        constant!(PATHINFO_FILENAME, Object::Integer(PATHINFO_FILENAME));
+        constant!(PATHINFO_ALL, Object::Integer(PATHINFO_ALL));
+        constant!(SCANDIR_SORT_ASCENDING, Object::Integer(SCANDIR_SORT_ASCENDING));
+        constant!(SCANDIR_SORT_DESCENDING, Object::Integer(SCANDIR_SORT_DESCENDING));
+        constant!(SCANDIR_SORT_NONE, Object::Integer(SCANDIR_SORT_NONE));
+        constant!(GLOB_ERR, Object::Integer(GLOB_ERR));
+        constant!(GLOB_MARK, Object::Integer(GLOB_MARK));
+        constant!(GLOB_NOSORT, Object::Integer(GLOB_NOSORT));
+        constant!(GLOB_NOCHECK, Object::Integer(GLOB_NOCHECK));
+        constant!(GLOB_NOESCAPE, Object::Integer(GLOB_NOESCAPE));
+        constant!(GLOB_BRACE, Object::Integer(GLOB_BRACE));
+        constant!(GLOB_ONLYDIR, Object::Integer(GLOB_ONLYDIR));
+
+        // Streams.
+        constant!(STDIN, standard_stream(STDIN));
+        constant!(STDOUT, standard_stream(STDOUT));
+        constant!(STDERR, standard_stream(STDERR));
+        constant!(SEEK_SET, Object::Integer(SEEK_SET));
+        constant!(SEEK_CUR, Object::Integer(SEEK_CUR));
+        constant!(SEEK_END, Object::Integer(SEEK_END));
+        constant!(LOCK_SH, Object::Integer(LOCK_SH));
+        constant!(LOCK_EX, Object::Integer(LOCK_EX));
+        constant!(LOCK_UN, Object::Integer(LOCK_UN));
+        constant!(LOCK_NB, Object::Integer(LOCK_NB));
+
+        // Random numbers.
+        constant!(MT_RAND_MT19937, Object::Integer(MT_RAND_MT19937));
+        constant!(MT_RAND_PHP, Object::Integer(MT_RAND_PHP));
+
+        // Arrays.
+        constant!(COUNT_NORMAL, Object::Integer(COUNT_NORMAL));
+        constant!(COUNT_RECURSIVE, Object::Integer(COUNT_RECURSIVE));
+        constant!(SORT_REGULAR, Object::Integer(SORT_REGULAR));
+        constant!(SORT_NUMERIC, Object::Integer(SORT_NUMERIC));
+        constant!(SORT_STRING, Object::Integer(SORT_STRING));
+        constant!(SORT_DESC, Object::Integer(SORT_DESC));
+        constant!(SORT_ASC, Object::Integer(SORT_ASC));
+        constant!(SORT_LOCALE_STRING, Object::Integer(SORT_LOCALE_STRING));
+        constant!(SORT_NATURAL, Object::Integer(SORT_NATURAL));
+        constant!(SORT_FLAG_CASE, Object::Integer(SORT_FLAG_CASE));
+        constant!(EXTR_OVERWRITE, Object::Integer(EXTR_OVERWRITE));
+        constant!(EXTR_SKIP, Object::Integer(EXTR_SKIP));
+        constant!(EXTR_PREFIX_SAME, Object::Integer(EXTR_PREFIX_SAME));
+        constant!(EXTR_PREFIX_ALL, Object::Integer(EXTR_PREFIX_ALL));
+        constant!(EXTR_PREFIX_INVALID, Object::Integer(EXTR_PREFIX_INVALID));
+        constant!(EXTR_PREFIX_IF_EXISTS, Object::Integer(EXTR_PREFIX_IF_EXISTS));
+        constant!(EXTR_IF_EXISTS, Object::Integer(EXTR_IF_EXISTS));
+        constant!(ARRAY_FILTER_USE_BOTH, Object::Integer(ARRAY_FILTER_USE_BOTH));
+        constant!(ARRAY_FILTER_USE_KEY, Object::Integer(ARRAY_FILTER_USE_KEY));
+
+        // Strings.
+        constant!(STR_PAD_LEFT, Object::Integer(STR_PAD_LEFT));
+        constant!(STR_PAD_RIGHT, Object::Integer(STR_PAD_RIGHT));
+        constant!(STR_PAD_BOTH, Object::Integer(STR_PAD_BOTH));
+        constant!(ENT_HTML_QUOTE_NONE, Object::Integer(ENT_HTML_QUOTE_NONE));
+        constant!(ENT_HTML_QUOTE_SINGLE, Object::Integer(ENT_HTML_QUOTE_SINGLE));
+        constant!(ENT_HTML_QUOTE_DOUBLE, Object::Integer(ENT_HTML_QUOTE_DOUBLE));
+        constant!(ENT_COMPAT, Object::Integer(ENT_COMPAT));
+        constant!(ENT_QUOTES, Object::Integer(ENT_QUOTES));
+        constant!(ENT_NOQUOTES, Object::Integer(ENT_NOQUOTES));
+        constant!(ENT_IGNORE, Object::Integer(ENT_IGNORE));
+        constant!(ENT_SUBSTITUTE, Object::Integer(ENT_SUBSTITUTE));
+        constant!(ENT_DISALLOWED, Object::Integer(ENT_DISALLOWED));
+        constant!(ENT_HTML401, Object::Integer(ENT_HTML401));
+        constant!(ENT_XML1, Object::Integer(ENT_XML1));
+        constant!(ENT_XHTML, Object::Integer(ENT_XHTML));
+        constant!(ENT_HTML5, Object::Integer(ENT_HTML5));
+
+        // JSON.
+        constant!(JSON_HEX_TAG, Object::Integer(JSON_HEX_TAG));
+        constant!(JSON_HEX_AMP, Object::Integer(JSON_HEX_AMP));
+        constant!(JSON_HEX_APOS, Object::Integer(JSON_HEX_APOS));
+        constant!(JSON_HEX_QUOT, Object::Integer(JSON_HEX_QUOT));
+        constant!(JSON_FORCE_OBJECT, Object::Integer(JSON_FORCE_OBJECT));
+        constant!(JSON_NUMERIC_CHECK, Object::Integer(JSON_NUMERIC_CHECK));
+        constant!(JSON_UNESCAPED_SLASHES, Object::Integer(JSON_UNESCAPED_SLASHES));
+        constant!(JSON_PRETTY_PRINT, Object::Integer(JSON_PRETTY_PRINT));
+        constant!(JSON_UNESCAPED_UNICODE, Object::Integer(JSON_UNESCAPED_UNICODE));
+        constant!(JSON_PARTIAL_OUTPUT_ON_ERROR, Object::Integer(JSON_PARTIAL_OUTPUT_ON_ERROR));
+        constant!(JSON_PRESERVE_ZERO_FRACTION, Object::Integer(JSON_PRESERVE_ZERO_FRACTION));
+        constant!(JSON_UNESCAPED_LINE_TERMINATORS, Object::Integer(JSON_UNESCAPED_LINE_TERMINATORS));
+        constant!(JSON_OBJECT_AS_ARRAY, Object::Integer(JSON_OBJECT_AS_ARRAY));
+        constant!(JSON_BIGINT_AS_STRING, Object::Integer(JSON_BIGINT_AS_STRING));
+        constant!(JSON_INVALID_UTF8_IGNORE, Object::Integer(JSON_INVALID_UTF8_IGNORE));
+        constant!(JSON_INVALID_UTF8_SUBSTITUTE, Object::Integer(JSON_INVALID_UTF8_SUBSTITUTE));
+        constant!(JSON_THROW_ON_ERROR, Object::Integer(JSON_THROW_ON_ERROR));
+        constant!(JSON_ERROR_NONE, Object::Integer(JSON_ERROR_NONE));
+        constant!(JSON_ERROR_DEPTH, Object::Integer(JSON_ERROR_DEPTH));
+        constant!(JSON_ERROR_STATE_MISMATCH, Object::Integer(JSON_ERROR_STATE_MISMATCH));
+        constant!(JSON_ERROR_CTRL_CHAR, Object::Integer(JSON_ERROR_CTRL_CHAR));
+        constant!(JSON_ERROR_SYNTAX, Object::Integer(JSON_ERROR_SYNTAX));
+        constant!(JSON_ERROR_UTF8, Object::Integer(JSON_ERROR_UTF8));
+        constant!(JSON_ERROR_RECURSION, Object::Integer(JSON_ERROR_RECURSION));
+        constant!(JSON_ERROR_INF_OR_NAN, Object::Integer(JSON_ERROR_INF_OR_NAN));
+        constant!(JSON_ERROR_UNSUPPORTED_TYPE, Object::Integer(JSON_ERROR_UNSUPPORTED_TYPE));
+        constant!(JSON_ERROR_INVALID_PROPERTY_NAME, Object::Integer(JSON_ERROR_INVALID_PROPERTY_NAME));
+        constant!(JSON_ERROR_UTF16, Object::Integer(JSON_ERROR_UTF16));
+
+        // Regular expressions.
+        constant!(PREG_PATTERN_ORDER, Object::Integer(PREG_PATTERN_ORDER));
+        constant!(PREG_SET_ORDER, Object::Integer(PREG_SET_ORDER));
+        constant!(PREG_OFFSET_CAPTURE, Object::Integer(PREG_OFFSET_CAPTURE));
+        constant!(PREG_UNMATCHED_AS_NULL, Object::Integer(PREG_UNMATCHED_AS_NULL));
+        constant!(PREG_SPLIT_NO_EMPTY, Object::Integer(PREG_SPLIT_NO_EMPTY));
+        constant!(PREG_SPLIT_DELIM_CAPTURE, Object::Integer(PREG_SPLIT_DELIM_CAPTURE));
+        constant!(PREG_SPLIT_OFFSET_CAPTURE, Object::Integer(PREG_SPLIT_OFFSET_CAPTURE));
+        constant!(PREG_GREP_INVERT, Object::Integer(PREG_GREP_INVERT));
+        constant!(PREG_NO_ERROR, Object::Integer(PREG_NO_ERROR));
+        constant!(PREG_INTERNAL_ERROR, Object::Integer(PREG_INTERNAL_ERROR));
+        constant!(PREG_BACKTRACK_LIMIT_ERROR, Object::Integer(PREG_BACKTRACK_LIMIT_ERROR));
+        constant!(PREG_RECURSION_LIMIT_ERROR, Object::Integer(PREG_RECURSION_LIMIT_ERROR));
+        constant!(PREG_BAD_UTF8_ERROR, Object::Integer(PREG_BAD_UTF8_ERROR));
+        constant!(PREG_BAD_UTF8_OFFSET_ERROR, Object::Integer(PREG_BAD_UTF8_OFFSET_ERROR));
+        constant!(PREG_JIT_STACKLIMIT_ERROR, Object::Integer(PREG_JIT_STACKLIMIT_ERROR));
+
+        // Date and time.
+        constant!(DATE_ATOM, Object::String(DATE_ATOM.into()));
+        constant!(DATE_COOKIE, Object::String(DATE_COOKIE.into()));
+        constant!(DATE_ISO8601, Object::String(DATE_ISO8601.into()));
+        constant!(DATE_RFC822, Object::String(DATE_RFC822.into()));
+        constant!(DATE_RFC850, Object::String(DATE_RFC850.into()));
+        constant!(DATE_RFC1036, Object::String(DATE_RFC1036.into()));
+        constant!(DATE_RFC1123, Object::String(DATE_RFC1123.into()));
+        constant!(DATE_RFC7231, Object::String(DATE_RFC7231.into()));
+        constant!(DATE_RFC2822, Object::String(DATE_RFC2822.into()));
+        constant!(DATE_RFC3339, Object::String(DATE_RFC3339.into()));
+        constant!(DATE_RFC3339_EXTENDED, Object::String(DATE_RFC3339_EXTENDED.into()));
+        constant!(DATE_RSS, Object::String(DATE_RSS.into()));
+        constant!(DATE_W3C, Object::String(DATE_W3C.into()));
+
+        // Hashing.
+        constant!(PASSWORD_DEFAULT, Object::String(PASSWORD_DEFAULT.into()));
+        constant!(PASSWORD_BCRYPT, Object::String(PASSWORD_BCRYPT.into()));
+        constant!(PASSWORD_BCRYPT_DEFAULT_COST, Object::Integer(PASSWORD_BCRYPT_DEFAULT_COST));
+
+        // URLs.
+        constant!(PHP_URL_SCHEME, Object::Integer(PHP_URL_SCHEME));
+        constant!(PHP_URL_HOST, Object::Integer(PHP_URL_HOST));
+        constant!(PHP_URL_PORT, Object::Integer(PHP_URL_PORT));
+        constant!(PHP_URL_USER, Object::Integer(PHP_URL_USER));
+        constant!(PHP_URL_PASS, Object::Integer(PHP_URL_PASS));
+        constant!(PHP_URL_PATH, Object::Integer(PHP_URL_PATH));
+        constant!(PHP_URL_QUERY, Object::Integer(PHP_URL_QUERY));
+        constant!(PHP_URL_FRAGMENT, Object::Integer(PHP_URL_FRAGMENT));
+        constant!(PHP_QUERY_RFC1738, Object::Integer(PHP_QUERY_RFC1738));
+        constant!(PHP_QUERY_RFC3986, Object::Integer(PHP_QUERY_RFC3986));
+
+        // Multibyte strings.
+        constant!(MB_CASE_UPPER, Object::Integer(MB_CASE_UPPER));
+        constant!(MB_CASE_LOWER, Object::Integer(MB_CASE_LOWER));
+        constant!(MB_CASE_TITLE, Object::Integer(MB_CASE_TITLE));
+        constant!(MB_CASE_FOLD, Object::Integer(MB_CASE_FOLD));
+        constant!(MB_CASE_UPPER_SIMPLE, Object::Integer(MB_CASE_UPPER_SIMPLE));
+        constant!(MB_CASE_LOWER_SIMPLE, Object::Integer(MB_CASE_LOWER_SIMPLE));
+        constant!(MB_CASE_TITLE_SIMPLE, Object::Integer(MB_CASE_TITLE_SIMPLE));
+        constant!(MB_CASE_FOLD_SIMPLE, Object::Integer(MB_CASE_FOLD_SIMPLE));
+
+        // Processes.
+        constant!(PHP_VERSION, Object::String(PHP_VERSION.into()));
+        constant!(PHP_MAJOR_VERSION, Object::Integer(PHP_MAJOR_VERSION));
+        constant!(PHP_MINOR_VERSION, Object::Integer(PHP_MINOR_VERSION));
+        constant!(PHP_RELEASE_VERSION, Object::Integer(PHP_RELEASE_VERSION));
+        constant!(PHP_VERSION_ID, Object::Integer(PHP_VERSION_ID));
+        constant!(PHP_EOL, Object::String(PHP_EOL.into()));
+        constant!(PHP_OS, Object::String(php_os().into()));
+        constant!(PHP_OS_FAMILY, Object::String(php_os_family().into()));
 
         s
     }
@@ -63,44 +722,96 @@ impl Globals {
         self.constants.get(&name).cloned()
     }
 
-    pub fn create_user_function(&mut self, name: String, instructions: Vec<Code>) {
-        self.functions.insert(name, Function::User(instructions));
+    /// Registers a stream wrapper for a URL scheme, replacing any existing one.
+    /// Schemes are case-insensitive.
+    pub fn register_wrapper(&mut self, scheme: &str, wrapper: impl Wrapper + 'static) {
+        self.wrappers.register(scheme, Rc::new(wrapper));
+    }
+
+    pub fn get_wrapper(&self, scheme: &str) -> Option<Rc<dyn Wrapper>> {
+        self.wrappers.get(scheme)
+    }
+
+    pub fn get_wrappers(&self) -> Vec<String> {
+        self.wrappers.schemes().cloned().collect()
     }
 
-    pub fn get_user_function(&mut self, name: String) -> Vec<Code> {
-        match self.functions.get(&name) {
-            Some(Function::User(code)) => code.to_vec(),
+    // Function names are case-insensitive in PHP, so they're always stored in lowercase.
+    // Lookups expect a name that's already been through `function_name()`, which the
+    // compiler does once for every call. Constants and variables are case-sensitive.
+    pub fn create_user_function(&mut self, name: &str, parameters: usize, instructions: Vec<Code>) {
+        let function = UserFunction { name: name.into(), parameters, instructions };
+
+        self.functions.insert(function_name(name), Function::User(function));
+    }
+
+    pub fn get_user_function(&mut self, name: &str) -> UserFunction {
+        match self.functions.get(name) {
+            Some(Function::User(function)) => function.clone(),
             _ => panic!("Cannot find user function for name {}", name),
         }
     }
 
-    pub fn is_user_function(&mut self, name: impl Into<String>) -> bool {
-        matches!(self.functions.get(&name.into()), Some(Function::User(..)))
+    pub fn is_user_function(&mut self, name: &str) -> bool {
+        matches!(self.functions.get(name), Some(Function::User(..)))
+    }
+
+    pub fn create_internal_function(&mut self, name: &str, callback: InternalFunctionCallback, references: References) {
+        let internal = InternalFunction { name: name.into(), callback, references };
+
+        self.functions.insert(function_name(name), Function::Internal(internal));
+    }
+
+    /// Methods of internal classes. Both the class and method name are case-insensitive.
+    pub fn create_method(&mut self, class: &str, method: &str, callback: InternalFunctionCallback) {
+        let name = format!("{}::{}", class, method);
+        let internal = InternalFunction { name: name.clone(), callback, references: References::Positions(&[]) };
+
+        self.methods.insert(function_name(&name), internal);
     }
 
-    pub fn create_internal_function(&mut self, name: impl Into<String> + Copy, callback: InternalFunctionCallback) {
-        let internal = InternalFunction { name: name.into(), callback };
+    /// Looks up a method by the class of an object, with a method name that's already
+    /// been through `function_name()`. A constructor is stored as `__construct`, and it
+    /// returns the new object instead of taking one.
+    pub fn get_method(&mut self, class: &str, method: &str) -> Option<InternalFunction> {
+        self.methods.get(&format!("{}::{}", function_name(class), method)).cloned()
+    }
 
-        self.functions.insert(name.into(), Function::Internal(internal));
+    pub fn is_function(&mut self, name: &str) -> bool {
+        self.functions.contains_key(name)
     }
 
-    pub fn get_internal_function(&mut self, name: impl Into<String>) -> InternalFunction {
-        match self.functions.get(&name.into()) {
+    /// The by-reference arguments of an internal function.
+    pub fn get_references(&mut self, name: &str) -> References {
+        match self.functions.get(name) {
+            Some(Function::Internal(i)) => i.references,
+            _ => References::Positions(&[]),
+        }
+    }
+
+    pub fn get_internal_function(&mut self, name: &str) -> InternalFunction {
+        match self.functions.get(name) {
             Some(Function::Internal(i)) => i.clone(),
-            _ => unreachable!()
+            _ => panic!("Call to undefined function {}()", name),
         }
     }
 }
 
+/// The name a function is stored under. PHP only folds ASCII letters, so `ÄBC()` and
+/// `äbc()` are different functions.
+pub fn function_name(name: &str) -> String {
+    name.to_ascii_lowercase()
+}
+
 pub enum Function {
-    User(Vec<Code>),
+    User(UserFunction),
     Internal(InternalFunction)
 }
 
 impl Debug for Function {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
         write!(f, "{}", match self {
-            Function::User(code) => format!("User({:?})", code),
+            Function::User(UserFunction { instructions, .. }) => format!("User({:?})", instructions),
             Function::Internal(InternalFunction { name, .. }) => format!("InternalFunction({})", name),
         })
     }
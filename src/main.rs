@@ -1,5 +1,4 @@
 use std::env::args;
-use std::net::TcpListener;
 
 mod cmd;
 mod token;
@@ -8,6 +7,7 @@ mod compiler;
 mod vm;
 mod object;
 mod globals;
+mod error;
 mod stdlib;
 
 fn main() {
@@ -15,37 +15,31 @@ fn main() {
         cmd::help();
     }
 
-    let command = match args().nth(1) {
-        Some(c) => c,
-        _ => unreachable!()
+    let file = match args().nth(1) {
+        Some(f) => f,
+        None => {
+            eprintln!("Please provide a file path.");
+            std::process::exit(1);
+        }
     };
 
-    match command.as_str() {
-        _ => {
-            let file = match args().nth(1) {
-                Some(f) => f,
-                None => {
-                    eprintln!("Please provide a file path.");
-                    std::process::exit(1);
-                }
-            };
+    let contents = match std::fs::read_to_string(file) {
+        Ok(s) => s,
+        Err(_) => {
+            eprintln!("Unable to open file.");
+            std::process::exit(1);
+        }
+    };
 
-            let contents = match std::fs::read_to_string(file) {
-                Ok(s) => s,
-                Err(_) => {
-                    eprintln!("Unable to open file.");
-                    std::process::exit(1);
-                }
-            };
+    let tokens = token::generate(&contents);
+    let ast = parser::parse(tokens);
+    let (constants, code, globals) = compiler::compile(ast);
 
-            let tokens = token::generate(&contents);
-            let ast = parser::parse(tokens);
-            let (constants, code, globals) = compiler::compile(ast);
+    #[cfg(debug_assertions)]
+    dbg!(&constants, &code, &globals);
+    
+    let arguments = std::env::args_os().skip(1).map(|argument| argument.as_encoded_bytes().into()).collect();
+    let status = vm::run(constants, code, globals, arguments);
 
-            #[cfg(debug_assertions)]
-            dbg!(&constants, &code, &globals);
-            
-            vm::run(constants, code, globals);
-        },
-    }
+    std::process::exit(status);
 }
\ No newline at end of file
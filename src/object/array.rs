@@ -0,0 +1,188 @@
+use std::fmt::{Display, Formatter, Result};
+use std::iter::FromIterator;
+use indexmap::IndexMap;
+use indexmap::map::{Iter, IntoIter};
+use super::{Object, ByteString};
+use crate::error;
+
+/// An array key. PHP only allows integers and strings as keys, and strings that
+/// look like canonical integers (`"12"` but not `"012"`) are stored as integers.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Key {
+    Integer(i64),
+    String(ByteString),
+}
+
+impl Key {
+    pub fn from_object(object: &Object) -> Self {
+        match object {
+            Object::Integer(i) => Key::Integer(*i),
+            Object::Float(f) => Key::Integer(*f as i64),
+            Object::True => Key::Integer(1),
+            Object::False => Key::Integer(0),
+            Object::Null => Key::String(ByteString::new()),
+            Object::String(s) => Key::from(s.as_bytes()),
+            Object::Resource(resource) => {
+                let id = resource.borrow().id as i64;
+
+                error::warning(format!("Resource ID#{} used as offset, casting to integer ({})", id, id));
+
+                Key::Integer(id)
+            },
+            Object::Array(..) | Object::Instance(..) => error::throw("TypeError", "Illegal offset type"),
+        }
+    }
+
+    pub fn to_object(&self) -> Object {
+        match self {
+            Key::Integer(i) => Object::Integer(*i),
+            Key::String(s) => Object::String(s.clone()),
+        }
+    }
+}
+
+impl From<&[u8]> for Key {
+    fn from(s: &[u8]) -> Self {
+        let digits = s.strip_prefix(b"-").unwrap_or(s);
+        let canonical = ! digits.is_empty()
+            && digits.iter().all(|b| b.is_ascii_digit())
+            && (digits == b"0" || ! digits.starts_with(b"0"))
+            && s != b"-0";
+
+        // Canonical integers are ASCII digits, so they're always valid UTF-8.
+        match std::str::from_utf8(s).ok().filter(|_| canonical).and_then(|s| s.parse().ok()) {
+            Some(i) => Key::Integer(i),
+            None => Key::String(s.into()),
+        }
+    }
+}
+
+impl From<&str> for Key {
+    fn from(s: &str) -> Self {
+        Key::from(s.as_bytes())
+    }
+}
+
+impl From<i64> for Key {
+    fn from(i: i64) -> Self {
+        Key::Integer(i)
+    }
+}
+
+impl Display for Key {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match self {
+            Key::Integer(i) => write!(f, "{}", i),
+            Key::String(s) => write!(f, "{}", s),
+        }
+    }
+}
+
+/// An ordered hash map, the same as PHP's arrays. Items keep their insertion order
+/// and `push` uses the next integer after the largest integer key ever inserted.
+#[derive(Debug, Clone, Default)]
+pub struct Array {
+    items: IndexMap<Key, Object>,
+    next_index: i64,
+}
+
+impl Array {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn get(&self, key: &Key) -> Option<&Object> {
+        self.items.get(key)
+    }
+
+    pub fn contains_key(&self, key: &Key) -> bool {
+        self.items.contains_key(key)
+    }
+
+    pub fn insert(&mut self, key: Key, value: Object) {
+        if let Key::Integer(i) = key {
+            if i >= self.next_index {
+                self.next_index = i.saturating_add(1);
+            }
+        }
+
+        self.items.insert(key, value);
+    }
+
+    pub fn push(&mut self, value: Object) {
+        self.insert(Key::Integer(self.next_index), value);
+    }
+
+    /// Removes the last item. Like PHP, the next index is wound back if the item
+    /// was the one most recently pushed.
+    pub fn pop(&mut self) -> Option<(Key, Object)> {
+        let (key, value) = self.items.pop()?;
+
+        if key == Key::Integer(self.next_index.wrapping_sub(1)) {
+            self.next_index -= 1;
+        }
+
+        Some((key, value))
+    }
+
+    pub fn first(&self) -> Option<(&Key, &Object)> {
+        self.items.first()
+    }
+
+    pub fn last(&self) -> Option<(&Key, &Object)> {
+        self.items.last()
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &Key> {
+        self.items.keys()
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &Object> {
+        self.items.values()
+    }
+
+    pub fn iter(&self) -> Iter<'_, Key, Object> {
+        self.items.iter()
+    }
+}
+
+impl IntoIterator for Array {
+    type Item = (Key, Object);
+    type IntoIter = IntoIter<Key, Object>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.items.into_iter()
+    }
+}
+
+impl FromIterator<Object> for Array {
+    fn from_iter<I: IntoIterator<Item = Object>>(iter: I) -> Self {
+        let mut array = Array::new();
+
+        for value in iter {
+            array.push(value);
+        }
+
+        array
+    }
+}
+
+impl FromIterator<(Key, Object)> for Array {
+    fn from_iter<I: IntoIterator<Item = (Key, Object)>>(iter: I) -> Self {
+        let mut array = Array::new();
+
+        for (key, value) in iter {
+            array.insert(key, value);
+        }
+
+        array
+    }
+}
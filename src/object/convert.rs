@@ -0,0 +1,532 @@
+use std::cmp::Ordering;
+use std::rc::Rc;
+use super::{Object, Array, Key, ByteString, new_instance};
+use crate::error;
+
+// PHP 8's type juggling rules. The VM's operators and internal functions go through
+// here so that values are converted and compared the same way everywhere.
+
+/// Integer arithmetic that overflows is promoted to a float, the same as PHP.
+macro_rules! integer_op {
+    ($lhs:expr, $checked:ident, $op:tt, $rhs:expr) => {
+        match $lhs.$checked($rhs) {
+            Some(i) => Object::Integer(i),
+            None => Object::Float($lhs as f64 $op $rhs as f64),
+        }
+    };
+}
+
+macro_rules! arithmetic {
+    ($lhs:expr, $op:tt, $checked:ident, $rhs:expr) => {
+        match operands($lhs, stringify!($op), $rhs) {
+            (Object::Integer(l), Object::Integer(r)) => integer_op!(l, $checked, $op, r),
+            (l, r) => Object::Float(number(&l) $op number(&r)),
+        }
+    };
+}
+
+/// The `precision` ini setting, used when converting floats to strings.
+pub const PRECISION: i32 = 14;
+
+/// The `serialize_precision` ini setting, used by `var_dump()` and friends. `-1` means
+/// the shortest representation that round-trips.
+pub const SERIALIZE_PRECISION: i32 = -1;
+
+/// Formats a float the same way as PHP's `%G`-style conversion, switching to
+/// exponential notation for very large and very small numbers.
+pub fn format_float(value: f64, precision: i32) -> String {
+    if value.is_nan() {
+        return "NAN".to_string();
+    }
+
+    if value.is_infinite() {
+        return if value > 0.0 { "INF" } else { "-INF" }.to_string();
+    }
+
+    gcvt(value, precision, 'E')
+}
+
+/// A port of PHP's `php_gcvt()`, which is shared by float conversion and `%g` in `sprintf()`.
+/// A precision of zero or less gives the shortest representation that round-trips.
+pub fn gcvt(value: f64, precision: i32, exponent_char: char) -> String {
+    let (precision, formatted) = match precision {
+        p if p > 0 => (p, format!("{:.*e}", p as usize - 1, value)),
+        _ => (17, format!("{:e}", value)),
+    };
+
+    // Rust gives us `-1.2345e-7`, which we split into the significant digits and the
+    // position of the decimal point relative to them.
+    let (mantissa, exponent) = formatted.split_once('e').unwrap();
+    let negative = mantissa.starts_with('-');
+    let digits: String = mantissa.chars().filter(|c| c.is_ascii_digit()).collect();
+    let digits = match digits.trim_end_matches('0') {
+        "" => "0",
+        d => d,
+    };
+    let exponent: i32 = exponent.parse().unwrap();
+    let point = if digits == "0" { 1 } else { exponent + 1 };
+
+    let mut buffer = String::new();
+
+    if negative {
+        buffer.push('-');
+    }
+
+    if point < -3 || point > precision {
+        buffer.push_str(&digits[..1]);
+        buffer.push('.');
+        buffer.push_str(if digits.len() > 1 { &digits[1..] } else { "0" });
+        buffer.push_str(&format!("{}{}{}", exponent_char, if point - 1 < 0 { '-' } else { '+' }, (point - 1).abs()));
+    } else if point <= 0 {
+        buffer.push_str("0.");
+        buffer.push_str(&"0".repeat(-point as usize));
+        buffer.push_str(digits);
+    } else {
+        let point = point as usize;
+
+        if digits.len() > point {
+            buffer.push_str(&digits[..point]);
+            buffer.push('.');
+            buffer.push_str(&digits[point..]);
+        } else {
+            buffer.push_str(digits);
+            buffer.push_str(&"0".repeat(point - digits.len()));
+        }
+    }
+
+    buffer
+}
+
+/// Converts a value to a string for `echo`, concatenation and string arguments.
+pub fn to_bytes(object: &Object) -> ByteString {
+    match object {
+        Object::String(s) => return s.clone(),
+        Object::Array(..) => error::warning("Array to string conversion"),
+        Object::Instance(instance) => error::throw("Error", format!("Object of class {} could not be converted to string", instance.borrow().class)),
+        _ => (),
+    };
+
+    object.to_string().into()
+}
+
+/// Converts a value to a string for arguments that are names, formats and other text,
+/// rather than data. Anything that isn't valid UTF-8 is replaced.
+pub fn to_string(object: &Object) -> String {
+    match object {
+        Object::String(s) => s.to_string_lossy().into_owned(),
+        object => to_bytes(object).to_string_lossy().into_owned(),
+    }
+}
+
+/// Converts a float to an integer. Out of range values wrap around the same way
+/// as PHP on 64-bit platforms, and `NAN` and `INF` become 0.
+pub fn float_to_int(f: f64) -> i64 {
+    const TWO_POW_63: f64 = 9223372036854775808.0;
+    const TWO_POW_64: f64 = 18446744073709551616.0;
+
+    if ! f.is_finite() {
+        return 0;
+    }
+
+    if (-TWO_POW_63..TWO_POW_63).contains(&f) {
+        return f as i64;
+    }
+
+    let mut modulus = f.trunc() % TWO_POW_64;
+
+    if modulus < 0.0 {
+        modulus += TWO_POW_64;
+    }
+
+    if modulus >= TWO_POW_63 {
+        modulus -= TWO_POW_64;
+    }
+
+    modulus as i64
+}
+
+/// The `(int)` cast.
+pub fn to_int(object: &Object) -> i64 {
+    match object {
+        Object::Integer(i) => *i,
+        Object::Float(f) => float_to_int(*f),
+        Object::True => 1,
+        Object::False | Object::Null => 0,
+        Object::String(s) => match parse_numeric(s) {
+            Some((Object::Integer(i), _)) => i,
+            Some((Object::Float(f), _)) => float_to_int(f),
+            _ => 0,
+        },
+        Object::Array(items) => ! items.borrow().is_empty() as i64,
+        Object::Instance(instance) => {
+            error::warning(format!("Object of class {} could not be converted to int", instance.borrow().class));
+
+            1
+        },
+        Object::Resource(resource) => resource.borrow().id as i64,
+    }
+}
+
+/// The `(float)` cast.
+pub fn to_float(object: &Object) -> f64 {
+    match object {
+        Object::Float(f) => *f,
+        Object::String(s) => match parse_numeric(s) {
+            Some((n, _)) => number(&n),
+            None => 0.0,
+        },
+        Object::Instance(instance) => {
+            error::warning(format!("Object of class {} could not be converted to float", instance.borrow().class));
+
+            1.0
+        },
+        _ => to_int(object) as f64,
+    }
+}
+
+/// The `(array)` cast.
+pub fn to_array(object: &Object) -> Object {
+    let array = match object {
+        Object::Array(..) => return object.clone(),
+        Object::Null => Array::new(),
+        Object::Instance(instance) => instance.borrow().properties.clone(),
+        _ => vec![object.clone()].into_iter().collect(),
+    };
+
+    Object::from_array(array)
+}
+
+/// The `(object)` cast. Anything that isn't already an object becomes a `stdClass`.
+pub fn to_instance(object: &Object) -> Object {
+    let mut properties = Array::new();
+
+    match object {
+        Object::Instance(..) => return object.clone(),
+        Object::Null => (),
+        Object::Array(items) => properties = items.borrow().clone(),
+        _ => properties.insert(Key::String("scalar".into()), object.clone()),
+    };
+
+    new_instance("stdClass", properties)
+}
+
+/// Parses an integer in the given base the same way as C's `strtol()`, which is what
+/// `intval()` uses for non-decimal bases. A base of 0 detects the base from the prefix.
+pub fn parse_int(s: &[u8], base: u32) -> i64 {
+    let start = s.iter().position(|b| ! is_whitespace(*b)).unwrap_or(s.len());
+    let s = &s[start..];
+    let (negative, s) = match s.first() {
+        Some(b'-') => (true, &s[1..]),
+        Some(b'+') => (false, &s[1..]),
+        _ => (false, s),
+    };
+
+    let lower = s.to_ascii_lowercase();
+    let (base, digits) = match base {
+        0 if lower.starts_with(b"0x") => (16, &s[2..]),
+        0 if lower.starts_with(b"0b") => (2, &s[2..]),
+        0 if lower.starts_with(b"0o") => (8, &s[2..]),
+        0 if lower.starts_with(b"0") => (8, s),
+        0 => (10, s),
+        16 if lower.starts_with(b"0x") => (16, &s[2..]),
+        8 if lower.starts_with(b"0o") => (8, &s[2..]),
+        2 if lower.starts_with(b"0b") => (2, &s[2..]),
+        base => (base, s),
+    };
+
+    let mut value: i64 = 0;
+
+    for c in digits.iter() {
+        let digit = match (*c as char).to_digit(base) {
+            Some(d) => d as i64,
+            None => break,
+        };
+
+        value = match value.checked_mul(base as i64).and_then(|v| if negative { v.checked_sub(digit) } else { v.checked_add(digit) }) {
+            Some(v) => v,
+            None => return if negative { i64::MIN } else { i64::MAX },
+        };
+    }
+
+    value
+}
+
+fn is_whitespace(b: u8) -> bool {
+    matches!(b, b' ' | b'\t' | b'\n' | b'\r' | 0x0b | 0x0c)
+}
+
+/// Parses the numeric prefix of a string, returning the number and whether anything
+/// other than whitespace came after it (a "leading-numeric" string like `"10 apples"`).
+/// Strings that don't start with a number return `None`.
+pub fn parse_numeric(bytes: &[u8]) -> Option<(Object, bool)> {
+    let digits = |mut i: usize| {
+        while i < bytes.len() && bytes[i].is_ascii_digit() {
+            i += 1;
+        }
+
+        i
+    };
+
+    let mut i = 0;
+
+    while i < bytes.len() && is_whitespace(bytes[i]) {
+        i += 1;
+    }
+
+    let start = i;
+
+    if i < bytes.len() && (bytes[i] == b'+' || bytes[i] == b'-') {
+        i += 1;
+    }
+
+    let integer_end = digits(i);
+    let mut seen_digits = integer_end > i;
+    let mut is_float = false;
+
+    i = integer_end;
+
+    if i < bytes.len() && bytes[i] == b'.' {
+        let fraction_end = digits(i + 1);
+
+        if seen_digits || fraction_end > i + 1 {
+            seen_digits = true;
+            is_float = true;
+            i = fraction_end;
+        }
+    }
+
+    if ! seen_digits {
+        return None;
+    }
+
+    if i < bytes.len() && (bytes[i] == b'e' || bytes[i] == b'E') {
+        let mut j = i + 1;
+
+        if j < bytes.len() && (bytes[j] == b'+' || bytes[j] == b'-') {
+            j += 1;
+        }
+
+        let exponent_end = digits(j);
+
+        if exponent_end > j {
+            is_float = true;
+            i = exponent_end;
+        }
+    }
+
+    // Everything that was matched is ASCII.
+    let number = std::str::from_utf8(&bytes[start..i]).unwrap();
+
+    while i < bytes.len() && is_whitespace(bytes[i]) {
+        i += 1;
+    }
+
+    let value = match number.parse() {
+        Ok(n) if ! is_float => Object::Integer(n),
+        _ => Object::Float(number.parse().unwrap_or(0.0)),
+    };
+
+    Some((value, i < bytes.len()))
+}
+
+fn number(object: &Object) -> f64 {
+    match object {
+        Object::Integer(i) => *i as f64,
+        Object::Float(f) => *f,
+        _ => unreachable!(),
+    }
+}
+
+/// Converts an operand of an arithmetic operator to an integer or float.
+fn to_operand(object: &Object) -> Option<Object> {
+    match object {
+        Object::Null | Object::False => Some(Object::Integer(0)),
+        Object::True => Some(Object::Integer(1)),
+        Object::Integer(_) | Object::Float(_) => Some(object.clone()),
+        Object::String(s) => {
+            let (value, trailing) = parse_numeric(s)?;
+
+            if trailing {
+                error::warning("A non-numeric value encountered");
+            }
+
+            Some(value)
+        },
+        Object::Array(_) | Object::Instance(_) | Object::Resource(_) => None,
+    }
+}
+
+/// Whether the string is entirely numeric, allowing surrounding whitespace.
+pub fn is_numeric(s: &[u8]) -> bool {
+    matches!(parse_numeric(s), Some((_, false)))
+}
+
+fn operands(lhs: &Object, op: &str, rhs: &Object) -> (Object, Object) {
+    let operands = match to_operand(lhs) {
+        Some(l) => to_operand(rhs).map(|r| (l, r)),
+        None => None,
+    };
+
+    match operands {
+        Some(operands) => operands,
+        None => error::throw("TypeError", format!("Unsupported operand types: {} {} {}", lhs.type_name(), op, rhs.type_name())),
+    }
+}
+
+pub fn add(lhs: &Object, rhs: &Object) -> Object {
+    // Adding two arrays is a union, keeping the left-hand value for duplicate keys.
+    if let (Object::Array(l), Object::Array(r)) = (lhs, rhs) {
+        let mut union = l.borrow().clone();
+
+        for (key, value) in r.borrow().iter() {
+            if ! union.contains_key(key) {
+                union.insert(key.clone(), value.clone());
+            }
+        }
+
+        return Object::from_array(union);
+    }
+
+    arithmetic!(lhs, +, checked_add, rhs)
+}
+
+pub fn subtract(lhs: &Object, rhs: &Object) -> Object {
+    arithmetic!(lhs, -, checked_sub, rhs)
+}
+
+pub fn multiply(lhs: &Object, rhs: &Object) -> Object {
+    arithmetic!(lhs, *, checked_mul, rhs)
+}
+
+pub fn divide(lhs: &Object, rhs: &Object) -> Object {
+    let (l, r) = operands(lhs, "/", rhs);
+
+    if number(&r) == 0.0 {
+        error::throw("DivisionByZeroError", "Division by zero");
+    }
+
+    match (l, r) {
+        // Integer division only produces an integer when there's no remainder.
+        (Object::Integer(l), Object::Integer(r)) => match (l.checked_rem(r), l.checked_div(r)) {
+            (Some(0), Some(i)) => Object::Integer(i),
+            _ => Object::Float(l as f64 / r as f64),
+        },
+        (l, r) => Object::Float(number(&l) / number(&r)),
+    }
+}
+
+/// The `.` operator.
+pub fn concat(lhs: &Object, rhs: &Object) -> Object {
+    let mut string = to_bytes(lhs);
+    string.extend_from_slice(&to_bytes(rhs));
+
+    Object::String(string)
+}
+
+/// The `%` operator, which always works on integers.
+pub fn modulo(lhs: &Object, rhs: &Object) -> Object {
+    let (l, r) = operands(lhs, "%", rhs);
+    let (l, r) = (to_int(&l), to_int(&r));
+
+    if r == 0 {
+        error::throw("DivisionByZeroError", "Modulo by zero");
+    }
+
+    // `PHP_INT_MIN % -1` would overflow, but the answer is always zero.
+    Object::Integer(l.checked_rem(r).unwrap_or(0))
+}
+
+fn compare_floats(lhs: f64, rhs: f64) -> Ordering {
+    // `NAN` is uncomparable, which PHP treats as "greater" in every direction.
+    lhs.partial_cmp(&rhs).unwrap_or(Ordering::Greater)
+}
+
+fn compare_strings(lhs: &[u8], rhs: &[u8]) -> Ordering {
+    if lhs == rhs {
+        return Ordering::Equal;
+    }
+
+    match (parse_numeric(lhs), parse_numeric(rhs)) {
+        (Some((l, false)), Some((r, false))) => compare(&l, &r),
+        _ => lhs.cmp(rhs),
+    }
+}
+
+fn compare_number_to_string(number: &Object, string: &[u8]) -> Ordering {
+    // PHP 8 only compares numerically when the string is numeric, otherwise the
+    // number is converted to a string instead.
+    match parse_numeric(string) {
+        Some((n, false)) => compare(number, &n),
+        _ => number.to_string().as_bytes().cmp(string),
+    }
+}
+
+fn compare_arrays(lhs: &Array, rhs: &Array) -> Ordering {
+    if lhs.len() != rhs.len() {
+        return lhs.len().cmp(&rhs.len());
+    }
+
+    for (key, value) in lhs.iter() {
+        match rhs.get(key) {
+            Some(other) => match compare(value, other) {
+                Ordering::Equal => continue,
+                ordering => return ordering,
+            },
+            None => return Ordering::Greater,
+        }
+    }
+
+    Ordering::Equal
+}
+
+/// Compares two values with PHP 8's loose comparison rules, the same as `<=>`.
+pub fn compare(lhs: &Object, rhs: &Object) -> Ordering {
+    match (lhs, rhs) {
+        (Object::Integer(l), Object::Integer(r)) => l.cmp(r),
+        (Object::Integer(_) | Object::Float(_), Object::Integer(_) | Object::Float(_)) => compare_floats(number(lhs), number(rhs)),
+        (Object::String(l), Object::String(r)) => compare_strings(l, r),
+        (Object::Null, Object::String(s)) => 0.cmp(&s.len()),
+        (Object::String(s), Object::Null) => s.len().cmp(&0),
+        (Object::Integer(_) | Object::Float(_), Object::String(s)) => compare_number_to_string(lhs, s),
+        (Object::String(s), Object::Integer(_) | Object::Float(_)) => compare_number_to_string(rhs, s).reverse(),
+        (Object::Array(l), Object::Array(r)) => compare_arrays(&l.borrow(), &r.borrow()),
+        (Object::Null | Object::True | Object::False, _) | (_, Object::Null | Object::True | Object::False) => lhs.to_bool().cmp(&rhs.to_bool()),
+        (Object::Instance(l), Object::Instance(r)) => {
+            let (l, r) = (l.borrow(), r.borrow());
+
+            match l.class == r.class {
+                true => compare_arrays(&l.properties, &r.properties),
+                false => Ordering::Greater,
+            }
+        },
+        (Object::Instance(_), _) => Ordering::Greater,
+        (_, Object::Instance(_)) => Ordering::Less,
+        (Object::Resource(l), _) => compare(&Object::Integer(l.borrow().id as i64), rhs),
+        (_, Object::Resource(r)) => compare(lhs, &Object::Integer(r.borrow().id as i64)),
+        (Object::Array(_), _) => Ordering::Greater,
+        (_, Object::Array(_)) => Ordering::Less,
+    }
+}
+
+/// The `==` operator.
+pub fn loose_equals(lhs: &Object, rhs: &Object) -> bool {
+    compare(lhs, rhs) == Ordering::Equal
+}
+
+/// The `===` operator.
+pub fn strict_equals(lhs: &Object, rhs: &Object) -> bool {
+    match (lhs, rhs) {
+        (Object::Integer(l), Object::Integer(r)) => l == r,
+        (Object::Float(l), Object::Float(r)) => l == r,
+        (Object::String(l), Object::String(r)) => l == r,
+        (Object::True, Object::True) | (Object::False, Object::False) | (Object::Null, Object::Null) => true,
+        (Object::Array(l), Object::Array(r)) => {
+            let (l, r) = (l.borrow(), r.borrow());
+
+            l.len() == r.len() && l.iter().zip(r.iter()).all(|((lk, lv), (rk, rv))| lk == rk && strict_equals(lv, rv))
+        },
+        (Object::Instance(l), Object::Instance(r)) => Rc::ptr_eq(l, r),
+        (Object::Resource(l), Object::Resource(r)) => Rc::ptr_eq(l, r),
+        _ => false,
+    }
+}
@@ -1,21 +1,70 @@
 use std::fmt::{Display, Formatter, Result};
-use std::collections::HashMap;
 use std::rc::Rc;
 use std::cell::RefCell;
+use std::sync::atomic::{AtomicUsize, Ordering};
+pub use array::{Array, Key};
+pub use string::ByteString;
+
+mod array;
+mod string;
+pub mod convert;
 
 #[derive(Debug, Clone)]
 pub enum Object {
-    String(String),
+    String(ByteString),
     Integer(i64),
     Float(f64),
     True,
     False,
     Null,
-    Array(Rc<RefCell<HashMap<String, Object>>>),
+    Array(Rc<RefCell<Array>>),
+    Instance(Rc<RefCell<Instance>>),
+    Resource(Rc<RefCell<Resource>>),
+}
+
+/// An instance of a class. There are no userland classes yet, so these are
+/// only created internally (e.g. `stdClass` from an `(object)` cast).
+#[derive(Debug)]
+pub struct Instance {
+    pub id: usize,
+    pub class: String,
+    pub properties: Array,
+}
+
+static NEXT_INSTANCE_ID: AtomicUsize = AtomicUsize::new(1);
+
+/// A handle to something that lives outside of the VM, like an open file. The
+/// underlying value is kept in a table on the `Machine`, keyed by the resource's id,
+/// and closing it changes the type to "Unknown" for every copy of the handle.
+#[derive(Debug)]
+pub struct Resource {
+    pub id: usize,
+    pub kind: String,
 }
 
+// STDIN, STDOUT and STDERR always take the first three ids.
+static NEXT_RESOURCE_ID: AtomicUsize = AtomicUsize::new(4);
+
 pub fn new_array() -> Object {
-    Object::Array(Rc::new(RefCell::new(HashMap::default())))
+    Object::Array(Rc::new(RefCell::new(Array::new())))
+}
+
+pub fn new_instance(class: impl Into<String>, properties: Array) -> Object {
+    let id = NEXT_INSTANCE_ID.fetch_add(1, Ordering::Relaxed);
+
+    Object::Instance(Rc::new(RefCell::new(Instance { id, class: class.into(), properties })))
+}
+
+pub fn new_resource(kind: impl Into<String>) -> Object {
+    let id = NEXT_RESOURCE_ID.fetch_add(1, Ordering::Relaxed);
+
+    Object::Resource(Rc::new(RefCell::new(Resource { id, kind: kind.into() })))
+}
+
+impl Resource {
+    pub fn is_closed(&self) -> bool {
+        self.kind == "Unknown"
+    }
 }
 
 impl Object {
@@ -27,6 +76,32 @@ impl Object {
         matches!(self, Object::Array(..))
     }
 
+    pub fn is_instance(&self) -> bool {
+        matches!(self, Object::Instance(..))
+    }
+
+    /// The type name used in error messages, e.g. "Unsupported operand types: string + int".
+    /// This is the same as `get_debug_type()`.
+    pub fn type_name(&self) -> String {
+        match self {
+            Object::String(..) => "string".into(),
+            Object::Integer(..) => "int".into(),
+            Object::Float(..) => "float".into(),
+            Object::True | Object::False => "bool".into(),
+            Object::Null => "null".into(),
+            Object::Array(..) => "array".into(),
+            Object::Instance(instance) => instance.borrow().class.clone(),
+            Object::Resource(resource) => match resource.borrow().is_closed() {
+                true => "resource (closed)".into(),
+                false => format!("resource ({})", resource.borrow().kind),
+            },
+        }
+    }
+
+    pub fn from_array(array: Array) -> Self {
+        Self::Array(Rc::new(RefCell::new(array)))
+    }
+
     pub fn from_bool(b: bool) -> Self {
         match b {
             true => Self::True,
@@ -38,45 +113,99 @@ impl Object {
         match self {
             Object::True => true,
             Object::False => false,
-            Object::Float(f) => f > &0.0,
-            Object::Integer(i) => i > &0,
-            Object::String(s) => ! s.is_empty(),
+            Object::Float(f) => *f != 0.0,
+            Object::Integer(i) => *i != 0,
+            Object::String(s) => ! s.is_empty() && s != "0",
             Object::Null => false,
-            _ => todo!("to_bool: {:?}", self),
+            Object::Array(items) => ! items.borrow().is_empty(),
+            Object::Instance(..) | Object::Resource(..) => true,
         }
     }
 
-    pub fn to_hash(&self) -> &Rc<RefCell<HashMap<String, Object>>> {
+    pub fn to_hash(&self) -> &Rc<RefCell<Array>> {
         match self {
             Object::Array(items) => items,
             _ => unreachable!()
         }
     }
 
-    pub fn dump(&self) -> String {
+    /// The output of `var_dump()`, without the trailing newline.
+    pub fn dump(&self) -> ByteString {
+        let mut buffer = ByteString::new();
+
+        self.dump_into(&mut buffer, 1, &mut Vec::new());
+        buffer.pop();
+        buffer
+    }
+
+    /// A port of `php_var_dump()`. Nested values are indented by two spaces a level,
+    /// and an object that's already being dumped is printed as `*RECURSION*`.
+    fn dump_into(&self, buffer: &mut ByteString, level: usize, seen: &mut Vec<usize>) {
+        if level > 1 {
+            buffer.push_str(&" ".repeat(level - 1));
+        }
+
         match self {
-            Object::Integer(i) => format!("int({})", i),
-            Object::Float(f) => format!("double({})", f),
-            Object::String(s) => format!("string({}) \"{}\"", s.len(), s),
-            Object::True => format!("bool(true)"),
-            Object::False => format!("bool(false)"),
-            Object::Null => format!("NULL"),
+            Object::Integer(i) => buffer.push_str(&format!("int({})\n", i)),
+            Object::Float(f) => buffer.push_str(&format!("float({})\n", convert::format_float(*f, convert::SERIALIZE_PRECISION))),
+            Object::String(s) => {
+                buffer.push_str(&format!("string({}) \"", s.len()));
+                buffer.extend_from_slice(s);
+                buffer.push_str("\"\n");
+            },
+            Object::True => buffer.push_str("bool(true)\n"),
+            Object::False => buffer.push_str("bool(false)\n"),
+            Object::Null => buffer.push_str("NULL\n"),
             Object::Array(items) => {
                 let items = items.borrow();
-                let items = items.iter();
-                let mut buffer: String = format!("array({}) {{\n", items.len());
-                
-                // TODO: Output keys here too.
-                for (_, value) in items {
-                    buffer.push_str(&format!("  {}\n", value.dump()));
+
+                buffer.push_str(&format!("array({}) {{\n", items.len()));
+                Self::dump_elements(buffer, &items, level, seen);
+            },
+            Object::Instance(instance) => {
+                let instance = instance.borrow();
+
+                if seen.contains(&instance.id) {
+                    buffer.push_str("*RECURSION*\n");
+                    return;
                 }
 
-                buffer.push_str("}");
-                buffer
+                buffer.push_str(&format!("object({})#{} ({}) {{\n", instance.class, instance.id, instance.properties.len()));
+
+                seen.push(instance.id);
+                Self::dump_elements(buffer, &instance.properties, level, seen);
+                seen.pop();
+            },
+            Object::Resource(resource) => {
+                let resource = resource.borrow();
+
+                buffer.push_str(&format!("resource({}) of type ({})\n", resource.id, resource.kind));
             },
-            _ => todo!()
         }
     }
+
+    fn dump_elements(buffer: &mut ByteString, items: &Array, level: usize, seen: &mut Vec<usize>) {
+        for (key, value) in items.iter() {
+            buffer.push_str(&" ".repeat(level + 1));
+
+            match key {
+                Key::Integer(i) => buffer.push_str(&format!("[{}]=>\n", i)),
+                Key::String(s) => {
+                    buffer.push_str("[\"");
+                    buffer.extend_from_slice(s);
+                    buffer.push_str("\"]=>\n");
+                },
+            }
+
+            value.dump_into(buffer, level + 2, seen);
+        }
+
+        if level > 1 {
+            buffer.push_str(&" ".repeat(level - 1));
+        }
+
+        buffer.push_str("}\n");
+    }
 }
 
 impl Display for Object {
@@ -84,10 +213,12 @@ impl Display for Object {
         write!(f, "{}", match self {
             Object::String(s) => s.to_string(),
             Object::Integer(i) => i.to_string(),
-            Object::Float(f) => f.to_string(),
+            Object::Float(f) => convert::format_float(*f, convert::PRECISION),
             Object::Null | Object::False => "".to_string(),
             Object::True => "1".to_string(),
-            _ => todo!("Display: {:?}", self),
+            Object::Array(..) => "Array".to_string(),
+            Object::Instance(instance) => format!("Object({})", instance.borrow().class),
+            Object::Resource(resource) => format!("Resource id #{}", resource.borrow().id),
         })
     }
 }
\ No newline at end of file
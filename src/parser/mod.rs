@@ -1,4 +1,5 @@
-use crate::token::Token;
+use crate::token::{Token, Number};
+use crate::error;
 use std::slice::Iter;
 
 #[derive(Debug)]
@@ -24,13 +25,31 @@ pub enum Expression {
     True,
     False,
     Null,
-    Array(Vec<Expression>),
+    // <key>, <value>
+    Array(Vec<(Option<Expression>, Expression)>),
     Infix(Box<Expression>, Op, Box<Expression>),
     Assign(Box<Expression>, Box<Expression>),
     Call(String, Vec<Expression>),
     Variable(String),
     Identifier(String),
     Index(Box<Expression>, Box<Expression>),
+    Not(Box<Expression>),
+    Cast(Cast, Box<Expression>),
+    Exit(Option<Box<Expression>>),
+    New(String, Vec<Expression>),
+    // <object>, <method>, <args>
+    MethodCall(Box<Expression>, String, Vec<Expression>),
+    Property(Box<Expression>, String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Cast {
+    Int,
+    Float,
+    String,
+    Bool,
+    Array,
+    Object,
 }
 
 struct Parser<'p> {
@@ -258,16 +277,105 @@ impl<'p> Parser<'p> {
 
                 Expression::String(string)
             },
-            Token::Integer(i) => {
+            // Backticks are shorthand for calling `shell_exec()`.
+            Token::Backtick(s) => {
+                self.read();
+
+                let command = s[1..s.len() - 1].to_string();
+
+                Expression::Call("shell_exec".into(), vec![Expression::String(command)])
+            },
+            Token::Exit => {
+                self.read();
+
+                // The parentheses and status are both optional.
+                let status = if self.current == Token::LeftParen {
+                    self.read();
+
+                    let status = if self.current == Token::RightParen {
+                        None
+                    } else {
+                        Some(Box::new(self.expression(0)))
+                    };
+
+                    self.expect(Token::RightParen);
+
+                    status
+                } else {
+                    None
+                };
+
+                Expression::Exit(status)
+            },
+            Token::New => {
+                self.read();
+
+                if let Token::Variable(..) = self.current {
+                    error::fatal("Dynamic class names are not supported");
+                }
+
+                let class = match self.current {
+                    Token::Identifier(i) => i.to_string(),
+                    _ => String::new(),
+                };
+
+                self.expect(Token::Identifier(""));
+
+                // The parentheses are optional when there are no arguments.
+                let args = if self.current == Token::LeftParen {
+                    self.read();
+                    self.arguments()
+                } else {
+                    Vec::new()
+                };
+
+                Expression::New(class, args)
+            },
+            Token::Number(Number::Integer(i)) => {
                 self.read();
 
                 Expression::Integer(i)
             },
-            Token::Float(f) => {
+            Token::Number(Number::Float(f)) => {
                 self.read();
 
                 Expression::Float(f)
             },
+            Token::Minus | Token::Plus | Token::Bang => {
+                let op = self.current.clone();
+
+                self.read();
+
+                let (_, rbp) = prefix_binding_power(&op);
+                let rhs = self.expression(rbp);
+
+                prefix(&op, rhs)
+            },
+            Token::LeftParen => {
+                self.read();
+
+                // Casts are parsed here instead of in the lexer, since `(float` followed by
+                // anything other than `)` still needs to lex as a parenthesis and identifier.
+                let cast = match (&self.current, &self.peek) {
+                    (Token::Identifier(i), Token::RightParen) => cast(i),
+                    _ => None,
+                };
+
+                if let Some(cast) = cast {
+                    self.read();
+                    self.read();
+
+                    let rhs = self.expression(17);
+
+                    Expression::Cast(cast, Box::new(rhs))
+                } else {
+                    let expression = self.expression(0);
+
+                    self.expect(Token::RightParen);
+
+                    expression
+                }
+            },
             Token::Variable(v) => {
                 self.read();
 
@@ -284,7 +392,17 @@ impl<'p> Parser<'p> {
                 let mut items = Vec::new();
 
                 while self.current != Token::RightBracket {
-                    items.push(self.expression(0));
+                    let mut key = None;
+                    let mut value = self.expression(0);
+
+                    if self.current == Token::DoubleArrow {
+                        self.read();
+
+                        key = Some(value);
+                        value = self.expression(0);
+                    }
+
+                    items.push((key, value));
 
                     if self.current == Token::Comma {
                         self.read();
@@ -339,6 +457,23 @@ impl<'p> Parser<'p> {
         lhs
     }
 
+    /// The arguments of a call, after the opening parenthesis has been read.
+    fn arguments(&mut self) -> Vec<Expression> {
+        let mut args = Vec::new();
+
+        while self.current != Token::RightParen {
+            args.push(self.expression(0));
+
+            if self.current == Token::Comma {
+                self.read()
+            }
+        }
+
+        self.expect(Token::RightParen);
+
+        args
+    }
+
     fn read(&mut self) {
         self.current = std::mem::replace(&mut self.peek, if let Some(t) = self.tokens.next() { t.clone() } else { Token::Eof });
     }
@@ -365,9 +500,42 @@ impl<'p> Parser<'p> {
     }
 }
 
+fn cast(name: &str) -> Option<Cast> {
+    Some(match name.to_lowercase().as_str() {
+        "int" | "integer" => Cast::Int,
+        "float" | "double" => Cast::Float,
+        "string" | "binary" => Cast::String,
+        "bool" | "boolean" => Cast::Bool,
+        "array" => Cast::Array,
+        "object" => Cast::Object,
+        _ => return None,
+    })
+}
+
+fn prefix_binding_power(token: &Token) -> ((), u8) {
+    match token {
+        Token::Minus | Token::Plus => ((), 17),
+        Token::Bang => ((), 15),
+        _ => unreachable!("prefix: {:?}", token),
+    }
+}
+
+fn prefix(op: &Token, rhs: Expression) -> Expression {
+    match (op, rhs) {
+        // Negative literals are folded here so that `-9223372036854775808` stays a float
+        // and `-PHP_INT_MAX - 1` can be written the same way as in PHP.
+        (Token::Minus, Expression::Integer(i)) => Expression::Integer(-i),
+        (Token::Minus, Expression::Float(f)) => Expression::Float(-f),
+        (Token::Minus, rhs) => Expression::Infix(Box::new(Expression::Integer(-1)), Op::Multiply, Box::new(rhs)),
+        (Token::Plus, rhs) => Expression::Infix(Box::new(Expression::Integer(1)), Op::Multiply, Box::new(rhs)),
+        (Token::Bang, rhs) => Expression::Not(Box::new(rhs)),
+        _ => unreachable!("prefix: {:?}", op),
+    }
+}
+
 fn postfix_binding_power(token: &Token) -> Option<(u8, ())> {
     Some(match token {
-        Token::LeftParen | Token::LeftBracket => (19, ()),
+        Token::LeftParen | Token::LeftBracket | Token::Arrow => (19, ()),
         _ => return None
     })
 }
@@ -380,19 +548,7 @@ fn postfix(parser: &mut Parser, lhs: Expression, op: &Token) -> Expression {
                 _ => unreachable!()
             };
 
-            let mut args = Vec::new();
-
-            while parser.current != Token::RightParen {
-                args.push(parser.expression(0));
-
-                if parser.current == Token::Comma {
-                    parser.read()
-                }
-            }
-
-            parser.expect(Token::RightParen);
-
-            Expression::Call(name, args)
+            Expression::Call(name, parser.arguments())
         },
         Token::LeftBracket => {
             let index = parser.expression(0);
@@ -401,16 +557,37 @@ fn postfix(parser: &mut Parser, lhs: Expression, op: &Token) -> Expression {
 
             Expression::Index(Box::new(lhs), Box::new(index))
         },
+        Token::Arrow => {
+            if let Token::Variable(..) = parser.current {
+                error::fatal("Dynamic property and method names are not supported");
+            }
+
+            let name = match parser.current {
+                Token::Identifier(i) => i.to_string(),
+                _ => String::new(),
+            };
+
+            parser.expect(Token::Identifier(""));
+
+            if parser.current == Token::LeftParen {
+                parser.read();
+
+                Expression::MethodCall(Box::new(lhs), name, parser.arguments())
+            } else {
+                Expression::Property(Box::new(lhs), name)
+            }
+        },
         _ => todo!("postfix: {:?}", op),
     }
 }
 
 fn infix_binding_power(token: &Token) -> Option<(u8, u8)> {
     Some(match token {
-        Token::Multiply | Token::Divide => (13, 14),
+        Token::Multiply | Token::Divide | Token::Modulo => (13, 14),
         Token::Plus | Token::Minus => (11, 12),
         Token::Dot => (11, 11),
-        Token::LessThan | Token::GreaterThan => (9, 10),
+        Token::LessThan | Token::GreaterThan | Token::LessThanOrEqual | Token::GreaterThanOrEqual => (9, 10),
+        Token::Equals | Token::NotEquals | Token::Identical | Token::NotIdentical | Token::Spaceship => (7, 8),
         Token::Assign => (2, 1),
         _ => return None
     })
@@ -428,8 +605,16 @@ fn infix(lhs: Expression, op: &Token, rhs: Expression) -> Expression {
                 Token::Minus => Op::Subtract,
                 Token::Multiply => Op::Multiply,
                 Token::Divide => Op::Divide,
+                Token::Modulo => Op::Modulo,
                 Token::LessThan => Op::LessThan,
                 Token::GreaterThan => Op::GreaterThan,
+                Token::LessThanOrEqual => Op::LessThanOrEqual,
+                Token::GreaterThanOrEqual => Op::GreaterThanOrEqual,
+                Token::Equals => Op::Equals,
+                Token::NotEquals => Op::NotEquals,
+                Token::Identical => Op::Identical,
+                Token::NotIdentical => Op::NotIdentical,
+                Token::Spaceship => Op::Spaceship,
                 Token::Dot => Op::Concat,
                 _ => todo!("infix op: {:?}", op),
             }, rhs)
@@ -443,8 +628,16 @@ pub enum Op {
     Subtract,
     Multiply,
     Divide,
+    Modulo,
     LessThan,
     GreaterThan,
+    LessThanOrEqual,
+    GreaterThanOrEqual,
+    Equals,
+    NotEquals,
+    Identical,
+    NotIdentical,
+    Spaceship,
     Concat,
 }
 
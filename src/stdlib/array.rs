@@ -1,14 +1,942 @@
-use super::{arity, typecheck};
-use crate::object::Object;
+use super::{arity, callback, natural_compare};
+use crate::object::{Object, Array, Key, convert};
 use crate::vm::Machine;
+use crate::error;
+use std::cmp::Ordering;
 
-pub fn count(_: &mut Machine, mut args: Vec<Object>) -> Object {
+pub const COUNT_NORMAL: i64 = 0;
+pub const COUNT_RECURSIVE: i64 = 1;
+
+pub const SORT_REGULAR: i64 = 0;
+pub const SORT_NUMERIC: i64 = 1;
+pub const SORT_STRING: i64 = 2;
+pub const SORT_DESC: i64 = 3;
+pub const SORT_ASC: i64 = 4;
+pub const SORT_LOCALE_STRING: i64 = 5;
+pub const SORT_NATURAL: i64 = 6;
+pub const SORT_FLAG_CASE: i64 = 8;
+
+pub const EXTR_OVERWRITE: i64 = 0;
+pub const EXTR_SKIP: i64 = 1;
+pub const EXTR_PREFIX_SAME: i64 = 2;
+pub const EXTR_PREFIX_ALL: i64 = 3;
+pub const EXTR_PREFIX_INVALID: i64 = 4;
+pub const EXTR_PREFIX_IF_EXISTS: i64 = 5;
+pub const EXTR_IF_EXISTS: i64 = 6;
+
+pub const ARRAY_FILTER_USE_BOTH: i64 = 1;
+pub const ARRAY_FILTER_USE_KEY: i64 = 2;
+
+/// Takes a copy of an array argument, throwing the same `TypeError` as PHP for anything else.
+fn array(function: &str, position: usize, name: &str, value: &Object) -> Array {
+    match value {
+        Object::Array(array) => array.borrow().clone(),
+        value => error::throw("TypeError", format!("{}(): Argument #{} (${}) must be of type array, {} given", function, position, name, value.type_name())),
+    }
+}
+
+/// Builds an array where integer keys are renumbered from zero and string keys are kept,
+/// which is what most of PHP's array functions do to their results.
+fn renumber(items: impl IntoIterator<Item = (Key, Object)>) -> Array {
+    let mut array = Array::new();
+
+    for (key, value) in items {
+        match key {
+            Key::Integer(..) => array.push(value),
+            key => array.insert(key, value),
+        }
+    }
+
+    array
+}
+
+/// Resolves a PHP-style offset and length (either of which can be negative) into a range.
+fn range_of(len: usize, offset: i64, length: Option<i64>) -> (usize, usize) {
+    let len = len as i64;
+    let start = match offset {
+        o if o > len => len,
+        o if o < 0 => (len + o).max(0),
+        o => o,
+    };
+
+    let end = match length {
+        None => len,
+        Some(l) if l < 0 => (len + l).max(start),
+        Some(l) => start.saturating_add(l).min(len),
+    };
+
+    (start as usize, end as usize)
+}
+
+fn optional_length(value: Option<&Object>) -> Option<i64> {
+    match value {
+        None | Some(Object::Null) => None,
+        Some(length) => Some(convert::to_int(length)),
+    }
+}
+
+fn count_recursive(array: &Array) -> i64 {
+    array.values().fold(array.len() as i64, |count, value| match value {
+        Object::Array(inner) => count + count_recursive(&inner.borrow()),
+        _ => count,
+    })
+}
+
+pub fn count(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    let mode = args.get(1).map(convert::to_int).unwrap_or(COUNT_NORMAL);
+
+    if mode != COUNT_NORMAL && mode != COUNT_RECURSIVE {
+        error::throw("ValueError", "count(): Argument #2 ($mode) must be either COUNT_NORMAL or COUNT_RECURSIVE");
+    }
+
+    let hash = match args.first().unwrap() {
+        Object::Array(hash) => hash.borrow(),
+        value => error::throw("TypeError", format!("count(): Argument #1 ($value) must be of type Countable|array, {} given", value.type_name())),
+    };
+
+    Object::Integer(match mode {
+        COUNT_RECURSIVE => count_recursive(&hash),
+        _ => hash.len() as i64,
+    })
+}
+
+pub fn sizeof(vm: &mut Machine, args: Vec<Object>) -> Object {
+    count(vm, args)
+}
+
+pub fn array_keys(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 3);
+
+    let array = array("array_keys", 1, "array", &args[0]);
+    let strict = args.get(2).map(Object::to_bool).unwrap_or(false);
+
+    Object::from_array(match args.get(1) {
+        Some(search) => array.iter()
+            .filter(|(_, value)| if strict { convert::strict_equals(value, search) } else { convert::loose_equals(value, search) })
+            .map(|(key, _)| key.to_object())
+            .collect(),
+        None => array.keys().map(Key::to_object).collect(),
+    })
+}
+
+pub fn array_values(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    let array = array("array_values", 1, "array", &args[0]);
+
+    Object::from_array(array.values().cloned().collect())
+}
+
+pub fn array_merge(_: &mut Machine, args: Vec<Object>) -> Object {
+    let mut merged = Array::new();
+
+    for (i, arg) in args.iter().enumerate() {
+        for (key, value) in array("array_merge", i + 1, "arrays", arg) {
+            match key {
+                Key::Integer(..) => merged.push(value),
+                key => merged.insert(key, value),
+            }
+        }
+    }
+
+    Object::from_array(merged)
+}
+
+pub fn array_combine(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    let keys = array("array_combine", 1, "keys", &args[0]);
+    let values = array("array_combine", 2, "values", &args[1]);
+
+    if keys.len() != values.len() {
+        error::throw("ValueError", "array_combine(): Argument #1 ($keys) and argument #2 ($values) must have the same number of elements");
+    }
+
+    Object::from_array(keys.values()
+        .map(|key| Key::from(convert::to_bytes(key).as_bytes()))
+        .zip(values.values().cloned())
+        .collect())
+}
+
+pub fn array_flip(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    let mut flipped = Array::new();
+
+    for (key, value) in array("array_flip", 1, "array", &args[0]) {
+        match value {
+            Object::Integer(..) | Object::String(..) => flipped.insert(Key::from_object(&value), key.to_object()),
+            _ => error::warning("array_flip(): Can only flip string and integer values, entry skipped"),
+        }
+    }
+
+    Object::from_array(flipped)
+}
+
+pub fn array_slice(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 4);
+
+    let array = array("array_slice", 1, "array", &args[0]);
+    let offset = convert::to_int(&args[1]);
+    let length = optional_length(args.get(2));
+    let preserve_keys = args.get(3).map(Object::to_bool).unwrap_or(false);
+
+    let (start, end) = range_of(array.len(), offset, length);
+    let slice = array.into_iter().skip(start).take(end - start);
+
+    Object::from_array(match preserve_keys {
+        true => slice.collect(),
+        false => renumber(slice),
+    })
+}
+
+pub fn array_splice(vm: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 4);
+
+    let array = array("array_splice", 1, "array", &args[0]);
+    let offset = convert::to_int(&args[1]);
+    let length = optional_length(args.get(2));
+    let replacement: Vec<Object> = match args.get(3) {
+        None => Vec::new(),
+        Some(Object::Array(replacement)) => replacement.borrow().values().cloned().collect(),
+        Some(replacement) => vec![replacement.clone()],
+    };
+
+    let (start, end) = range_of(array.len(), offset, length);
+    let mut items: Vec<(Key, Object)> = array.into_iter().collect();
+    let removed: Vec<(Key, Object)> = items.splice(start..end, replacement.into_iter().map(|value| (Key::Integer(0), value))).collect();
+
+    vm.set_reference(0, Object::from_array(renumber(items)));
+
+    Object::from_array(removed.into_iter().map(|(_, value)| value).collect())
+}
+
+fn search(function: &str, needle: &Object, haystack: &Object, strict: bool) -> Option<Key> {
+    let haystack = match haystack {
+        Object::Array(haystack) => haystack.borrow(),
+        value => error::throw("TypeError", format!("{}(): Argument #2 ($haystack) must be of type array, {} given", function, value.type_name())),
+    };
+
+    haystack.iter()
+        .find(|(_, value)| if strict { convert::strict_equals(value, needle) } else { convert::loose_equals(value, needle) })
+        .map(|(key, _)| key.clone())
+}
+
+pub fn array_search(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 3);
+
+    let strict = args.get(2).map(Object::to_bool).unwrap_or(false);
+
+    match search("array_search", &args[0], &args[1], strict) {
+        Some(key) => key.to_object(),
+        None => Object::False,
+    }
+}
+
+pub fn in_array(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 3);
+
+    let strict = args.get(2).map(Object::to_bool).unwrap_or(false);
+
+    Object::from_bool(search("in_array", &args[0], &args[1], strict).is_some())
+}
+
+pub fn array_key_exists(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    let array = array("array_key_exists", 2, "array", &args[1]);
+
+    Object::from_bool(array.contains_key(&Key::from_object(&args[0])))
+}
+
+pub fn key_exists(vm: &mut Machine, args: Vec<Object>) -> Object {
+    array_key_exists(vm, args)
+}
+
+pub fn array_key_first(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    let array = array("array_key_first", 1, "array", &args[0]);
+
+    array.first().map(|(key, _)| key.to_object()).unwrap_or(Object::Null)
+}
+
+pub fn array_key_last(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    let array = array("array_key_last", 1, "array", &args[0]);
+
+    array.last().map(|(key, _)| key.to_object()).unwrap_or(Object::Null)
+}
+
+pub fn array_unique(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    let array = array("array_unique", 1, "array", &args[0]);
+    let flags = args.get(1).map(convert::to_int).unwrap_or(SORT_STRING);
+    let mut unique: Vec<(Key, Object)> = Vec::new();
+
+    let same = |a: &Object, b: &Object| match flags {
+        SORT_REGULAR => convert::loose_equals(a, b),
+        SORT_NUMERIC => convert::to_float(a) == convert::to_float(b),
+        _ => convert::to_bytes(a) == convert::to_bytes(b),
+    };
+
+    for (key, value) in array {
+        if ! unique.iter().any(|(_, kept)| same(kept, &value)) {
+            unique.push((key, value));
+        }
+    }
+
+    Object::from_array(unique.into_iter().collect())
+}
+
+pub fn array_reverse(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    let array = array("array_reverse", 1, "array", &args[0]);
+    let preserve_keys = args.get(1).map(Object::to_bool).unwrap_or(false);
+    let mut items: Vec<(Key, Object)> = array.into_iter().collect();
+
+    items.reverse();
+
+    Object::from_array(match preserve_keys {
+        true => items.into_iter().collect(),
+        false => renumber(items),
+    })
+}
+
+pub fn array_fill(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 3);
+
+    let start = convert::to_int(&args[0]);
+    let count = convert::to_int(&args[1]);
+    let value = &args[2];
+
+    if count < 0 {
+        error::throw("ValueError", "array_fill(): Argument #2 ($count) must be greater than or equal to 0");
+    }
+
+    Object::from_array((0..count).map(|i| (Key::Integer(start.wrapping_add(i)), value.clone())).collect())
+}
+
+pub fn array_fill_keys(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    let keys = array("array_fill_keys", 1, "keys", &args[0]);
+    let value = &args[1];
+
+    Object::from_array(keys.values().map(|key| (Key::from(convert::to_bytes(key).as_bytes()), value.clone())).collect())
+}
+
+pub fn array_pad(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 3);
+
+    let array = array("array_pad", 1, "array", &args[0]);
+    let length = convert::to_int(&args[1]);
+    let value = &args[2];
+    let padding = (length.unsigned_abs() as usize).saturating_sub(array.len());
+
+    if padding == 0 {
+        return Object::from_array(array);
+    }
+
+    let pad = std::iter::repeat_n((Key::Integer(0), value.clone()), padding);
+
+    Object::from_array(match length < 0 {
+        true => renumber(pad.chain(array)),
+        false => renumber(array.into_iter().chain(pad)),
+    })
+}
+
+pub fn array_chunk(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 3);
+
+    let array = array("array_chunk", 1, "array", &args[0]);
+    let length = convert::to_int(&args[1]);
+    let preserve_keys = args.get(2).map(Object::to_bool).unwrap_or(false);
+
+    if length < 1 {
+        error::throw("ValueError", "array_chunk(): Argument #2 ($length) must be greater than 0");
+    }
+
+    let items: Vec<(Key, Object)> = array.into_iter().collect();
+
+    Object::from_array(items.chunks(length as usize)
+        .map(|chunk| Object::from_array(match preserve_keys {
+            true => chunk.iter().cloned().collect(),
+            false => chunk.iter().map(|(_, value)| value.clone()).collect(),
+        }))
+        .collect())
+}
+
+pub fn array_column(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 3);
+
+    let rows = array("array_column", 1, "array", &args[0]);
+    let column = args.get(1).filter(|c| ! matches!(c, Object::Null)).map(Key::from_object);
+    let index = args.get(2).filter(|i| ! matches!(i, Object::Null)).map(Key::from_object);
+    let mut result = Array::new();
+
+    for row in rows.values() {
+        let row = match row {
+            Object::Array(row) => row.borrow().clone(),
+            Object::Instance(instance) => instance.borrow().properties.clone(),
+            _ => continue,
+        };
+
+        let value = match &column {
+            Some(column) => match row.get(column) {
+                Some(value) => value.clone(),
+                None => continue,
+            },
+            None => Object::from_array(row.clone()),
+        };
+
+        match index.as_ref().and_then(|index| row.get(index)) {
+            Some(key @ (Object::Integer(..) | Object::String(..))) => result.insert(Key::from_object(key), value),
+            _ => result.push(value),
+        }
+    }
+
+    Object::from_array(result)
+}
+
+pub fn range(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 3);
+
+    let step = args.get(2).cloned().unwrap_or(Object::Integer(1));
+    let step = match &step {
+        Object::String(s) => convert::parse_numeric(s).map(|(n, _)| n).unwrap_or(Object::Integer(1)),
+        step => step.clone(),
+    };
+    let step_float = convert::to_float(&step).abs();
+
+    if step_float == 0.0 {
+        error::throw("ValueError", "range(): Argument #3 ($step) cannot be 0");
+    }
+
+    let character = |value: &Object| match value {
+        Object::String(s) if s.len() == 1 && ! s.as_bytes()[0].is_ascii_digit() => Some(s.as_bytes()[0]),
+        _ => None,
+    };
+
+    if let (Some(start), Some(end)) = (character(&args[0]), character(&args[1])) {
+        let step = (step_float as usize).max(1);
+        let characters: Vec<u8> = match start <= end {
+            true => (start..=end).step_by(step).collect(),
+            false => (end..=start).rev().step_by(step).collect(),
+        };
+
+        return Object::from_array(characters.into_iter().map(|c| Object::String(vec![c].into())).collect());
+    }
+
+    let number = |value: &Object| match value {
+        Object::String(s) => convert::parse_numeric(s).map(|(n, _)| n).unwrap_or(Object::Integer(0)),
+        Object::Float(..) => value.clone(),
+        value => Object::Integer(convert::to_int(value)),
+    };
+
+    let (start, end) = (number(&args[0]), number(&args[1]));
+    let too_large = || error::throw("ValueError", "range(): Argument #3 ($step) must not exceed the specified range");
+
+    match (&start, &end, &step) {
+        (Object::Integer(start), Object::Integer(end), step) if ! matches!(step, Object::Float(f) if f.fract() != 0.0) => {
+            let (start, end) = (*start as i128, *end as i128);
+            let step = step_float as i128;
+
+            if (end - start).abs() < step && start != end {
+                too_large();
+            }
+
+            let count = (end - start).abs() / step;
+
+            Object::from_array((0..=count).map(|i| Object::Integer(if start <= end { start + i * step } else { start - i * step } as i64)).collect())
+        },
+        _ => {
+            let (start, end) = (convert::to_float(&start), convert::to_float(&end));
+
+            if (end - start).abs() < step_float && start != end {
+                too_large();
+            }
+
+            let count = ((end - start).abs() / step_float + 1e-9).floor() as i64;
+
+            Object::from_array((0..=count).map(|i| Object::Float(if start <= end { start + i as f64 * step_float } else { start - i as f64 * step_float })).collect())
+        },
+    }
+}
+
+fn compact_into(vm: &mut Machine, result: &mut Array, name: &Object) {
+    match name {
+        Object::Array(names) => {
+            let names: Vec<Object> = names.borrow().values().cloned().collect();
+
+            for name in names.iter() {
+                compact_into(vm, result, name);
+            }
+        },
+        name => {
+            let name = convert::to_string(name);
+
+            match vm.get_variable(&format!("${}", name)) {
+                Some(value) => result.insert(Key::String(name.into()), value),
+                None => error::warning(format!("compact(): Undefined variable ${}", name)),
+            }
+        },
+    }
+}
+
+pub fn compact(vm: &mut Machine, args: Vec<Object>) -> Object {
+    let mut result = Array::new();
+
+    for name in args.iter() {
+        compact_into(vm, &mut result, name);
+    }
+
+    Object::from_array(result)
+}
+
+fn is_valid_variable_name(name: &str) -> bool {
+    let mut bytes = name.bytes();
+
+    match bytes.next() {
+        Some(b) if b.is_ascii_alphabetic() || b == b'_' || b >= 0x80 => (),
+        _ => return false,
+    }
+
+    bytes.all(|b| b.is_ascii_alphanumeric() || b == b'_' || b >= 0x80)
+}
+
+pub fn extract(vm: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 3);
+
+    let array = array("extract", 1, "array", &args[0]);
+    let flags = args.get(1).map(convert::to_int).unwrap_or(EXTR_OVERWRITE);
+    let prefix = args.get(2).map(convert::to_string);
+
+    if ! (EXTR_OVERWRITE..=EXTR_IF_EXISTS).contains(&flags) {
+        error::throw("ValueError", "extract(): Argument #2 ($flags) must be a valid extract type");
+    }
+
+    if (EXTR_PREFIX_SAME..=EXTR_PREFIX_IF_EXISTS).contains(&flags) && prefix.is_none() {
+        error::throw("ValueError", "extract(): Argument #3 ($prefix) is required when using this extract type");
+    }
+
+    let prefixed = |name: &str| format!("{}_{}", prefix.as_deref().unwrap_or_default(), name);
+    let mut extracted = 0;
+
+    for (key, value) in array {
+        let name = key.to_string();
+        let exists = vm.get_variable(&format!("${}", name)).is_some();
+
+        let name = match (flags, key) {
+            (EXTR_PREFIX_ALL, _) | (EXTR_PREFIX_INVALID, Key::Integer(..)) => prefixed(&name),
+            (EXTR_PREFIX_INVALID, _) if ! is_valid_variable_name(&name) => prefixed(&name),
+            (EXTR_PREFIX_SAME, _) if exists => prefixed(&name),
+            (EXTR_PREFIX_IF_EXISTS, _) if exists => prefixed(&name),
+            (EXTR_PREFIX_IF_EXISTS | EXTR_IF_EXISTS, _) => continue,
+            (EXTR_SKIP, _) if exists => continue,
+            _ => name,
+        };
+
+        if ! is_valid_variable_name(&name) || name == "this" {
+            continue;
+        }
+
+        vm.set_variable(format!("${}", name), value);
+        extracted += 1;
+    }
+
+    Object::Integer(extracted)
+}
+
+/// Converts a value to a number for `array_sum()` and `array_product()`, which skip
+/// arrays and treat anything non-numeric as zero.
+fn summable(value: &Object) -> Option<Object> {
+    match value {
+        Object::Integer(..) | Object::Float(..) => Some(value.clone()),
+        Object::String(s) => Some(convert::parse_numeric(s).map(|(n, _)| n).unwrap_or(Object::Integer(0))),
+        Object::Array(..) | Object::Instance(..) | Object::Resource(..) => None,
+        value => Some(Object::Integer(convert::to_int(value))),
+    }
+}
+
+pub fn array_sum(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    let array = array("array_sum", 1, "array", &args[0]);
+
+    array.values().filter_map(summable).fold(Object::Integer(0), |sum, value| convert::add(&sum, &value))
+}
+
+pub fn array_product(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    let array = array("array_product", 1, "array", &args[0]);
+
+    array.values().filter_map(summable).fold(Object::Integer(1), |product, value| convert::multiply(&product, &value))
+}
+
+pub fn array_push(vm: &mut Machine, mut args: Vec<Object>) -> Object {
+    let mut array = array("array_push", 1, "array", &args.remove(0));
+
+    for value in args {
+        array.push(value);
+    }
+
+    let count = array.len();
+    vm.set_reference(0, Object::from_array(array));
+
+    Object::Integer(count as i64)
+}
+
+pub fn array_pop(vm: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    let mut array = array("array_pop", 1, "array", &args[0]);
+    let popped = array.pop();
+
+    vm.set_reference(0, Object::from_array(array));
+
+    popped.map(|(_, value)| value).unwrap_or(Object::Null)
+}
+
+pub fn array_shift(vm: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    let array = array("array_shift", 1, "array", &args[0]);
+    let mut items = array.into_iter();
+    let shifted = items.next();
+
+    vm.set_reference(0, Object::from_array(renumber(items)));
+
+    shifted.map(|(_, value)| value).unwrap_or(Object::Null)
+}
+
+pub fn array_unshift(vm: &mut Machine, mut args: Vec<Object>) -> Object {
+    let array = array("array_unshift", 1, "array", &args.remove(0));
+    let values = args.into_iter().map(|value| (Key::Integer(0), value));
+    let array = renumber(values.chain(array));
+    let count = array.len();
+
+    vm.set_reference(0, Object::from_array(array));
+
+    Object::Integer(count as i64)
+}
+
+/// A stable merge sort. `slice::sort_by()` is stable too, but it's allowed to panic when the
+/// comparison isn't a total order, which user comparison functions often aren't.
+fn stable_sort<T: Clone>(items: &mut [T], compare: &mut impl FnMut(&T, &T) -> Ordering) {
+    if items.len() <= 1 {
+        return;
+    }
+
+    let middle = items.len() / 2;
+
+    stable_sort(&mut items[..middle], compare);
+    stable_sort(&mut items[middle..], compare);
+
+    let mut merged = Vec::with_capacity(items.len());
+    let (mut i, mut j) = (0, middle);
+
+    while i < middle && j < items.len() {
+        if compare(&items[j], &items[i]) == Ordering::Less {
+            merged.push(items[j].clone());
+            j += 1;
+        } else {
+            merged.push(items[i].clone());
+            i += 1;
+        }
+    }
+
+    merged.extend_from_slice(&items[i..middle]);
+    merged.extend_from_slice(&items[j..]);
+    items.clone_from_slice(&merged);
+}
+
+/// Calls a user comparison function, which can return any number.
+fn user_compare(vm: &mut Machine, callback: &Object, a: &Object, b: &Object) -> Ordering {
+    convert::to_int(&vm.call(callback, vec![a.clone(), b.clone()])).cmp(&0)
+}
+
+pub fn array_map(vm: &mut Machine, args: Vec<Object>) -> Object {
+    let callback = callback(vm, "array_map", 1, &args[0], true);
+    let arrays: Vec<Array> = args[1..].iter().enumerate()
+        .map(|(i, arg)| array("array_map", i + 2, if i == 0 { "array" } else { "arrays" }, arg))
+        .collect();
+
+    if arrays.is_empty() {
+        error::throw("ArgumentCountError", "array_map() expects at least 2 arguments, 1 given");
+    }
+
+    // With a single array the keys are kept, otherwise the arrays are zipped together.
+    if arrays.len() == 1 {
+        let array = arrays.into_iter().next().unwrap();
+
+        return Object::from_array(match callback {
+            Some(callback) => array.into_iter().map(|(key, value)| (key, vm.call(&callback, vec![value]))).collect(),
+            None => array,
+        });
+    }
+
+    let length = arrays.iter().map(Array::len).max().unwrap_or(0);
+    let columns: Vec<Vec<Object>> = arrays.iter().map(|array| array.values().cloned().collect()).collect();
+    let mut result = Array::new();
+
+    for i in 0..length {
+        let args: Vec<Object> = columns.iter().map(|column| column.get(i).cloned().unwrap_or(Object::Null)).collect();
+
+        result.push(match &callback {
+            Some(callback) => vm.call(callback, args),
+            None => Object::from_array(args.into_iter().collect()),
+        });
+    }
+
+    Object::from_array(result)
+}
+
+pub fn array_filter(vm: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 3);
+
+    let array = array("array_filter", 1, "array", &args[0]);
+    let callback = match args.get(1) {
+        Some(value) => callback(vm, "array_filter", 2, value, true),
+        None => None,
+    };
+    let mode = args.get(2).map(convert::to_int).unwrap_or(0);
+
+    Object::from_array(array.into_iter()
+        .filter(|(key, value)| match &callback {
+            Some(callback) => vm.call(callback, match mode {
+                ARRAY_FILTER_USE_KEY => vec![key.to_object()],
+                ARRAY_FILTER_USE_BOTH => vec![value.clone(), key.to_object()],
+                _ => vec![value.clone()],
+            }).to_bool(),
+            None => value.to_bool(),
+        })
+        .collect())
+}
+
+pub fn array_reduce(vm: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 3);
+
+    let array = array("array_reduce", 1, "array", &args[0]);
+    let callback = callback(vm, "array_reduce", 2, &args[1], false).unwrap();
+    let initial = args.get(2).cloned().unwrap_or(Object::Null);
+
+    array.into_iter().fold(initial, |carry, (_, value)| vm.call(&callback, vec![carry, value]))
+}
+
+pub fn array_walk(vm: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 3);
+
+    let array = array("array_walk", 1, "array", &args[0]);
+    let callback = callback(vm, "array_walk", 2, &args[1], false).unwrap();
+    let extra = args.get(2).cloned();
+
+    // User functions can't take parameters by reference yet, so the array is never modified.
+    for (key, value) in array {
+        let mut args = vec![value, key.to_object()];
+        args.extend(extra.clone());
+
+        vm.call(&callback, args);
+    }
+
+    Object::True
+}
+
+pub fn usort(vm: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    let array = array("usort", 1, "array", &args[0]);
+    let callback = callback(vm, "usort", 2, &args[1], false).unwrap();
+    let mut values: Vec<Object> = array.values().cloned().collect();
+
+    stable_sort(&mut values, &mut |a, b| user_compare(vm, &callback, a, b));
+    vm.set_reference(0, Object::from_array(values.into_iter().collect()));
+
+    Object::True
+}
+
+pub fn uasort(vm: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    let array = array("uasort", 1, "array", &args[0]);
+    let callback = callback(vm, "uasort", 2, &args[1], false).unwrap();
+    let mut items: Vec<(Key, Object)> = array.into_iter().collect();
+
+    stable_sort(&mut items, &mut |(_, a), (_, b)| user_compare(vm, &callback, a, b));
+    vm.set_reference(0, Object::from_array(items.into_iter().collect()));
+
+    Object::True
+}
+
+pub fn uksort(vm: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    let array = array("uksort", 1, "array", &args[0]);
+    let callback = callback(vm, "uksort", 2, &args[1], false).unwrap();
+    let mut items: Vec<(Key, Object)> = array.into_iter().collect();
+
+    stable_sort(&mut items, &mut |(a, _), (b, _)| user_compare(vm, &callback, &a.to_object(), &b.to_object()));
+    vm.set_reference(0, Object::from_array(items.into_iter().collect()));
+
+    Object::True
+}
+
+/// Compares two values the way the `sort()` family does for the given `SORT_*` flags.
+fn compare_with_flags(a: &Object, b: &Object, flags: i64) -> Ordering {
+    let fold_case = flags & SORT_FLAG_CASE != 0;
+
+    match flags & ! SORT_FLAG_CASE {
+        SORT_NUMERIC => convert::to_float(a).partial_cmp(&convert::to_float(b)).unwrap_or(Ordering::Equal),
+        SORT_STRING | SORT_LOCALE_STRING => match fold_case {
+            true => convert::to_bytes(a).to_ascii_lowercase().cmp(&convert::to_bytes(b).to_ascii_lowercase()),
+            false => convert::to_bytes(a).cmp(&convert::to_bytes(b)),
+        },
+        SORT_NATURAL => natural_compare(&convert::to_bytes(a), &convert::to_bytes(b), fold_case),
+        _ => convert::compare(a, b),
+    }
+}
+
+/// Sorts an array argument and writes it back to the variable it came from. `by_key` sorts
+/// on the keys instead of the values and `keep_keys` decides whether they survive the sort.
+fn sort_by(vm: &mut Machine, function: &str, args: &[Object], by_key: bool, keep_keys: bool, reverse: bool) -> Object {
+    let array = array(function, 1, "array", &args[0]);
+    let flags = args.get(1).map(convert::to_int).unwrap_or(SORT_REGULAR);
+    let mut items: Vec<(Key, Object)> = array.into_iter().collect();
+
+    stable_sort(&mut items, &mut |(a_key, a), (b_key, b)| {
+        let ordering = match by_key {
+            true => compare_with_flags(&a_key.to_object(), &b_key.to_object(), flags),
+            false => compare_with_flags(a, b, flags),
+        };
+
+        if reverse { ordering.reverse() } else { ordering }
+    });
+
+    vm.set_reference(0, Object::from_array(match keep_keys {
+        true => items.into_iter().collect(),
+        false => items.into_iter().map(|(_, value)| value).collect(),
+    }));
+
+    Object::True
+}
+
+pub fn sort(vm: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    sort_by(vm, "sort", &args, false, false, false)
+}
+
+pub fn rsort(vm: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    sort_by(vm, "rsort", &args, false, false, true)
+}
+
+pub fn asort(vm: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    sort_by(vm, "asort", &args, false, true, false)
+}
+
+pub fn arsort(vm: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    sort_by(vm, "arsort", &args, false, true, true)
+}
+
+pub fn ksort(vm: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    sort_by(vm, "ksort", &args, true, true, false)
+}
+
+pub fn krsort(vm: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    sort_by(vm, "krsort", &args, true, true, true)
+}
+
+pub fn natsort(vm: &mut Machine, args: Vec<Object>) -> Object {
     arity!(args, 1);
 
-    let array = args.remove(0);
-    typecheck!(array, is_array);
-    
-    let hash = array.to_hash().borrow();
+    sort_by(vm, "natsort", &[args[0].clone(), Object::Integer(SORT_NATURAL)], false, true, false)
+}
+
+pub fn natcasesort(vm: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    sort_by(vm, "natcasesort", &[args[0].clone(), Object::Integer(SORT_NATURAL | SORT_FLAG_CASE)], false, true, false)
+}
+
+pub fn array_multisort(vm: &mut Machine, args: Vec<Object>) -> Object {
+    // Each array can be followed by a sort order and a set of sort flags, in either order.
+    struct Column {
+        position: usize,
+        items: Vec<(Key, Object)>,
+        order: Option<i64>,
+        flags: Option<i64>,
+    }
+
+    let mut columns: Vec<Column> = Vec::new();
+
+    for (position, arg) in args.iter().enumerate() {
+        match (arg, columns.last_mut()) {
+            (Object::Array(array), _) => columns.push(Column {
+                position,
+                items: array.borrow().clone().into_iter().collect(),
+                order: None,
+                flags: None,
+            }),
+            (Object::Integer(i), Some(column)) => {
+                let slot = match *i & ! SORT_FLAG_CASE {
+                    SORT_ASC | SORT_DESC => &mut column.order,
+                    SORT_REGULAR | SORT_NUMERIC | SORT_STRING | SORT_LOCALE_STRING | SORT_NATURAL => &mut column.flags,
+                    _ => error::throw("ValueError", format!("array_multisort(): Argument #{} must be a valid sort flag", position + 1)),
+                };
+
+                if slot.is_some() {
+                    error::throw("TypeError", format!("array_multisort(): Argument #{} must be an array or a sort flag that has not already been specified", position + 1));
+                }
+
+                *slot = Some(*i);
+            },
+            _ => error::throw("TypeError", format!("array_multisort(): Argument #{} must be an array or a sort flag", position + 1)),
+        }
+    }
+
+    let size = columns.first().map(|column| column.items.len()).unwrap_or(0);
+
+    if columns.iter().any(|column| column.items.len() != size) {
+        error::throw("ValueError", "Array sizes are inconsistent");
+    }
+
+    // Sort the row numbers, comparing by each column in turn until one of them differs.
+    let mut rows: Vec<usize> = (0..size).collect();
+
+    stable_sort(&mut rows, &mut |a, b| {
+        for column in columns.iter() {
+            let ordering = compare_with_flags(&column.items[*a].1, &column.items[*b].1, column.flags.unwrap_or(SORT_REGULAR));
+            let ordering = if column.order == Some(SORT_DESC) { ordering.reverse() } else { ordering };
+
+            if ordering != Ordering::Equal {
+                return ordering;
+            }
+        }
+
+        Ordering::Equal
+    });
+
+    for column in columns.iter() {
+        let sorted = renumber(rows.iter().map(|row| column.items[*row].clone()));
+
+        vm.set_reference(column.position, Object::from_array(sorted));
+    }
 
-    Object::Integer(hash.len() as i64)
-}
\ No newline at end of file
+    Object::True
+}
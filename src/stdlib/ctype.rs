@@ -0,0 +1,94 @@
+use super::arity;
+use crate::object::Object;
+use crate::vm::Machine;
+use crate::error;
+
+/// Checks every byte of a string against a character class from the C locale. An
+/// empty string never matches. For backwards compatibility, integers from -128 to 255
+/// are taken to be a single character (negative ones have 256 added to them), and
+/// any other integer is checked as a string of its digits.
+fn check(function: &str, value: &Object, class: fn(&u8) -> bool) -> Object {
+    match value {
+        Object::String(s) => Object::from_bool(! s.is_empty() && s.iter().all(class)),
+        Object::Integer(i) => {
+            error::deprecated(format!("{}(): Argument of type int will be interpreted as string in the future", function));
+
+            match *i {
+                -128..=-1 => Object::from_bool(class(&((i + 256) as u8))),
+                0..=255 => Object::from_bool(class(&(*i as u8))),
+                i => Object::from_bool(i.to_string().as_bytes().iter().all(class)),
+            }
+        },
+        value => {
+            error::deprecated(format!("{}(): Argument of type {} will be interpreted as string in the future", function, value.type_name()));
+            Object::False
+        },
+    }
+}
+
+pub fn ctype_digit(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    check("ctype_digit", &args[0], u8::is_ascii_digit)
+}
+
+pub fn ctype_alpha(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    check("ctype_alpha", &args[0], u8::is_ascii_alphabetic)
+}
+
+pub fn ctype_alnum(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    check("ctype_alnum", &args[0], u8::is_ascii_alphanumeric)
+}
+
+pub fn ctype_upper(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    check("ctype_upper", &args[0], u8::is_ascii_uppercase)
+}
+
+pub fn ctype_lower(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    check("ctype_lower", &args[0], u8::is_ascii_lowercase)
+}
+
+pub fn ctype_space(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    // Unlike `u8::is_ascii_whitespace()`, C's `isspace()` includes the vertical tab.
+    check("ctype_space", &args[0], |c| matches!(c, b' ' | b'\t' | b'\n' | b'\x0b' | b'\x0c' | b'\r'))
+}
+
+pub fn ctype_punct(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    check("ctype_punct", &args[0], u8::is_ascii_punctuation)
+}
+
+pub fn ctype_xdigit(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    check("ctype_xdigit", &args[0], u8::is_ascii_hexdigit)
+}
+
+pub fn ctype_cntrl(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    check("ctype_cntrl", &args[0], u8::is_ascii_control)
+}
+
+pub fn ctype_graph(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    check("ctype_graph", &args[0], u8::is_ascii_graphic)
+}
+
+pub fn ctype_print(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    check("ctype_print", &args[0], |c| c.is_ascii_graphic() || *c == b' ')
+}
@@ -0,0 +1,1614 @@
+use super::arity;
+use crate::object::{Object, Array, Key, Instance, convert, new_instance};
+use crate::vm::Machine;
+use crate::error;
+use chrono::{DateTime, Datelike, Duration, FixedOffset, LocalResult, NaiveDate, NaiveDateTime, NaiveTime, Offset, TimeZone, Timelike, Utc};
+use chrono_tz::{OffsetComponents, OffsetName, Tz, TZ_VARIANTS};
+use std::cell::RefCell;
+use std::convert::TryFrom;
+use std::rc::Rc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub const DATE_ATOM: &str = "Y-m-d\\TH:i:sP";
+pub const DATE_COOKIE: &str = "l, d-M-Y H:i:s T";
+pub const DATE_ISO8601: &str = "Y-m-d\\TH:i:sO";
+pub const DATE_RFC822: &str = "D, d M y H:i:s O";
+pub const DATE_RFC850: &str = "l, d-M-y H:i:s T";
+pub const DATE_RFC1036: &str = "D, d M y H:i:s O";
+pub const DATE_RFC1123: &str = "D, d M Y H:i:s O";
+pub const DATE_RFC7231: &str = "D, d M Y H:i:s \\G\\M\\T";
+pub const DATE_RFC2822: &str = "D, d M Y H:i:s O";
+pub const DATE_RFC3339: &str = "Y-m-d\\TH:i:sP";
+pub const DATE_RFC3339_EXTENDED: &str = "Y-m-d\\TH:i:s.vP";
+pub const DATE_RSS: &str = "D, d M Y H:i:s O";
+pub const DATE_W3C: &str = "Y-m-d\\TH:i:sP";
+
+/// A timezone, either one from the tz database (type 3 in PHP's terms) or a fixed
+/// UTC offset like "+05:00" (type 1).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Zone {
+    Named(Tz),
+    Offset(FixedOffset),
+}
+
+impl Zone {
+    /// Looks up a tz database identifier, ignoring case like PHP does.
+    pub fn find(name: &str) -> Option<Zone> {
+        TZ_VARIANTS.iter().find(|tz| tz.name().eq_ignore_ascii_case(name)).map(|tz| Zone::Named(*tz))
+    }
+
+    fn at(&self, timestamp: i64, micros: u32) -> Option<DateTime<FixedOffset>> {
+        let utc = Utc.timestamp_opt(timestamp, micros * 1000).single()?;
+
+        Some(match self {
+            Zone::Named(tz) => utc.with_timezone(tz).fixed_offset(),
+            Zone::Offset(offset) => utc.with_timezone(offset),
+        })
+    }
+
+    /// Finds the moment a wall-clock time refers to. Times that fall in a DST gap are
+    /// moved forward by the size of the gap, and ambiguous ones take the earlier offset.
+    fn resolve(&self, local: NaiveDateTime) -> Option<DateTime<FixedOffset>> {
+        match self {
+            Zone::Named(tz) => match tz.from_local_datetime(&local) {
+                LocalResult::Single(time) | LocalResult::Ambiguous(time, _) => Some(time.fixed_offset()),
+                LocalResult::None => {
+                    // Read the time with the offset from before the gap.
+                    let before = tz.offset_from_utc_datetime(&(local - Duration::hours(3))).fix();
+                    let utc = local - Duration::seconds(before.local_minus_utc() as i64);
+
+                    Some(utc.and_utc().with_timezone(tz).fixed_offset())
+                },
+            },
+            Zone::Offset(offset) => offset.from_local_datetime(&local).single(),
+        }
+    }
+
+    fn name(&self) -> String {
+        match self {
+            Zone::Named(tz) => tz.name().into(),
+            Zone::Offset(offset) => format_offset(offset.local_minus_utc(), true),
+        }
+    }
+
+    fn kind(&self) -> i64 {
+        match self {
+            Zone::Named(..) => 3,
+            Zone::Offset(..) => 1,
+        }
+    }
+}
+
+/// A point in time along with the zone it's shown in.
+#[derive(Debug, Clone, Copy)]
+pub struct Moment {
+    time: DateTime<FixedOffset>,
+    zone: Zone,
+}
+
+impl Moment {
+    fn now(zone: Zone) -> Moment {
+        let (seconds, micros) = now();
+
+        Moment { time: zone.at(seconds, micros).unwrap(), zone }
+    }
+
+    fn local(&self) -> NaiveDateTime {
+        self.time.naive_local()
+    }
+
+    fn with_local(&self, local: NaiveDateTime) -> Option<Moment> {
+        Some(Moment { time: self.zone.resolve(local)?, zone: self.zone })
+    }
+}
+
+fn now() -> (i64, u32) {
+    let elapsed = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+
+    (elapsed.as_secs() as i64, elapsed.subsec_micros())
+}
+
+fn format_offset(seconds: i32, colon: bool) -> String {
+    let sign = if seconds < 0 { '-' } else { '+' };
+    let seconds = seconds.abs();
+
+    match colon {
+        true => format!("{}{:02}:{:02}", sign, seconds / 3600, seconds / 60 % 60),
+        false => format!("{}{:02}{:02}", sign, seconds / 3600, seconds / 60 % 60),
+    }
+}
+
+fn days_in_month(year: i32, month: u32) -> u32 {
+    let (next_year, next_month) = if month == 12 { (year + 1, 1) } else { (year, month + 1) };
+
+    NaiveDate::from_ymd_opt(next_year, next_month, 1)
+        .and_then(|next| next.pred_opt())
+        .map_or(31, |last| last.day())
+}
+
+/// Builds a wall-clock time from fields that may have overflowed (like month 13 or
+/// day 0), carrying them over the same way `mktime()` does.
+fn build(year: i64, month: i64, day: i64, hour: i64, minute: i64, second: i64, micros: i64) -> Option<NaiveDateTime> {
+    let months = year.checked_mul(12)?.checked_add(month - 1)?;
+    let first = NaiveDate::from_ymd_opt(i32::try_from(months.div_euclid(12)).ok()?, months.rem_euclid(12) as u32 + 1, 1)?;
+    let offset = Duration::try_days(day - 1)?
+        .checked_add(&Duration::try_hours(hour)?)?
+        .checked_add(&Duration::try_minutes(minute)?)?
+        .checked_add(&Duration::try_seconds(second)?)?
+        .checked_add(&Duration::microseconds(micros))?;
+
+    first.and_time(NaiveTime::MIN).checked_add_signed(offset)
+}
+
+/// The parts of a date/time string that move a time rather than set it, like "+1 day".
+/// These are also what a `DateInterval` holds.
+#[derive(Debug, Default, Clone, Copy)]
+struct Relative {
+    years: i64,
+    months: i64,
+    days: i64,
+    hours: i64,
+    minutes: i64,
+    seconds: i64,
+    micros: i64,
+}
+
+impl Relative {
+    fn negate(&mut self) {
+        *self = Relative {
+            years: -self.years,
+            months: -self.months,
+            days: -self.days,
+            hours: -self.hours,
+            minutes: -self.minutes,
+            seconds: -self.seconds,
+            micros: -self.micros,
+        };
+    }
+
+    fn add(&mut self, unit: Unit, amount: i64) {
+        match unit {
+            Unit::Year => self.years += amount,
+            Unit::Month => self.months += amount,
+            Unit::Day => self.days += amount,
+            Unit::Week => self.days += amount * 7,
+            Unit::Fortnight => self.days += amount * 14,
+            Unit::Hour => self.hours += amount,
+            Unit::Minute => self.minutes += amount,
+            Unit::Second => self.seconds += amount,
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.years == 0 && self.months == 0 && self.days == 0 && self.hours == 0 && self.minutes == 0 && self.seconds == 0 && self.micros == 0
+    }
+
+    /// Moves a wall-clock time. Months are added without clamping the day, so
+    /// January 31st plus a month is March 2nd (or 3rd), as in PHP.
+    fn apply(&self, local: NaiveDateTime) -> Option<NaiveDateTime> {
+        build(
+            local.year() as i64 + self.years,
+            local.month() as i64 + self.months,
+            local.day() as i64 + self.days,
+            local.hour() as i64 + self.hours,
+            local.minute() as i64 + self.minutes,
+            local.second() as i64 + self.seconds,
+            (local.nanosecond() / 1000) as i64 + self.micros,
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Unit {
+    Year,
+    Month,
+    Week,
+    Fortnight,
+    Day,
+    Hour,
+    Minute,
+    Second,
+}
+
+fn unit(word: &str) -> Option<Unit> {
+    Some(match word {
+        "year" | "years" => Unit::Year,
+        "month" | "months" => Unit::Month,
+        "week" | "weeks" => Unit::Week,
+        "fortnight" | "fortnights" | "forthnight" | "forthnights" => Unit::Fortnight,
+        "day" | "days" => Unit::Day,
+        "hour" | "hours" => Unit::Hour,
+        "min" | "mins" | "minute" | "minutes" => Unit::Minute,
+        "sec" | "secs" | "second" | "seconds" => Unit::Second,
+        _ => return None,
+    })
+}
+
+fn weekday(word: &str) -> Option<u32> {
+    Some(match word {
+        "sunday" | "sun" => 0,
+        "monday" | "mon" => 1,
+        "tuesday" | "tue" | "tues" => 2,
+        "wednesday" | "wed" | "wednes" => 3,
+        "thursday" | "thu" | "thur" | "thurs" => 4,
+        "friday" | "fri" => 5,
+        "saturday" | "sat" => 6,
+        _ => return None,
+    })
+}
+
+fn month(word: &str) -> Option<u32> {
+    Some(match word {
+        "january" | "jan" => 1,
+        "february" | "feb" => 2,
+        "march" | "mar" => 3,
+        "april" | "apr" => 4,
+        "may" => 5,
+        "june" | "jun" => 6,
+        "july" | "jul" => 7,
+        "august" | "aug" => 8,
+        "september" | "sep" | "sept" => 9,
+        "october" | "oct" => 10,
+        "november" | "nov" => 11,
+        "december" | "dec" => 12,
+        _ => return None,
+    })
+}
+
+/// Which occurrence of a weekday "monday", "next monday" and "last monday" mean.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Occurrence {
+    This,
+    Next,
+    Last,
+}
+
+/// Everything found in a date/time string, before it's applied to a base time.
+#[derive(Debug, Default)]
+struct Parsed {
+    year: Option<i64>,
+    month: Option<i64>,
+    day: Option<i64>,
+    time: Option<(i64, i64, i64, i64)>,
+    relative: Relative,
+    weekday: Option<(u32, Occurrence)>,
+    // `Some(true)` for "first day of", `Some(false)` for "last day of".
+    first_day_of: Option<bool>,
+    timestamp: Option<(i64, u32)>,
+    zone: Option<Zone>,
+}
+
+/// Why a string couldn't be parsed, pointing at the character that stopped it like
+/// timelib's error messages do.
+struct ParseError {
+    position: usize,
+    character: char,
+}
+
+impl ParseError {
+    fn message(&self) -> &'static str {
+        match self.character.is_ascii_alphabetic() {
+            true => "The timezone could not be found in the database",
+            false => "Unexpected character",
+        }
+    }
+}
+
+/// A parser for the date/time formats `strtotime()` understands. This covers the
+/// common absolute formats (ISO 8601, American and European dates, textual months),
+/// times with am/pm, timezones, and relative phrases like "+1 week 2 days",
+/// "next monday", "3 days ago" and "first day of next month".
+struct Parser<'a> {
+    original: &'a str,
+    text: Vec<u8>,
+    position: usize,
+    parsed: Parsed,
+}
+
+impl<'a> Parser<'a> {
+    fn parse(original: &'a str) -> Result<Parsed, ParseError> {
+        let mut text = original.to_ascii_lowercase().into_bytes();
+        let mut parsed = Parsed::default();
+
+        // Timezone identifiers are picked out first, since they contain characters
+        // (like '/') that would otherwise look like part of a date.
+        let mut start = 0;
+
+        for word in original.split(|c: char| c.is_ascii_whitespace()) {
+            if word.contains('/') && word.starts_with(|c: char| c.is_ascii_alphabetic()) {
+                if let Some(zone) = Zone::find(word) {
+                    parsed.zone = Some(zone);
+                    text[start..start + word.len()].iter_mut().for_each(|c| *c = b' ');
+                }
+            }
+
+            start += word.len() + 1;
+        }
+
+        let mut parser = Parser { original, text, position: 0, parsed };
+
+        while parser.skip(|c| c.is_ascii_whitespace() || c == b',') {
+            match parser.peek() {
+                Some(b'@') => parser.timestamp()?,
+                Some(b'0'..=b'9') => parser.numeric()?,
+                Some(b'+') | Some(b'-') => parser.signed()?,
+                Some(b'a'..=b'z') => parser.text()?,
+                _ => return Err(parser.error()),
+            }
+        }
+
+        Ok(parser.parsed)
+    }
+
+    fn error(&self) -> ParseError {
+        ParseError {
+            position: self.position,
+            character: self.original[self.position..].chars().next().unwrap_or('\0'),
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.text.get(self.position).copied()
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<u8> {
+        self.text.get(self.position + offset).copied()
+    }
+
+    /// Skips characters matching a predicate, returning whether there's anything left.
+    fn skip(&mut self, predicate: impl Fn(u8) -> bool) -> bool {
+        while self.peek().is_some_and(&predicate) {
+            self.position += 1;
+        }
+
+        self.position < self.text.len()
+    }
+
+    fn spaces(&mut self) {
+        self.skip(|c| c == b' ' || c == b'\t');
+    }
+
+    fn eat(&mut self, c: u8) -> bool {
+        match self.peek() == Some(c) {
+            true => {
+                self.position += 1;
+                true
+            },
+            false => false,
+        }
+    }
+
+    fn expect(&mut self, c: u8) -> Result<(), ParseError> {
+        match self.eat(c) {
+            true => Ok(()),
+            false => Err(self.error()),
+        }
+    }
+
+    /// Reads a run of digits, returning the value and how many digits there were.
+    fn digits(&mut self) -> Option<(i64, usize)> {
+        let start = self.position;
+
+        self.skip(|c| c.is_ascii_digit());
+
+        let digits = std::str::from_utf8(&self.text[start..self.position]).unwrap();
+
+        match digits.is_empty() {
+            true => None,
+            false => Some((digits.parse().unwrap_or(i64::MAX), digits.len())),
+        }
+    }
+
+    fn number(&mut self, min: usize, max: usize) -> Result<i64, ParseError> {
+        let start = self.position;
+
+        match self.digits() {
+            Some((value, length)) if length >= min && length <= max => Ok(value),
+            _ => {
+                self.position = start;
+                Err(self.error())
+            },
+        }
+    }
+
+    fn word(&mut self) -> String {
+        let start = self.position;
+
+        self.skip(|c| c.is_ascii_alphabetic());
+
+        String::from_utf8(self.text[start..self.position].to_vec()).unwrap()
+    }
+
+    /// Looks at the next word without consuming it.
+    fn peek_word(&mut self) -> String {
+        let start = self.position;
+
+        self.spaces();
+
+        let word = self.word();
+
+        self.position = start;
+
+        word
+    }
+
+    fn date(&mut self, year: i64, month: i64, day: i64) -> Result<(), ParseError> {
+        if ! (1..=12).contains(&month) || ! (1..=31).contains(&day) || self.parsed.year.is_some() {
+            return Err(self.error());
+        }
+
+        self.parsed.year = Some(year);
+        self.parsed.month = Some(month);
+        self.parsed.day = Some(day);
+
+        Ok(())
+    }
+
+    fn year(value: i64, length: usize) -> i64 {
+        match length {
+            1 | 2 if value < 70 => value + 2000,
+            1 | 2 => value + 1900,
+            _ => value,
+        }
+    }
+
+    fn timestamp(&mut self) -> Result<(), ParseError> {
+        self.position += 1;
+
+        let negative = self.eat(b'-');
+        let seconds = self.number(1, 19)?;
+        let mut micros = 0;
+
+        if self.eat(b'.') {
+            let start = self.position;
+            let fraction = self.number(1, 6)?;
+
+            micros = fraction * 10i64.pow(6 - (self.position - start) as u32);
+        }
+
+        self.parsed.timestamp = Some(match negative {
+            true if micros > 0 => (-seconds - 1, (1_000_000 - micros) as u32),
+            true => (-seconds, 0),
+            false => (seconds, micros as u32),
+        });
+        self.parsed.zone = Some(Zone::Offset(FixedOffset::east_opt(0).unwrap()));
+
+        Ok(())
+    }
+
+    fn numeric(&mut self) -> Result<(), ParseError> {
+        let start = self.position;
+        let (value, length) = self.digits().unwrap();
+
+        match (self.peek(), length) {
+            // 2024-01-05, 2024/01/05
+            (Some(separator @ (b'-' | b'/')), 4) => {
+                self.position += 1;
+                let month = self.number(1, 2)?;
+                self.expect(separator)?;
+                let day = self.number(1, 2)?;
+
+                self.date(value, month, day)?;
+
+                // The "T" between the date and time in ISO 8601.
+                if self.peek() == Some(b't') && self.peek_at(1).is_some_and(|c| c.is_ascii_digit()) {
+                    self.position += 1;
+                }
+            },
+            // 1/5/2024 (American order)
+            (Some(b'/'), 1 | 2) => {
+                self.position += 1;
+                let day = self.number(1, 2)?;
+                let mut year = self.parsed.year.unwrap_or(i64::MIN);
+
+                if self.eat(b'/') {
+                    let digits = self.position;
+                    year = self.number(1, 4)?;
+                    year = Self::year(year, self.position - digits);
+                }
+
+                self.date(year, value, day)?;
+
+                if year == i64::MIN {
+                    self.parsed.year = None;
+                }
+            },
+            // 5-1-2024, 5.1.2024 (European order)
+            (Some(separator @ (b'-' | b'.')), 1 | 2) if self.peek_at(1).is_some_and(|c| c.is_ascii_digit()) => {
+                self.position += 1;
+                let month = self.number(1, 2)?;
+                self.expect(separator)?;
+                let year = self.number(4, 4)?;
+
+                self.date(year, month, value)?;
+            },
+            // 10:30, 10:30:15, 10:30:15.5
+            (Some(b':'), 1 | 2) => {
+                self.position += 1;
+                let minute = self.number(2, 2)?;
+                let mut second = 0;
+                let mut micros = 0;
+
+                if self.eat(b':') {
+                    second = self.number(2, 2)?;
+
+                    if self.eat(b'.') || self.eat(b',') {
+                        let digits = self.position;
+                        let fraction = self.number(1, 9)?;
+                        let length = (self.position - digits) as u32;
+
+                        micros = match length {
+                            1..=6 => fraction * 10i64.pow(6 - length),
+                            _ => fraction / 10i64.pow(length - 6),
+                        };
+                    }
+                }
+
+                let hour = self.meridian(value)?;
+
+                if hour > 23 || minute > 59 || second > 60 {
+                    self.position = start;
+                    return Err(self.error());
+                }
+
+                self.parsed.time = Some((hour, minute, second, micros));
+            },
+            // 20240105
+            (_, 8) if ! self.peek().is_some_and(|c| c.is_ascii_alphabetic()) => {
+                self.date(value / 10000, value / 100 % 100, value % 100)?;
+            },
+            _ => {
+                let word = self.peek_word();
+
+                if word == "am" || word == "pm" {
+                    let hour = self.meridian(value)?;
+
+                    self.parsed.time = Some((hour, 0, 0, 0));
+                } else if let Some(unit) = unit(&word) {
+                    self.spaces();
+                    self.word();
+                    self.parsed.relative.add(unit, value);
+                } else if matches!(word.as_str(), "st" | "nd" | "rd" | "th") || month(&word).is_some() {
+                    // 5th January 2024, 5 Jan
+                    if matches!(word.as_str(), "st" | "nd" | "rd" | "th") {
+                        self.word();
+                    }
+
+                    self.spaces();
+
+                    let name_start = self.position;
+                    let name = self.word();
+
+                    match month(&name) {
+                        Some(month) => {
+                            self.parsed.month = Some(month as i64);
+                            self.parsed.day = Some(value);
+                            self.textual_year();
+                        },
+                        None => {
+                            self.position = name_start;
+                            self.parsed.day = Some(value);
+                        },
+                    }
+                } else if length == 4 && self.parsed.year.is_none() {
+                    self.parsed.year = Some(value);
+                } else {
+                    self.position = start;
+                    return Err(self.error());
+                }
+            },
+        }
+
+        Ok(())
+    }
+
+    /// Reads an optional "am" or "pm" after an hour, converting it to 24-hour time.
+    fn meridian(&mut self, hour: i64) -> Result<i64, ParseError> {
+        let start = self.position;
+        let word = self.peek_word();
+
+        if word != "am" && word != "pm" {
+            return Ok(hour);
+        }
+
+        if ! (1..=12).contains(&hour) {
+            return Err(self.error());
+        }
+
+        self.spaces();
+        self.position += 2;
+
+        // "a.m." and "p.m." are fine too.
+        if self.text[start..].starts_with(b".") {
+            self.position = start;
+        }
+
+        Ok(match word.as_str() {
+            "am" => hour % 12,
+            _ => hour % 12 + 12,
+        })
+    }
+
+    /// The optional year after a textual month and day, as in "January 5, 2024".
+    fn textual_year(&mut self) {
+        let start = self.position;
+
+        self.skip(|c| c == b' ' || c == b',');
+
+        match self.digits() {
+            // "January 5 10:00" is a time, not a year.
+            Some((year, 4)) if self.peek() != Some(b':') => self.parsed.year = Some(year),
+            _ => self.position = start,
+        }
+    }
+
+    /// A sign, which starts either a relative offset ("+1 day") or a UTC offset
+    /// ("+05:00") after a time.
+    fn signed(&mut self) -> Result<(), ParseError> {
+        let start = self.position;
+        let sign = if self.text[self.position] == b'-' { -1 } else { 1 };
+
+        self.position += 1;
+        self.spaces();
+
+        let (value, length) = match self.digits() {
+            Some(digits) => digits,
+            None => {
+                self.position = start;
+                return Err(self.error());
+            },
+        };
+
+        let word = self.peek_word();
+
+        if let Some(unit) = unit(&word) {
+            self.spaces();
+            self.word();
+            self.parsed.relative.add(unit, sign * value);
+
+            return Ok(());
+        }
+
+        let (hours, minutes) = match length {
+            1 | 2 if self.eat(b':') => (value, self.number(2, 2)?),
+            1 | 2 => (value, 0),
+            4 => (value / 100, value % 100),
+            _ => {
+                self.position = start;
+                return Err(self.error());
+            },
+        };
+
+        match FixedOffset::east_opt(sign as i32 * (hours * 3600 + minutes * 60) as i32) {
+            Some(offset) if self.parsed.zone.is_none() => self.parsed.zone = Some(Zone::Offset(offset)),
+            _ => {
+                self.position = start;
+                return Err(self.error());
+            },
+        }
+
+        Ok(())
+    }
+
+    fn text(&mut self) -> Result<(), ParseError> {
+        let start = self.position;
+        let word = self.word();
+
+        match word.as_str() {
+            "now" => (),
+            "today" | "midnight" => self.parsed.time = Some((0, 0, 0, 0)),
+            "noon" => self.parsed.time = Some((12, 0, 0, 0)),
+            "tomorrow" => {
+                self.parsed.relative.days += 1;
+                self.parsed.time = Some((0, 0, 0, 0));
+            },
+            "yesterday" => {
+                self.parsed.relative.days -= 1;
+                self.parsed.time = Some((0, 0, 0, 0));
+            },
+            "ago" => self.parsed.relative.negate(),
+            "t" if self.peek().is_some_and(|c| c.is_ascii_digit()) => (),
+            "utc" | "gmt" | "z" if self.parsed.zone.is_none() => self.parsed.zone = Some(Zone::Named(Tz::UTC)),
+            "first" | "last" if self.day_of() => self.parsed.first_day_of = Some(word == "first"),
+            "next" | "last" | "previous" | "this" => {
+                self.spaces();
+
+                let target_start = self.position;
+                let target = self.word();
+                let amount = match word.as_str() {
+                    "next" => 1,
+                    "this" => 0,
+                    _ => -1,
+                };
+
+                if let Some(unit) = unit(&target) {
+                    self.parsed.relative.add(unit, amount);
+                } else if let Some(day) = weekday(&target) {
+                    let occurrence = match amount {
+                        1 => Occurrence::Next,
+                        0 => Occurrence::This,
+                        _ => Occurrence::Last,
+                    };
+
+                    self.set_weekday(day, occurrence, target_start)?;
+                } else {
+                    self.position = target_start;
+                    return Err(self.error());
+                }
+            },
+            word => if let Some(day) = weekday(word) {
+                self.set_weekday(day, Occurrence::This, start)?;
+            } else if let Some(month) = month(word) {
+                self.parsed.month = Some(month as i64);
+                self.skip(|c| c == b' ' || c == b'.');
+
+                let day_start = self.position;
+
+                match self.digits() {
+                    // "January 2024" means the first of the month.
+                    Some((year, 4)) => {
+                        self.parsed.year = Some(year);
+                        self.parsed.day = Some(1);
+                    },
+                    Some((day, 1 | 2)) => {
+                        let suffix_start = self.position;
+
+                        if ! matches!(self.word().as_str(), "st" | "nd" | "rd" | "th") {
+                            self.position = suffix_start;
+                        }
+
+                        // A time like "Jan 5 10:00" isn't a day.
+                        if self.peek() == Some(b':') {
+                            self.position = day_start;
+                            self.parsed.day = Some(1);
+                        } else {
+                            self.parsed.day = Some(day);
+                            self.textual_year();
+                        }
+                    },
+                    _ => {
+                        self.position = day_start;
+                        self.parsed.day = Some(1);
+                    },
+                }
+            } else {
+                self.position = start;
+                return Err(self.error());
+            },
+        }
+
+        Ok(())
+    }
+
+    fn set_weekday(&mut self, day: u32, occurrence: Occurrence, start: usize) -> Result<(), ParseError> {
+        if self.parsed.weekday.is_some() {
+            self.position = start;
+            return Err(self.error());
+        }
+
+        self.parsed.weekday = Some((day, occurrence));
+        self.parsed.time.get_or_insert((0, 0, 0, 0));
+
+        Ok(())
+    }
+
+    /// Checks for (and consumes) the "day of" in "first day of".
+    fn day_of(&mut self) -> bool {
+        let start = self.position;
+
+        self.spaces();
+
+        if self.word() == "day" {
+            self.spaces();
+
+            if self.word() == "of" {
+                return true;
+            }
+        }
+
+        self.position = start;
+
+        false
+    }
+}
+
+impl Parsed {
+    /// Applies everything that was parsed to a base time.
+    fn resolve(&self, base: Moment) -> Option<Moment> {
+        let zone = self.zone.unwrap_or(base.zone);
+        let base = match self.timestamp {
+            Some((seconds, micros)) => Moment { time: zone.at(seconds, micros)?, zone },
+            None => Moment { time: base.time.with_timezone(&zone.at(0, 0)?.timezone()), zone },
+        };
+
+        let local = base.local();
+        let has_date = self.year.is_some() || self.month.is_some() || self.day.is_some();
+
+        let (hour, minute, second, micros) = match (self.time, has_date) {
+            (Some(time), _) => time,
+            (None, true) => (0, 0, 0, 0),
+            (None, false) => (local.hour() as i64, local.minute() as i64, local.second() as i64, (local.nanosecond() / 1000) as i64),
+        };
+
+        let year = self.year.unwrap_or(local.year() as i64) + self.relative.years;
+        let month = self.month.unwrap_or(local.month() as i64) + self.relative.months;
+        let mut day = self.day.unwrap_or(local.day() as i64);
+
+        if let Some(first) = self.first_day_of {
+            let normalized = build(year, month, 1, 0, 0, 0, 0)?;
+
+            day = match first {
+                true => 1,
+                false => days_in_month(normalized.year(), normalized.month()) as i64,
+            };
+        }
+
+        let mut date = build(year, month, day + self.relative.days, 0, 0, 0, 0)?.date();
+
+        if let Some((target, occurrence)) = self.weekday {
+            let current = date.weekday().num_days_from_sunday() as i64;
+            let target = target as i64;
+
+            date = match occurrence {
+                Occurrence::This => date + Duration::days((target - current).rem_euclid(7)),
+                Occurrence::Next => date + Duration::days(match (target - current).rem_euclid(7) { 0 => 7, days => days }),
+                Occurrence::Last => date - Duration::days(match (current - target).rem_euclid(7) { 0 => 7, days => days }),
+            };
+        }
+
+        let local = build(
+            date.year() as i64,
+            date.month() as i64,
+            date.day() as i64,
+            hour + self.relative.hours,
+            minute + self.relative.minutes,
+            second + self.relative.seconds,
+            micros + self.relative.micros,
+        )?;
+
+        base.with_local(local)
+    }
+}
+
+/// Parses a date/time string relative to a base time, the way `strtotime()` and
+/// `date_create()` do.
+fn parse(text: &str, base: Moment) -> Result<Moment, Option<ParseError>> {
+    Parser::parse(text).map_err(Some)?.resolve(base).ok_or(None)
+}
+
+/// Formats a time with a `date()` format string.
+fn format(moment: &Moment, format: &str) -> String {
+    let time = moment.time;
+    let offset = time.offset().local_minus_utc();
+    let mut formatted = String::new();
+    let mut chars = format.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            'd' => formatted.push_str(&format!("{:02}", time.day())),
+            'D' => formatted.push_str(&time.format("%a").to_string()),
+            'j' => formatted.push_str(&time.day().to_string()),
+            'l' => formatted.push_str(&time.format("%A").to_string()),
+            'N' => formatted.push_str(&time.weekday().number_from_monday().to_string()),
+            'S' => formatted.push_str(match time.day() {
+                1 | 21 | 31 => "st",
+                2 | 22 => "nd",
+                3 | 23 => "rd",
+                _ => "th",
+            }),
+            'w' => formatted.push_str(&time.weekday().num_days_from_sunday().to_string()),
+            'z' => formatted.push_str(&time.ordinal0().to_string()),
+            'W' => formatted.push_str(&format!("{:02}", time.iso_week().week())),
+            'F' => formatted.push_str(&time.format("%B").to_string()),
+            'm' => formatted.push_str(&format!("{:02}", time.month())),
+            'M' => formatted.push_str(&time.format("%b").to_string()),
+            'n' => formatted.push_str(&time.month().to_string()),
+            't' => formatted.push_str(&days_in_month(time.year(), time.month()).to_string()),
+            'L' => formatted.push(if days_in_month(time.year(), 2) == 29 { '1' } else { '0' }),
+            'o' => formatted.push_str(&time.iso_week().year().to_string()),
+            'Y' if time.year() < 0 => formatted.push_str(&format!("-{:04}", -time.year())),
+            'Y' => formatted.push_str(&format!("{:04}", time.year())),
+            'y' => formatted.push_str(&format!("{:02}", time.year().rem_euclid(100))),
+            'a' => formatted.push_str(if time.hour() < 12 { "am" } else { "pm" }),
+            'A' => formatted.push_str(if time.hour() < 12 { "AM" } else { "PM" }),
+            'B' => {
+                let seconds = (time.timestamp() + 3600).rem_euclid(86400);
+
+                formatted.push_str(&format!("{:03}", seconds * 10 / 864));
+            },
+            'g' => formatted.push_str(&time.hour12().1.to_string()),
+            'G' => formatted.push_str(&time.hour().to_string()),
+            'h' => formatted.push_str(&format!("{:02}", time.hour12().1)),
+            'H' => formatted.push_str(&format!("{:02}", time.hour())),
+            'i' => formatted.push_str(&format!("{:02}", time.minute())),
+            's' => formatted.push_str(&format!("{:02}", time.second())),
+            'u' => formatted.push_str(&format!("{:06}", time.nanosecond() / 1000)),
+            'v' => formatted.push_str(&format!("{:03}", time.nanosecond() / 1_000_000)),
+            'e' => formatted.push_str(&moment.zone.name()),
+            'I' => formatted.push(match moment.zone {
+                Zone::Named(tz) if ! tz.offset_from_utc_datetime(&time.naive_utc()).dst_offset().is_zero() => '1',
+                _ => '0',
+            }),
+            'O' => formatted.push_str(&format_offset(offset, false)),
+            'P' => formatted.push_str(&format_offset(offset, true)),
+            'p' if offset == 0 => formatted.push('Z'),
+            'p' => formatted.push_str(&format_offset(offset, true)),
+            'T' => formatted.push_str(&match moment.zone {
+                Zone::Named(tz) => tz.offset_from_utc_datetime(&time.naive_utc()).abbreviation().map_or_else(|| format_offset(offset, true), String::from),
+                Zone::Offset(..) => format_offset(offset, true),
+            }),
+            'Z' => formatted.push_str(&offset.to_string()),
+            'c' => formatted.push_str(&self::format(moment, "Y-m-d\\TH:i:sP")),
+            'r' => formatted.push_str(&self::format(moment, "D, d M Y H:i:s O")),
+            'U' => formatted.push_str(&time.timestamp().to_string()),
+            '\\' => if let Some(next) = chars.next() {
+                formatted.push(next);
+            },
+            c => formatted.push(c),
+        }
+    }
+
+    formatted
+}
+
+fn default_zone(vm: &Machine) -> Zone {
+    Zone::Named(vm.timezone)
+}
+
+/// The moment a timestamp argument refers to, defaulting to now when it's `null`.
+fn at(zone: Zone, timestamp: Option<&Object>) -> Moment {
+    match timestamp {
+        Some(Object::Null) | None => Moment::now(zone),
+        Some(timestamp) => {
+            let timestamp = convert::to_int(timestamp);
+
+            match zone.at(timestamp, 0) {
+                Some(time) => Moment { time, zone },
+                None => error::throw("ValueError", format!("Timestamp {} is out of range", timestamp)),
+            }
+        },
+    }
+}
+
+pub fn time(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 0);
+
+    Object::Integer(now().0)
+}
+
+pub fn microtime(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    let (seconds, micros) = now();
+
+    match args.first().is_some_and(Object::to_bool) {
+        true => Object::Float(seconds as f64 + micros as f64 / 1e6),
+        false => Object::String(format!("{:.8} {}", micros as f64 / 1e6, seconds).into()),
+    }
+}
+
+pub fn hrtime(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    let mut time = libc::timespec { tv_sec: 0, tv_nsec: 0 };
+
+    unsafe {
+        libc::clock_gettime(libc::CLOCK_MONOTONIC, &mut time);
+    }
+
+    match args.first().is_some_and(Object::to_bool) {
+        true => Object::Integer(time.tv_sec * 1_000_000_000 + time.tv_nsec),
+        false => Object::from_array(vec![Object::Integer(time.tv_sec), Object::Integer(time.tv_nsec)].into_iter().collect()),
+    }
+}
+
+pub fn date(vm: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    let moment = at(default_zone(vm), args.get(1));
+
+    Object::String(format(&moment, &convert::to_string(&args[0])).into())
+}
+
+pub fn gmdate(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    let moment = at(Zone::Named(Tz::UTC), args.get(1));
+
+    Object::String(format(&moment, &convert::to_string(&args[0])).into())
+}
+
+fn make_time(zone: Zone, args: &[Object]) -> Object {
+    let now = Moment::now(zone).local();
+    let field = |position: usize, default: u32| match args.get(position) {
+        Some(Object::Null) | None => default as i64,
+        Some(value) => convert::to_int(value),
+    };
+
+    let year = match args.get(5) {
+        Some(Object::Null) | None => now.year() as i64,
+        Some(year) => match convert::to_int(year) {
+            year @ 0..=69 => year + 2000,
+            year @ 70..=100 => year + 1900,
+            year => year,
+        },
+    };
+
+    build(year, field(3, now.month()), field(4, now.day()), field(0, now.hour()), field(1, now.minute()), field(2, now.second()), 0)
+        .and_then(|local| zone.resolve(local))
+        .map_or(Object::False, |time| Object::Integer(time.timestamp()))
+}
+
+pub fn mktime(vm: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 6);
+
+    make_time(default_zone(vm), &args)
+}
+
+pub fn gmmktime(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 6);
+
+    make_time(Zone::Named(Tz::UTC), &args)
+}
+
+pub fn checkdate(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 3);
+
+    let month = convert::to_int(&args[0]);
+    let day = convert::to_int(&args[1]);
+    let year = convert::to_int(&args[2]);
+
+    Object::from_bool((1..=12).contains(&month) && (1..=32767).contains(&year) && day >= 1 && day <= days_in_month(year as i32, month as u32) as i64)
+}
+
+pub fn strtotime(vm: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    let text = convert::to_string(&args[0]);
+    let base = at(default_zone(vm), args.get(1));
+
+    // Unlike `date_create("")`, an empty string isn't read as "now".
+    if text.is_empty() {
+        return Object::False;
+    }
+
+    match parse(&text, base) {
+        Ok(moment) => Object::Integer(moment.time.timestamp()),
+        Err(_) => Object::False,
+    }
+}
+
+pub fn date_default_timezone_set(vm: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    let name = convert::to_string(&args[0]);
+
+    match Zone::find(&name) {
+        Some(Zone::Named(tz)) => {
+            vm.timezone = tz;
+            Object::True
+        },
+        _ => {
+            error::notice(format!("date_default_timezone_set(): Timezone ID '{}' is invalid", name));
+            Object::False
+        },
+    }
+}
+
+pub fn date_default_timezone_get(vm: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 0);
+
+    Object::String(vm.timezone.name().into())
+}
+
+/// Reads a property of a date object. They're always there since the objects can only
+/// be created internally.
+fn property(instance: &Instance, name: &str) -> Object {
+    instance.properties.get(&Key::String(name.into())).cloned().unwrap_or(Object::Null)
+}
+
+fn zone_properties(zone: Zone, properties: &mut Array) {
+    properties.insert(Key::String("timezone_type".into()), Object::Integer(zone.kind()));
+    properties.insert(Key::String("timezone".into()), Object::String(zone.name().into()));
+}
+
+fn zone_from(instance: &Instance) -> Zone {
+    let name = convert::to_string(&property(instance, "timezone"));
+
+    match Zone::find(&name) {
+        Some(zone) => zone,
+        None => match Parser::parse(&name).ok().and_then(|parsed| parsed.zone) {
+            Some(zone) => zone,
+            None => Zone::Named(Tz::UTC),
+        },
+    }
+}
+
+/// A `DateTime` or `DateTimeImmutable` keeps its state in the same three properties
+/// that `var_dump()` shows in PHP.
+fn date_properties(moment: &Moment) -> Array {
+    let mut properties = Array::new();
+
+    properties.insert(Key::String("date".into()), Object::String(moment.time.format("%Y-%m-%d %H:%M:%S%.6f").to_string().into()));
+    zone_properties(moment.zone, &mut properties);
+
+    properties
+}
+
+fn new_date(class: &str, moment: &Moment) -> Object {
+    new_instance(class, date_properties(moment))
+}
+
+fn date_object(function: &str, position: usize, object: &Object) -> (Rc<RefCell<Instance>>, Moment) {
+    match object {
+        Object::Instance(instance) if matches!(instance.borrow().class.as_str(), "DateTime" | "DateTimeImmutable") => {
+            let moment = {
+                let instance = instance.borrow();
+                let zone = zone_from(&instance);
+                let date = convert::to_string(&property(&instance, "date"));
+                let local = NaiveDateTime::parse_from_str(&date, "%Y-%m-%d %H:%M:%S%.f").unwrap();
+
+                Moment { time: zone.resolve(local).unwrap(), zone }
+            };
+
+            (instance.clone(), moment)
+        },
+        value => error::throw("TypeError", format!("{}(): Argument #{} ($object) must be of type DateTimeInterface, {} given", function, position, value.type_name())),
+    }
+}
+
+fn zone_object(function: &str, position: usize, object: &Object) -> Zone {
+    match object {
+        Object::Instance(instance) if instance.borrow().class == "DateTimeZone" => zone_from(&instance.borrow()),
+        value => error::throw("TypeError", format!("{}(): Argument #{} ($timezone) must be of type DateTimeZone, {} given", function, position, value.type_name())),
+    }
+}
+
+/// Stores a changed time in a date object. A `DateTime` is changed in place, while a
+/// `DateTimeImmutable` is copied, and the object with the change is returned.
+fn update(instance: Rc<RefCell<Instance>>, moment: &Moment) -> Object {
+    if instance.borrow().class == "DateTimeImmutable" {
+        return new_date("DateTimeImmutable", moment);
+    }
+
+    instance.borrow_mut().properties = date_properties(moment);
+
+    Object::Instance(instance)
+}
+
+fn create(vm: &mut Machine, function: &str, class: &str, args: &[Object]) -> Result<Object, Option<ParseError>> {
+    let text = args.first().map(convert::to_string).unwrap_or_else(|| "now".into());
+    let zone = match args.get(1) {
+        Some(Object::Null) | None => default_zone(vm),
+        Some(zone) => zone_object(function, 2, zone),
+    };
+
+    parse(&text, Moment::now(zone)).map(|moment| new_date(class, &moment))
+}
+
+/// `new DateTime()` and `new DateTimeImmutable()` throw where `date_create()` returns false.
+fn construct(vm: &mut Machine, class: &str, args: Vec<Object>) -> Object {
+    let function = format!("{}::__construct", class);
+    let text = args.first().map(convert::to_string).unwrap_or_else(|| "now".into());
+
+    match create(vm, &function, class, &args) {
+        Ok(date) => date,
+        Err(Some(e)) => error::throw("Exception", format!("{}(): Failed to parse time string ({}) at position {} ({}): {}", function, text, e.position, e.character, e.message())),
+        Err(None) => error::throw("Exception", format!("{}(): Failed to parse time string ({})", function, text)),
+    }
+}
+
+pub fn date_create(vm: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    create(vm, "date_create", "DateTime", &args).unwrap_or(Object::False)
+}
+
+pub fn date_create_immutable(vm: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    create(vm, "date_create_immutable", "DateTimeImmutable", &args).unwrap_or(Object::False)
+}
+
+pub fn datetime_construct(vm: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    construct(vm, "DateTime", args)
+}
+
+pub fn datetime_immutable_construct(vm: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    construct(vm, "DateTimeImmutable", args)
+}
+
+pub fn date_format(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    let (_, moment) = date_object("date_format", 1, &args[0]);
+
+    Object::String(format(&moment, &convert::to_string(&args[1])).into())
+}
+
+pub fn date_modify(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    let (instance, moment) = date_object("date_modify", 1, &args[0]);
+    let modifier = convert::to_string(&args[1]);
+
+    match parse(&modifier, moment) {
+        Ok(modified) => update(instance, &modified),
+        Err(Some(e)) => {
+            error::warning(format!("date_modify(): Failed to parse time string ({}) at position {} ({}): {}", modifier, e.position, e.character, e.message()));
+            Object::False
+        },
+        Err(None) => Object::False,
+    }
+}
+
+pub fn date_timestamp_get(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    let (_, moment) = date_object("date_timestamp_get", 1, &args[0]);
+
+    Object::Integer(moment.time.timestamp())
+}
+
+pub fn date_timestamp_set(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    let (instance, moment) = date_object("date_timestamp_set", 1, &args[0]);
+    let timestamp = convert::to_int(&args[1]);
+
+    match moment.zone.at(timestamp, 0) {
+        Some(time) => update(instance, &Moment { time, zone: moment.zone }),
+        None => error::throw("ValueError", format!("Timestamp {} is out of range", timestamp)),
+    }
+}
+
+pub fn date_date_set(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 4);
+
+    let (instance, moment) = date_object("date_date_set", 1, &args[0]);
+    let local = moment.local();
+
+    build(convert::to_int(&args[1]), convert::to_int(&args[2]), convert::to_int(&args[3]), local.hour() as i64, local.minute() as i64, local.second() as i64, (local.nanosecond() / 1000) as i64)
+        .and_then(|local| moment.with_local(local))
+        .map_or(Object::False, |moment| update(instance, &moment))
+}
+
+pub fn date_time_set(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 5);
+
+    let (instance, moment) = date_object("date_time_set", 1, &args[0]);
+    let local = moment.local();
+    let second = args.get(3).map(convert::to_int).unwrap_or(0);
+    let micros = args.get(4).map(convert::to_int).unwrap_or(0);
+
+    build(local.year() as i64, local.month() as i64, local.day() as i64, convert::to_int(&args[1]), convert::to_int(&args[2]), second, micros)
+        .and_then(|local| moment.with_local(local))
+        .map_or(Object::False, |moment| update(instance, &moment))
+}
+
+pub fn date_timezone_get(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    let (_, moment) = date_object("date_timezone_get", 1, &args[0]);
+    let mut properties = Array::new();
+
+    zone_properties(moment.zone, &mut properties);
+
+    new_instance("DateTimeZone", properties)
+}
+
+pub fn date_timezone_set(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    let (instance, moment) = date_object("date_timezone_set", 1, &args[0]);
+    let zone = zone_object("date_timezone_set", 2, &args[1]);
+
+    match zone.at(moment.time.timestamp(), moment.time.timestamp_subsec_micros()) {
+        Some(time) => update(instance, &Moment { time, zone }),
+        None => Object::False,
+    }
+}
+
+/// A timezone name as `timezone_open()` takes it: a tz database identifier, an
+/// abbreviation or an offset.
+fn open_zone(name: &str) -> Option<Object> {
+    let zone = match Zone::find(name) {
+        Some(zone) => zone,
+        None => Parser::parse(name).ok().filter(|parsed| parsed.relative.is_empty()).and_then(|parsed| parsed.zone)?,
+    };
+
+    let mut properties = Array::new();
+
+    zone_properties(zone, &mut properties);
+
+    Some(new_instance("DateTimeZone", properties))
+}
+
+pub fn timezone_open(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    let name = convert::to_string(&args[0]);
+
+    open_zone(&name).unwrap_or_else(|| {
+        error::warning(format!("timezone_open(): Unknown or bad timezone ({})", name));
+        Object::False
+    })
+}
+
+pub fn timezone_construct(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    let name = convert::to_string(&args[0]);
+
+    open_zone(&name).unwrap_or_else(|| error::throw("Exception", format!("DateTimeZone::__construct(): Unknown or bad timezone ({})", name)))
+}
+
+pub fn timezone_name_get(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    Object::String(zone_object("timezone_name_get", 1, &args[0]).name().into())
+}
+
+/// A `DateInterval`, from `date_diff()` or a relative date string.
+#[derive(Debug, Default)]
+struct Interval {
+    relative: Relative,
+    invert: bool,
+    days: Option<i64>,
+}
+
+impl Interval {
+    fn to_object(&self) -> Object {
+        let relative = &self.relative;
+        let mut properties = Array::new();
+
+        for (name, value) in [("y", relative.years), ("m", relative.months), ("d", relative.days), ("h", relative.hours), ("i", relative.minutes), ("s", relative.seconds)] {
+            properties.insert(Key::String(name.into()), Object::Integer(value));
+        }
+
+        properties.insert(Key::String("f".into()), Object::Float(relative.micros as f64 / 1e6));
+        properties.insert(Key::String("invert".into()), Object::Integer(self.invert as i64));
+        properties.insert(Key::String("days".into()), self.days.map_or(Object::False, Object::Integer));
+        properties.insert(Key::String("from_string".into()), Object::False);
+
+        new_instance("DateInterval", properties)
+    }
+
+    fn from_object(function: &str, position: usize, object: &Object) -> Interval {
+        let instance = match object {
+            Object::Instance(instance) if instance.borrow().class == "DateInterval" => instance.borrow(),
+            value => error::throw("TypeError", format!("{}(): Argument #{} ($interval) must be of type DateInterval, {} given", function, position, value.type_name())),
+        };
+
+        let field = |name: &str| convert::to_int(&property(&instance, name));
+
+        Interval {
+            relative: Relative {
+                years: field("y"),
+                months: field("m"),
+                days: field("d"),
+                hours: field("h"),
+                minutes: field("i"),
+                seconds: field("s"),
+                micros: (convert::to_float(&property(&instance, "f")) * 1e6).round() as i64,
+            },
+            invert: field("invert") != 0,
+            days: match property(&instance, "days") {
+                Object::False => None,
+                days => Some(convert::to_int(&days)),
+            },
+        }
+    }
+
+    /// The difference between two wall-clock times, borrowing from each larger unit
+    /// the same way timelib does.
+    fn between(earlier: NaiveDateTime, later: NaiveDateTime) -> Relative {
+        let mut relative = Relative {
+            years: later.year() as i64 - earlier.year() as i64,
+            months: later.month() as i64 - earlier.month() as i64,
+            days: later.day() as i64 - earlier.day() as i64,
+            hours: later.hour() as i64 - earlier.hour() as i64,
+            minutes: later.minute() as i64 - earlier.minute() as i64,
+            seconds: later.second() as i64 - earlier.second() as i64,
+            micros: (later.nanosecond() / 1000) as i64 - (earlier.nanosecond() / 1000) as i64,
+        };
+
+        if relative.micros < 0 {
+            relative.micros += 1_000_000;
+            relative.seconds -= 1;
+        }
+
+        if relative.seconds < 0 {
+            relative.seconds += 60;
+            relative.minutes -= 1;
+        }
+
+        if relative.minutes < 0 {
+            relative.minutes += 60;
+            relative.hours -= 1;
+        }
+
+        if relative.hours < 0 {
+            relative.hours += 24;
+            relative.days -= 1;
+        }
+
+        let (mut year, mut month) = (earlier.year(), earlier.month());
+
+        while relative.days < 0 {
+            relative.days += days_in_month(year, month) as i64;
+            relative.months -= 1;
+
+            (year, month) = if month == 12 { (year + 1, 1) } else { (year, month + 1) };
+        }
+
+        if relative.months < 0 {
+            relative.months += 12;
+            relative.years -= 1;
+        }
+
+        relative
+    }
+}
+
+pub fn date_diff(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 3);
+
+    let (_, first) = date_object("date_diff", 1, &args[0]);
+    let (_, second) = date_object("date_diff", 2, &args[1]);
+    let absolute = args.get(2).is_some_and(Object::to_bool);
+
+    // Times in different offsets are compared in UTC.
+    let (first, second) = match first.time.offset() == second.time.offset() {
+        true => (first.local(), second.local()),
+        false => (first.time.naive_utc(), second.time.naive_utc()),
+    };
+
+    let invert = second < first;
+    let (earlier, later) = if invert { (second, first) } else { (first, second) };
+
+    Interval {
+        relative: Interval::between(earlier, later),
+        invert: invert && ! absolute,
+        days: Some((later - earlier).num_days()),
+    }.to_object()
+}
+
+fn shift(function: &str, args: &[Object], direction: i64) -> Object {
+    let (instance, moment) = date_object(function, 1, &args[0]);
+    let interval = Interval::from_object(function, 2, &args[1]);
+    let mut relative = interval.relative;
+
+    if interval.invert != (direction < 0) {
+        relative.negate();
+    }
+
+    relative.apply(moment.local())
+        .and_then(|local| moment.with_local(local))
+        .map_or(Object::False, |moment| update(instance, &moment))
+}
+
+pub fn date_add(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    shift("date_add", &args, 1)
+}
+
+pub fn date_sub(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    shift("date_sub", &args, -1)
+}
+
+pub fn date_interval_create_from_date_string(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    let text = convert::to_string(&args[0]);
+
+    match Parser::parse(&text) {
+        Ok(parsed) => Interval { relative: parsed.relative, invert: false, days: None }.to_object(),
+        Err(e) => {
+            error::warning(format!("date_interval_create_from_date_string(): Unknown or bad format ({}) at position {} ({}): {}", text, e.position, e.character, e.message()));
+            Object::False
+        },
+    }
+}
+
+/// Parses an ISO 8601 duration like "P1Y2M3DT4H5M6S". Weeks can be combined with days.
+fn duration(text: &str) -> Option<Relative> {
+    let mut relative = Relative::default();
+    let mut number = String::new();
+    let mut time = false;
+    let mut empty = true;
+
+    for c in text.strip_prefix('P')?.chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'T' if ! time && number.is_empty() => time = true,
+            _ => {
+                let value: i64 = number.parse().ok()?;
+
+                match (time, c) {
+                    (false, 'Y') => relative.years = value,
+                    (false, 'M') => relative.months = value,
+                    (false, 'W') => relative.days += value * 7,
+                    (false, 'D') => relative.days += value,
+                    (true, 'H') => relative.hours = value,
+                    (true, 'M') => relative.minutes = value,
+                    (true, 'S') => relative.seconds = value,
+                    _ => return None,
+                }
+
+                number.clear();
+                empty = false;
+            },
+        }
+    }
+
+    match number.is_empty() && ! empty {
+        true => Some(relative),
+        false => None,
+    }
+}
+
+pub fn interval_construct(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    let text = convert::to_string(&args[0]);
+
+    match duration(&text) {
+        Some(relative) => Interval { relative, invert: false, days: None }.to_object(),
+        None => error::throw("Exception", format!("DateInterval::__construct(): Unknown or bad format ({})", text)),
+    }
+}
+
+pub fn date_interval_format(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    let interval = Interval::from_object("date_interval_format", 1, &args[0]);
+    let relative = &interval.relative;
+    let format = convert::to_string(&args[1]);
+    let mut formatted = String::new();
+    let mut chars = format.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            formatted.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('Y') => formatted.push_str(&format!("{:04}", relative.years)),
+            Some('y') => formatted.push_str(&relative.years.to_string()),
+            Some('M') => formatted.push_str(&format!("{:02}", relative.months)),
+            Some('m') => formatted.push_str(&relative.months.to_string()),
+            Some('D') => formatted.push_str(&format!("{:02}", relative.days)),
+            Some('d') => formatted.push_str(&relative.days.to_string()),
+            Some('H') => formatted.push_str(&format!("{:02}", relative.hours)),
+            Some('h') => formatted.push_str(&relative.hours.to_string()),
+            Some('I') => formatted.push_str(&format!("{:02}", relative.minutes)),
+            Some('i') => formatted.push_str(&relative.minutes.to_string()),
+            Some('S') => formatted.push_str(&format!("{:02}", relative.seconds)),
+            Some('s') => formatted.push_str(&relative.seconds.to_string()),
+            Some('F') => formatted.push_str(&format!("{:06}", relative.micros)),
+            Some('f') => formatted.push_str(&relative.micros.to_string()),
+            Some('a') => formatted.push_str(&interval.days.map_or_else(|| "(unknown)".into(), |days| days.to_string())),
+            Some('R') => formatted.push(if interval.invert { '-' } else { '+' }),
+            Some('r') => if interval.invert {
+                formatted.push('-');
+            },
+            Some('%') => formatted.push('%'),
+            Some(c) => {
+                formatted.push('%');
+                formatted.push(c);
+            },
+            None => formatted.push('%'),
+        }
+    }
+
+    Object::String(formatted.into())
+}
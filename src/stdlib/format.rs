@@ -0,0 +1,382 @@
+use super::{arity, round_float, write, PHP_ROUND_HALF_UP};
+use crate::object::{Object, ByteString, convert};
+use crate::vm::Machine;
+use crate::error;
+use std::io::Write;
+
+// The format-spec engine behind `sprintf()` and friends, ported from PHP's
+// `ext/standard/formatted_print.c` so that padding and rounding come out identical.
+
+/// The largest precision PHP allows for floating point conversions.
+const MAX_FLOAT_PRECISION: usize = 53;
+
+#[derive(PartialEq)]
+enum Alignment {
+    Left,
+    Right,
+}
+
+struct Spec {
+    alignment: Alignment,
+    padding: u8,
+    always_sign: bool,
+    width: usize,
+    precision: Option<usize>,
+}
+
+impl Spec {
+    /// Pads `value` out to the spec's width. The sign of a zero-padded number is kept in
+    /// front of the padding, so `%05d` gives `-0042` rather than `000-42`.
+    fn pad(&self, buffer: &mut Vec<u8>, value: &[u8], signed: bool) {
+        let padding = self.width.saturating_sub(value.len());
+        let mut value = value;
+
+        if self.alignment == Alignment::Right {
+            if signed && self.padding == b'0' {
+                buffer.push(value[0]);
+                value = &value[1..];
+            }
+
+            buffer.extend(std::iter::repeat_n(self.padding, padding));
+        }
+
+        buffer.extend_from_slice(value);
+
+        if self.alignment == Alignment::Left {
+            buffer.extend(std::iter::repeat_n(self.padding, padding));
+        }
+    }
+
+    fn signed(&self, mut value: String, negative: bool) -> (String, bool) {
+        if ! negative && self.always_sign {
+            value.insert(0, '+');
+        }
+
+        (value, negative || self.always_sign)
+    }
+}
+
+/// Where the arguments for a format string came from, which changes the error that's
+/// thrown when there aren't enough of them.
+#[derive(Clone, Copy)]
+enum Source {
+    Arguments,
+    Array,
+}
+
+fn exponential(value: f64, precision: usize, exponent_char: char) -> String {
+    let formatted = format!("{:.*e}", precision, value.abs());
+    let (mantissa, exponent) = formatted.split_once('e').unwrap();
+    let exponent: i32 = exponent.parse().unwrap();
+
+    format!("{}{}{}{}", mantissa, exponent_char, if exponent < 0 { '-' } else { '+' }, exponent.abs())
+}
+
+fn float(spec: &Spec, value: f64, conversion: u8) -> (String, bool) {
+    let negative = value.is_sign_negative() && value != 0.0;
+
+    if value.is_nan() {
+        return ("NaN".into(), false);
+    }
+
+    if value.is_infinite() {
+        return spec.signed(if negative { "-Inf" } else { "Inf" }.into(), negative);
+    }
+
+    let mut precision = spec.precision.unwrap_or(6);
+
+    if precision > MAX_FLOAT_PRECISION {
+        error::notice(format!("Requested precision of {} digits was truncated to PHP maximum of {} digits", precision, MAX_FLOAT_PRECISION));
+        precision = MAX_FLOAT_PRECISION;
+    }
+
+    let formatted = match conversion {
+        b'e' | b'E' => exponential(value, precision, conversion as char),
+        b'g' | b'G' => {
+            let exponent_char = if conversion == b'g' { 'e' } else { 'E' };
+
+            convert::gcvt(value.abs(), precision.max(1) as i32, exponent_char)
+        },
+        _ => format!("{:.*}", precision, value.abs()),
+    };
+
+    spec.signed(if negative { format!("-{}", formatted) } else { formatted }, negative)
+}
+
+fn format(format: &[u8], args: &[Object], source: Source) -> Vec<u8> {
+    let mut buffer = Vec::with_capacity(format.len());
+    let mut next_argument = 0;
+    let mut missing: Option<usize> = None;
+    let mut i = 0;
+
+    let unknown = |c: u8| -> ! {
+        error::throw("ValueError", format!("Unknown format specifier \"{}\"", c as char))
+    };
+
+    while i < format.len() {
+        if format[i] != b'%' {
+            buffer.push(format[i]);
+            i += 1;
+            continue;
+        }
+
+        i += 1;
+
+        if format.get(i) == Some(&b'%') {
+            buffer.push(b'%');
+            i += 1;
+            continue;
+        }
+
+        let number = |i: &mut usize| {
+            let start = *i;
+
+            while format.get(*i).is_some_and(u8::is_ascii_digit) {
+                *i += 1;
+            }
+
+            std::str::from_utf8(&format[start..*i]).unwrap().parse::<usize>().ok()
+        };
+
+        // An explicit argument number, as in `%2$s`.
+        let mut position = None;
+        let start = i;
+
+        if let Some(n) = number(&mut i) {
+            if format.get(i) == Some(&b'$') {
+                if n == 0 || n > i32::MAX as usize {
+                    error::throw("ValueError", "Argument number specifier must be greater than zero and less than 2147483647");
+                }
+
+                position = Some(n - 1);
+                i += 1;
+            } else {
+                i = start;
+            }
+        }
+
+        let mut spec = Spec { alignment: Alignment::Right, padding: b' ', always_sign: false, width: 0, precision: None };
+
+        loop {
+            match format.get(i) {
+                Some(b'-') => spec.alignment = Alignment::Left,
+                Some(b'+') => spec.always_sign = true,
+                Some(b'0') => spec.padding = b'0',
+                Some(b' ') => spec.padding = b' ',
+                Some(b'\'') => match format.get(i + 1) {
+                    Some(&c) => {
+                        spec.padding = c;
+                        i += 1;
+                    },
+                    None => error::throw("ValueError", "Missing padding character"),
+                },
+                _ => break,
+            }
+
+            i += 1;
+        }
+
+        let mut star = |i: &mut usize, what: &str| -> usize {
+            *i += 1;
+
+            let argument = next_argument;
+            next_argument += 1;
+
+            match args.get(argument) {
+                Some(Object::Integer(n)) if *n >= 0 && *n <= i32::MAX as i64 => *n as usize,
+                Some(Object::Integer(..)) => error::throw("ValueError", format!("{} must be greater than or equal to 0 and less than {}", what, i32::MAX)),
+                Some(..) => error::throw("ValueError", format!("{} must be an integer", what)),
+                None => {
+                    missing = missing.max(Some(argument));
+                    0
+                },
+            }
+        };
+
+        if format.get(i) == Some(&b'*') {
+            spec.width = star(&mut i, "Width");
+        } else if let Some(width) = number(&mut i) {
+            spec.width = width;
+        }
+
+        if format.get(i) == Some(&b'.') {
+            i += 1;
+
+            spec.precision = Some(match format.get(i) {
+                Some(b'*') => star(&mut i, "Precision"),
+                _ => number(&mut i).unwrap_or(0),
+            });
+        }
+
+        // PHP accepts, and ignores, a C-style `l` length modifier.
+        if format.get(i) == Some(&b'l') {
+            i += 1;
+        }
+
+        let conversion = match format.get(i) {
+            Some(&c) => c,
+            None => error::throw("ValueError", "Missing format specifier at end of string"),
+        };
+
+        i += 1;
+
+        let argument = position.unwrap_or_else(|| {
+            next_argument += 1;
+            next_argument - 1
+        });
+
+        let value = match args.get(argument) {
+            Some(value) => value,
+            None => {
+                if ! b"sdufFeEgGxXobc".contains(&conversion) {
+                    unknown(conversion);
+                }
+
+                missing = missing.max(Some(argument));
+                continue;
+            },
+        };
+
+        match conversion {
+            b's' => {
+                let value = convert::to_bytes(value);
+                let value = value.as_bytes();
+                let value = match spec.precision {
+                    Some(p) if p < value.len() => &value[..p],
+                    _ => value,
+                };
+
+                spec.pad(&mut buffer, value, false);
+            },
+            b'd' => {
+                let value = convert::to_int(value);
+                let (value, signed) = spec.signed(value.to_string(), value < 0);
+
+                spec.pad(&mut buffer, value.as_bytes(), signed);
+            },
+            b'u' => spec.pad(&mut buffer, (convert::to_int(value) as u64).to_string().as_bytes(), false),
+            b'f' | b'F' | b'e' | b'E' | b'g' | b'G' => {
+                let (value, signed) = float(&spec, convert::to_float(value), conversion);
+
+                spec.pad(&mut buffer, value.as_bytes(), signed);
+            },
+            b'x' => spec.pad(&mut buffer, format!("{:x}", convert::to_int(value)).as_bytes(), false),
+            b'X' => spec.pad(&mut buffer, format!("{:X}", convert::to_int(value)).as_bytes(), false),
+            b'o' => spec.pad(&mut buffer, format!("{:o}", convert::to_int(value)).as_bytes(), false),
+            b'b' => spec.pad(&mut buffer, format!("{:b}", convert::to_int(value)).as_bytes(), false),
+            // Characters are never padded.
+            b'c' => buffer.push(convert::to_int(value) as u8),
+            c => unknown(c),
+        }
+    }
+
+    if let Some(missing) = missing {
+        match source {
+            Source::Arguments => error::throw("ArgumentCountError", format!("{} arguments are required, {} given", missing + 2, args.len() + 1)),
+            Source::Array => error::throw("ValueError", format!("The arguments array must contain {} items, {} given", missing + 1, args.len())),
+        }
+    }
+
+    buffer
+}
+
+fn values(function: &str, values: &Object) -> Vec<Object> {
+    match values {
+        Object::Array(values) => values.borrow().iter().map(|(_, v)| v.clone()).collect(),
+        value => error::throw("TypeError", format!("{}(): Argument #2 ($values) must be of type array, {} given", function, value.type_name())),
+    }
+}
+
+fn output(bytes: &[u8]) -> Object {
+    let mut stdout = std::io::stdout();
+    let _ = stdout.write_all(bytes);
+
+    Object::Integer(bytes.len() as i64)
+}
+
+pub fn sprintf(_: &mut Machine, args: Vec<Object>) -> Object {
+    let template = convert::to_bytes(args.first().unwrap());
+
+    Object::String(format(&template, &args[1..], Source::Arguments).into())
+}
+
+pub fn printf(_: &mut Machine, args: Vec<Object>) -> Object {
+    let template = convert::to_bytes(args.first().unwrap());
+
+    output(&format(&template, &args[1..], Source::Arguments))
+}
+
+pub fn vsprintf(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    let template = convert::to_bytes(&args[0]);
+    let values = values("vsprintf", &args[1]);
+
+    Object::String(format(&template, &values, Source::Array).into())
+}
+
+pub fn vprintf(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    let template = convert::to_bytes(&args[0]);
+    let values = values("vprintf", &args[1]);
+
+    output(&format(&template, &values, Source::Array))
+}
+
+pub fn fprintf(vm: &mut Machine, args: Vec<Object>) -> Object {
+    let template = convert::to_bytes(&args[1]);
+    let bytes = format(&template, &args[2..], Source::Arguments);
+
+    match write(vm, "fprintf", &args[0], &bytes) {
+        Object::False => Object::False,
+        _ => Object::Integer(bytes.len() as i64),
+    }
+}
+
+pub fn number_format(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 4);
+
+    let number = convert::to_float(&args[0]);
+    let decimals = args.get(1).map(convert::to_int).unwrap_or(0).max(0) as usize;
+    let decimal_separator = args.get(2).map(convert::to_bytes).unwrap_or_else(|| ".".into());
+    let thousands_separator = args.get(3).map(convert::to_bytes).unwrap_or_else(|| ",".into());
+
+    let number = round_float(number, decimals as i64, PHP_ROUND_HALF_UP);
+
+    if ! number.is_finite() {
+        return Object::String(convert::format_float(number, convert::PRECISION).into());
+    }
+
+    let formatted = format!("{:.*}", decimals.min(MAX_FLOAT_PRECISION), number.abs());
+    let (integer, fraction) = match formatted.split_once('.') {
+        Some((integer, fraction)) => (integer, Some(fraction)),
+        None => (formatted.as_str(), None),
+    };
+
+    let mut result = ByteString::new();
+
+    // Negative numbers that round to zero don't keep their sign.
+    if number < 0.0 && formatted.bytes().any(|b| b.is_ascii_digit() && b != b'0') {
+        result.push(b'-');
+    }
+
+    for (i, digit) in integer.bytes().enumerate() {
+        if i > 0 && (integer.len() - i) % 3 == 0 {
+            result.extend_from_slice(&thousands_separator);
+        }
+
+        result.push(digit);
+    }
+
+    if let Some(fraction) = fraction {
+        result.extend_from_slice(&decimal_separator);
+        result.push_str(fraction);
+    }
+
+    if decimals > MAX_FLOAT_PRECISION {
+        result.push_str(&"0".repeat(decimals - MAX_FLOAT_PRECISION));
+    }
+
+    Object::String(result)
+}
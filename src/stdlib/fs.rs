@@ -1,19 +1,941 @@
-use super::{arity, typecheck};
-use crate::object::Object;
-use std::path::Path;
+use super::{arity, open, Handle, Stream, LOCK_EX, SEEK_END, SEEK_SET};
+use crate::object::{Object, Array, ByteString, Key, convert};
 use crate::vm::Machine;
+use crate::error;
+use std::fs::{self, File, OpenOptions};
+use std::ffi::CString;
+use std::io::{self, SeekFrom};
+use std::os::unix::fs::{MetadataExt, PermissionsExt};
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+pub const FILE_USE_INCLUDE_PATH: i64 = 1;
+pub const FILE_IGNORE_NEW_LINES: i64 = 2;
+pub const FILE_SKIP_EMPTY_LINES: i64 = 4;
+pub const FILE_APPEND: i64 = 8;
+
+pub const PATHINFO_DIRNAME: i64 = 1;
+pub const PATHINFO_BASENAME: i64 = 2;
+pub const PATHINFO_EXTENSION: i64 = 4;
+pub const PATHINFO_FILENAME: i64 = 8;
+pub const PATHINFO_ALL: i64 = 15;
+
+pub const SCANDIR_SORT_ASCENDING: i64 = 0;
+pub const SCANDIR_SORT_DESCENDING: i64 = 1;
+pub const SCANDIR_SORT_NONE: i64 = 2;
+
+// These match glibc, which is what PHP passes them straight through to.
+pub const GLOB_ERR: i64 = 1;
+pub const GLOB_MARK: i64 = 2;
+pub const GLOB_NOSORT: i64 = 4;
+pub const GLOB_NOCHECK: i64 = 16;
+pub const GLOB_NOESCAPE: i64 = 64;
+pub const GLOB_BRACE: i64 = 1024;
+pub const GLOB_ONLYDIR: i64 = 8192;
+
+/// The C library's description of an I/O error, which is what PHP puts in its warnings
+/// (e.g. "No such file or directory").
+pub(crate) fn reason(error: &io::Error) -> String {
+    let message = error.to_string();
+
+    match message.find(" (os error") {
+        Some(position) => message[..position].to_string(),
+        None => message,
+    }
+}
+
+fn string(bytes: Vec<u8>) -> Object {
+    Object::String(bytes.into())
+}
+
+fn path(value: &Object) -> String {
+    convert::to_string(value)
+}
+
+/// The number of seconds since the Unix epoch, which is how PHP represents file times.
+fn timestamp(time: io::Result<SystemTime>) -> Option<i64> {
+    let time = time.ok()?;
+
+    Some(match time.duration_since(UNIX_EPOCH) {
+        Ok(duration) => duration.as_secs() as i64,
+        Err(e) => -(e.duration().as_secs() as i64),
+    })
+}
+
+/// A port of `php_basename()`, which (unlike `Path::file_name()`) copes with trailing
+/// slashes and never fails.
+fn php_basename(path: &str, suffix: Option<&str>) -> String {
+    let trimmed = path.trim_end_matches('/');
+
+    let basename = match trimmed.rfind('/') {
+        Some(position) => &trimmed[position + 1..],
+        None => trimmed,
+    };
+
+    match suffix {
+        Some(suffix) if basename.len() > suffix.len() && basename.ends_with(suffix) => basename[..basename.len() - suffix.len()].to_string(),
+        _ => basename.to_string(),
+    }
+}
+
+/// A port of `zend_dirname()`.
+fn php_dirname(path: &str) -> String {
+    if path.is_empty() {
+        return String::new();
+    }
+
+    let trimmed = path.trim_end_matches('/');
+
+    if trimmed.is_empty() {
+        return "/".into();
+    }
+
+    match trimmed.rfind('/') {
+        None => ".".into(),
+        Some(position) => match trimmed[..position].trim_end_matches('/') {
+            "" => "/".into(),
+            parent => parent.into(),
+        },
+    }
+}
 
 pub fn basename(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    let path = path(&args[0]);
+    let suffix = args.get(1).map(convert::to_string);
+
+    Object::String(php_basename(&path, suffix.as_deref()).into())
+}
+
+pub fn dirname(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    let mut path = path(&args[0]);
+    let levels = args.get(1).map(convert::to_int).unwrap_or(1);
+
+    if levels < 1 {
+        error::throw("ValueError", "dirname(): Argument #2 ($levels) must be greater than or equal to 1");
+    }
+
+    for _ in 0..levels {
+        let parent = php_dirname(&path);
+
+        if parent == path {
+            break;
+        }
+
+        path = parent;
+    }
+
+    Object::String(path.into())
+}
+
+pub fn pathinfo(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    let path = path(&args[0]);
+    let flags = args.get(1).map(convert::to_int).unwrap_or(PATHINFO_ALL);
+    let mut info = Array::new();
+
+    if flags & PATHINFO_DIRNAME != 0 && ! path.is_empty() {
+        info.insert(Key::from("dirname"), Object::String(php_dirname(&path).into()));
+    }
+
+    let basename = php_basename(&path, None);
+    let extension = basename.rfind('.').map(|position| &basename[position + 1..]);
+
+    if flags & PATHINFO_BASENAME != 0 {
+        info.insert(Key::from("basename"), Object::String(basename.as_str().into()));
+    }
+
+    if flags & PATHINFO_EXTENSION != 0 {
+        if let Some(extension) = extension {
+            info.insert(Key::from("extension"), Object::String(extension.into()));
+        }
+    }
+
+    if flags & PATHINFO_FILENAME != 0 {
+        let filename = match basename.rfind('.') {
+            Some(position) => &basename[..position],
+            None => &basename,
+        };
+
+        info.insert(Key::from("filename"), Object::String(filename.into()));
+    }
+
+    // Asking for a single part returns it as a string rather than an array.
+    if flags == PATHINFO_ALL {
+        return Object::from_array(info);
+    }
+
+    let part = info.values().next().cloned();
+
+    part.unwrap_or_else(|| Object::String(ByteString::new()))
+}
+
+pub fn realpath(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    match fs::canonicalize(path(&args[0])) {
+        Ok(path) => Object::String(path.to_string_lossy().as_ref().into()),
+        Err(_) => Object::False,
+    }
+}
+
+pub fn file_exists(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    Object::from_bool(Path::new(&path(&args[0])).exists())
+}
+
+pub fn is_file(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    Object::from_bool(Path::new(&path(&args[0])).is_file())
+}
+
+pub fn is_dir(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    Object::from_bool(Path::new(&path(&args[0])).is_dir())
+}
+
+pub fn file_get_contents(vm: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 5);
+
+    let filename = path(&args[0]);
+    let offset = args.get(3).map(convert::to_int).unwrap_or(0);
+    let length = match args.get(4) {
+        None | Some(Object::Null) => None,
+        Some(length) => Some(convert::to_int(length)),
+    };
+
+    if let Some(length) = length.filter(|l| *l < 0) {
+        error::throw("ValueError", format!("file_get_contents(): Argument #5 ($length) must be greater than or equal to 0, {} given", length));
+    }
+
+    let mut stream = match open(vm, "file_get_contents", &filename, "rb") {
+        Ok(stream) => stream,
+        Err(reason) => {
+            error::warning(format!("file_get_contents({}): Failed to open stream: {}", filename, reason));
+            return Object::False;
+        },
+    };
+
+    let seek = match offset {
+        0 => Ok(0),
+        o if o < 0 => stream.seek(o, SEEK_END),
+        o => stream.seek(o, SEEK_SET),
+    };
+
+    if seek.is_err() {
+        error::warning(format!("file_get_contents(): Failed to seek to position {} in the stream", offset));
+        return Object::False;
+    }
+
+    let contents = match length {
+        Some(0) => Ok(Vec::new()),
+        Some(length) => stream.read(length as usize),
+        None => stream.read_to_end(),
+    };
+
+    match contents {
+        Ok(contents) => string(contents),
+        Err(e) => {
+            error::notice(format!("file_get_contents(): Read of 8192 bytes failed with errno={} {}", e.raw_os_error().unwrap_or(0), reason(&e)));
+
+            Object::String(ByteString::new())
+        },
+    }
+}
+
+pub fn file_put_contents(vm: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 4);
+
+    let filename = path(&args[0]);
+    let flags = args.get(2).map(convert::to_int).unwrap_or(0);
+
+    let data = match &args[1] {
+        Object::Array(items) => items.borrow().values().flat_map(|item| convert::to_bytes(item).into_bytes()).collect(),
+        data => convert::to_bytes(data),
+    };
+
+    // The file is only truncated once we hold the lock, so a reader never sees it empty.
+    let mode = if flags & FILE_APPEND != 0 { "ab" } else { "cb" };
+
+    let mut stream = match open(vm, "file_put_contents", &filename, mode) {
+        Ok(stream) => stream,
+        Err(reason) => {
+            error::warning(format!("file_put_contents({}): Failed to open stream: {}", filename, reason));
+            return Object::False;
+        },
+    };
+
+    if flags & LOCK_EX != 0 && ! matches!(stream.lock(LOCK_EX), Ok(true)) {
+        error::warning("file_put_contents(): Exclusive locks are not supported for this stream");
+        return Object::False;
+    }
+
+    if flags & FILE_APPEND == 0 {
+        let _ = stream.truncate(0);
+    }
+
+    let written = stream.write(data.as_bytes()).and_then(|written| stream.flush().map(|_| written));
+
+    match written {
+        Ok(written) => Object::Integer(written as i64),
+        Err(e) => {
+            error::warning(format!("file_put_contents(): Write of {} bytes failed with errno={} {}", data.len(), e.raw_os_error().unwrap_or(0), reason(&e)));
+            Object::False
+        },
+    }
+}
+
+pub fn file(vm: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 3);
+
+    let filename = path(&args[0]);
+    let flags = args.get(1).map(convert::to_int).unwrap_or(0);
+
+    let contents = match open(vm, "file", &filename, "rb").and_then(|mut stream| stream.read_to_end().map_err(|e| reason(&e))) {
+        Ok(contents) => contents,
+        Err(reason) => {
+            error::warning(format!("file({}): Failed to open stream: {}", filename, reason));
+            return Object::False;
+        },
+    };
+
+    let mut lines = Array::new();
+
+    for line in contents.split_inclusive(|b| *b == b'\n') {
+        let line = match flags & FILE_IGNORE_NEW_LINES != 0 {
+            true => line.strip_suffix(b"\n").unwrap_or(line),
+            false => line,
+        };
+
+        if flags & FILE_SKIP_EMPTY_LINES != 0 && flags & FILE_IGNORE_NEW_LINES != 0 && line.is_empty() {
+            continue;
+        }
+
+        lines.push(string(line.to_vec()));
+    }
+
+    Object::from_array(lines)
+}
+
+pub fn unlink(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    let filename = path(&args[0]);
+
+    if Path::new(&filename).is_dir() {
+        error::warning(format!("unlink({}): Is a directory", filename));
+        return Object::False;
+    }
+
+    match fs::remove_file(&filename) {
+        Ok(_) => Object::True,
+        Err(e) => {
+            error::warning(format!("unlink({}): {}", filename, reason(&e)));
+            Object::False
+        },
+    }
+}
+
+pub fn mkdir(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 4);
+
+    let directory = path(&args[0]);
+    let permissions = args.get(1).map(convert::to_int).unwrap_or(0o777);
+    let recursive = args.get(2).map(Object::to_bool).unwrap_or(false);
+
+    let mut builder = fs::DirBuilder::new();
+    builder.recursive(recursive);
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::DirBuilderExt;
+
+        builder.mode(permissions as u32);
+    }
+
+    if recursive && Path::new(&directory).exists() {
+        error::warning("mkdir(): File exists");
+        return Object::False;
+    }
+
+    match builder.create(&directory) {
+        Ok(_) => Object::True,
+        Err(e) => {
+            error::warning(format!("mkdir(): {}", reason(&e)));
+            Object::False
+        },
+    }
+}
+
+pub fn rmdir(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    let directory = path(&args[0]);
+
+    match fs::remove_dir(&directory) {
+        Ok(_) => Object::True,
+        Err(e) => {
+            error::warning(format!("rmdir({}): {}", directory, reason(&e)));
+            Object::False
+        },
+    }
+}
+
+pub fn rename(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 3);
+
+    let (from, to) = (path(&args[0]), path(&args[1]));
+
+    match fs::rename(&from, &to) {
+        Ok(_) => Object::True,
+        Err(e) => {
+            error::warning(format!("rename({},{}): {}", from, to, reason(&e)));
+            Object::False
+        },
+    }
+}
+
+pub fn copy(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 3);
+
+    let (from, to) = (path(&args[0]), path(&args[1]));
+
+    if let Err(e) = File::open(&from) {
+        error::warning(format!("copy({}): Failed to open stream: {}", from, reason(&e)));
+        return Object::False;
+    }
+
+    match fs::copy(&from, &to) {
+        Ok(_) => Object::True,
+        Err(e) => {
+            error::warning(format!("copy({}): Failed to open stream: {}", to, reason(&e)));
+            Object::False
+        },
+    }
+}
+
+pub fn filesize(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    let filename = path(&args[0]);
+
+    match fs::metadata(&filename) {
+        Ok(metadata) => Object::Integer(metadata.len() as i64),
+        Err(_) => {
+            error::warning(format!("filesize(): stat failed for {}", filename));
+            Object::False
+        },
+    }
+}
+
+pub fn filemtime(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    let filename = path(&args[0]);
+
+    match fs::metadata(&filename).ok().and_then(|metadata| timestamp(metadata.modified())) {
+        Some(time) => Object::Integer(time),
+        None => {
+            error::warning(format!("filemtime(): stat failed for {}", filename));
+            Object::False
+        },
+    }
+}
+
+pub fn touch(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 3);
+
+    let filename = path(&args[0]);
+    let time = |value: Option<&Object>| match value {
+        None | Some(Object::Null) => None,
+        Some(time) => Some(UNIX_EPOCH + Duration::from_secs(convert::to_int(time).max(0) as u64)),
+    };
+
+    let modified = time(args.get(1)).unwrap_or_else(SystemTime::now);
+    let accessed = time(args.get(2)).unwrap_or(modified);
+
+    let file = match OpenOptions::new().create(true).append(true).open(&filename) {
+        Ok(file) => file,
+        Err(e) => {
+            error::warning(format!("touch(): Unable to create file {} because {}", filename, reason(&e)));
+            return Object::False;
+        },
+    };
+
+    match file.set_times(fs::FileTimes::new().set_modified(modified).set_accessed(accessed)) {
+        Ok(_) => Object::True,
+        Err(e) => {
+            error::warning(format!("touch(): Utime failed: {}", reason(&e)));
+            Object::False
+        },
+    }
+}
+
+pub fn scandir(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 3);
+
+    let directory = path(&args[0]);
+    let order = args.get(1).map(convert::to_int).unwrap_or(SCANDIR_SORT_ASCENDING);
+
+    let entries = match fs::read_dir(&directory) {
+        Ok(entries) => entries,
+        Err(e) => {
+            error::warning(format!("scandir({}): Failed to open directory: {}", directory, reason(&e)));
+            error::warning(format!("scandir(): (errno {}): {}", e.raw_os_error().unwrap_or(0), reason(&e)));
+            return Object::False;
+        },
+    };
+
+    let mut names: Vec<String> = vec![".".into(), "..".into()];
+    names.extend(entries.filter_map(Result::ok).map(|entry| entry.file_name().to_string_lossy().into_owned()));
+
+    match order {
+        SCANDIR_SORT_NONE => (),
+        SCANDIR_SORT_DESCENDING => names.sort_by(|a, b| b.as_bytes().cmp(a.as_bytes())),
+        _ => names.sort_by(|a, b| a.as_bytes().cmp(b.as_bytes())),
+    }
+
+    Object::from_array(names.into_iter().map(|name| Object::String(name.into())).collect())
+}
+
+/// Expands `{a,b}` alternatives in a glob pattern, which the `glob` crate doesn't support.
+fn expand_braces(pattern: &str) -> Vec<String> {
+    let open = match pattern.find('{') {
+        Some(open) => open,
+        None => return vec![pattern.to_string()],
+    };
+
+    let mut depth = 0;
+    let mut close = None;
+    let mut alternatives = Vec::new();
+    let mut start = open + 1;
+
+    for (i, c) in pattern.char_indices().skip_while(|(i, _)| *i <= open) {
+        match c {
+            '{' => depth += 1,
+            '}' if depth > 0 => depth -= 1,
+            '}' => {
+                alternatives.push(&pattern[start..i]);
+                close = Some(i);
+                break;
+            },
+            ',' if depth == 0 => {
+                alternatives.push(&pattern[start..i]);
+                start = i + 1;
+            },
+            _ => (),
+        }
+    }
+
+    let close = match close {
+        Some(close) => close,
+        None => return vec![pattern.to_string()],
+    };
+
+    alternatives.iter()
+        .flat_map(|alternative| expand_braces(&format!("{}{}{}", &pattern[..open], alternative, &pattern[close + 1..])))
+        .collect()
+}
+
+pub fn glob(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    let pattern = convert::to_string(&args[0]);
+    let flags = args.get(1).map(convert::to_int).unwrap_or(0);
+
+    let options = glob::MatchOptions {
+        case_sensitive: true,
+        require_literal_separator: true,
+        require_literal_leading_dot: true,
+    };
+
+    let patterns = match flags & GLOB_BRACE != 0 {
+        true => expand_braces(&pattern),
+        false => vec![pattern.clone()],
+    };
+
+    let mut matches = Vec::new();
+
+    for pattern in patterns.iter() {
+        let pattern = match flags & GLOB_NOESCAPE != 0 {
+            true => pattern.clone(),
+            false => pattern.replace("\\*", "[*]").replace("\\?", "[?]").replace("\\[", "[[]"),
+        };
+
+        let paths = match glob::glob_with(&pattern, options) {
+            Ok(paths) => paths,
+            Err(_) => return Object::False,
+        };
+
+        let mut found: Vec<String> = paths.filter_map(Result::ok)
+            .filter(|path| flags & GLOB_ONLYDIR == 0 || path.is_dir())
+            .map(|path| {
+                let mut name = path.to_string_lossy().into_owned();
+
+                if flags & GLOB_MARK != 0 && path.is_dir() {
+                    name.push('/');
+                }
+
+                name
+            })
+            .collect();
+
+        // Each brace alternative is sorted separately, the same as glibc.
+        if flags & GLOB_NOSORT == 0 {
+            found.sort_by(|a, b| a.as_bytes().cmp(b.as_bytes()));
+        }
+
+        matches.extend(found);
+    }
+
+    if matches.is_empty() && flags & GLOB_NOCHECK != 0 {
+        matches.push(pattern);
+    }
+
+    Object::from_array(matches.into_iter().map(|name| Object::String(name.into())).collect())
+}
+
+pub fn sys_get_temp_dir(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 0);
+
+    let directory = std::env::temp_dir().to_string_lossy().into_owned();
+
+    Object::String(match directory.trim_end_matches('/') {
+        "" => "/".into(),
+        directory => directory.into(),
+    })
+}
+
+pub fn tempnam(vm: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    let mut directory = path(&args[0]);
+    let prefix = php_basename(&convert::to_string(&args[1]), None);
+    let prefix: String = prefix.chars().take(63).collect();
+
+    if directory.is_empty() || ! Path::new(&directory).is_dir() {
+        error::notice("tempnam(): file created in the system's temporary directory");
+        directory = convert::to_string(&sys_get_temp_dir(vm, Vec::new()));
+    }
+
+    const CHARACTERS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
+
+    for _ in 0..100 {
+        let mut random = [0u8; 6];
+
+        if getrandom::getrandom(&mut random).is_err() {
+            break;
+        }
+
+        let suffix: String = random.iter().map(|b| CHARACTERS[*b as usize % CHARACTERS.len()] as char).collect();
+        let filename = format!("{}/{}{}", directory.trim_end_matches('/'), prefix, suffix);
+
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+
+            options.mode(0o600);
+        }
+
+        match options.open(&filename) {
+            Ok(_) => return Object::String(filename.into()),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(_) => break,
+        }
+    }
+
+    Object::False
+}
+
+/// The handle behind an `opendir()` resource. Entries are read up front, so the
+/// directory changing underneath doesn't affect an iteration that's in progress.
+struct Directory {
+    entries: Vec<String>,
+    next: usize,
+}
+
+impl Handle for Directory {
+    fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+        Err(io::Error::from_raw_os_error(libc::EBADF))
+    }
+
+    fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+        Err(io::Error::from_raw_os_error(libc::EBADF))
+    }
+
+    fn seek(&mut self, position: SeekFrom) -> io::Result<u64> {
+        match position {
+            SeekFrom::Start(0) => {
+                self.next = 0;
+                Ok(0)
+            },
+            _ => Err(io::Error::from(io::ErrorKind::InvalidInput)),
+        }
+    }
+
+    fn read_entry(&mut self) -> io::Result<Option<String>> {
+        let entry = self.entries.get(self.next).cloned();
+        self.next += 1;
+
+        Ok(entry)
+    }
+}
+
+pub fn opendir(vm: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    let directory = path(&args[0]);
+
+    let entries = match fs::read_dir(&directory) {
+        Ok(entries) => entries,
+        Err(e) => {
+            error::warning(format!("opendir({}): Failed to open directory: {}", directory, reason(&e)));
+            return Object::False;
+        },
+    };
+
+    let mut names: Vec<String> = vec![".".into(), "..".into()];
+    names.extend(entries.filter_map(Result::ok).map(|entry| entry.file_name().to_string_lossy().into_owned()));
+
+    let handle = vm.streams.insert(Stream::new(Box::new(Directory { entries: names, next: 0 })));
+    vm.streams.directory = Some(handle.clone());
+
+    handle
+}
+
+/// Looks up the directory handle for `readdir()` and friends, which fall back to the
+/// last one that `opendir()` returned.
+fn directory<'a>(vm: &'a mut Machine, function: &str, args: &[Object]) -> (usize, &'a mut Stream) {
+    let handle = match args.first().or(vm.streams.directory.as_ref()) {
+        Some(Object::Resource(resource)) => resource.borrow().id,
+        Some(value) => error::throw("TypeError", format!("{}(): Argument #1 ($dir_handle) must be of type resource or null, {} given", function, value.type_name())),
+        None => error::throw("TypeError", format!("{}(): No resource supplied", function)),
+    };
+
+    match vm.streams.get(handle) {
+        Some(stream) => (handle, stream),
+        None => error::throw("TypeError", format!("{}(): supplied resource is not a valid Directory resource", function)),
+    }
+}
+
+pub fn readdir(vm: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    match directory(vm, "readdir", &args).1.read_entry() {
+        Ok(Some(entry)) => Object::String(entry.into()),
+        Ok(None) => Object::False,
+        Err(_) => error::throw("TypeError", "readdir(): Argument #1 ($dir_handle) must be a valid Directory resource"),
+    }
+}
+
+pub fn rewinddir(vm: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    if directory(vm, "rewinddir", &args).1.seek(0, SEEK_SET).is_err() {
+        error::throw("TypeError", "rewinddir(): Argument #1 ($dir_handle) must be a valid Directory resource");
+    }
+
+    Object::Null
+}
+
+pub fn closedir(vm: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    let (id, stream) = directory(vm, "closedir", &args);
+
+    if stream.read_entry().is_err() {
+        error::throw("TypeError", "closedir(): Argument #1 ($dir_handle) must be a valid Directory resource");
+    }
+
+    let handle = match args.into_iter().next().or_else(|| vm.streams.directory.clone()) {
+        Some(Object::Resource(resource)) => resource,
+        _ => unreachable!(),
+    };
+
+    vm.streams.remove(id);
+    handle.borrow_mut().kind = "Unknown".into();
+
+    if matches!(&vm.streams.directory, Some(Object::Resource(last)) if last.borrow().id == id) {
+        vm.streams.directory = None;
+    }
+
+    Object::Null
+}
+
+/// Calls `access()`, which (unlike looking at the permission bits) takes the user
+/// running the script into account.
+fn accessible(path: &str, mode: libc::c_int) -> bool {
+    match CString::new(path) {
+        Ok(path) => unsafe { libc::access(path.as_ptr(), mode) == 0 },
+        Err(_) => false,
+    }
+}
+
+pub fn is_readable(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    Object::from_bool(accessible(&path(&args[0]), libc::R_OK))
+}
+
+pub fn is_writable(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    Object::from_bool(accessible(&path(&args[0]), libc::W_OK))
+}
+
+pub fn is_link(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    Object::from_bool(fs::symlink_metadata(path(&args[0])).is_ok_and(|metadata| metadata.file_type().is_symlink()))
+}
+
+/// The array returned by `stat()`, which has every field twice: once by index and
+/// once by name.
+fn stat_array(metadata: &fs::Metadata) -> Object {
+    let fields = [
+        ("dev", metadata.dev() as i64),
+        ("ino", metadata.ino() as i64),
+        ("mode", metadata.mode() as i64),
+        ("nlink", metadata.nlink() as i64),
+        ("uid", metadata.uid() as i64),
+        ("gid", metadata.gid() as i64),
+        ("rdev", metadata.rdev() as i64),
+        ("size", metadata.size() as i64),
+        ("atime", metadata.atime()),
+        ("mtime", metadata.mtime()),
+        ("ctime", metadata.ctime()),
+        ("blksize", metadata.blksize() as i64),
+        ("blocks", metadata.blocks() as i64),
+    ];
+
+    let mut stat = Array::new();
+
+    for (_, value) in fields.iter() {
+        stat.push(Object::Integer(*value));
+    }
+
+    for (name, value) in fields.iter() {
+        stat.insert(Key::from(*name), Object::Integer(*value));
+    }
+
+    Object::from_array(stat)
+}
+
+pub fn stat(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    let filename = path(&args[0]);
+
+    match fs::metadata(&filename) {
+        Ok(metadata) => stat_array(&metadata),
+        Err(_) => {
+            error::warning(format!("stat(): stat failed for {}", filename));
+            Object::False
+        },
+    }
+}
+
+pub fn lstat(_: &mut Machine, args: Vec<Object>) -> Object {
     arity!(args, 1);
 
-    let path = args.first().unwrap();
+    let filename = path(&args[0]);
+
+    match fs::symlink_metadata(&filename) {
+        Ok(metadata) => stat_array(&metadata),
+        Err(_) => {
+            error::warning(format!("lstat(): Lstat failed for {}", filename));
+            Object::False
+        },
+    }
+}
+
+pub fn fileperms(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    let filename = path(&args[0]);
+
+    match fs::metadata(&filename) {
+        Ok(metadata) => Object::Integer(metadata.mode() as i64),
+        Err(_) => {
+            error::warning(format!("fileperms(): stat failed for {}", filename));
+            Object::False
+        },
+    }
+}
+
+pub fn chmod(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    let filename = path(&args[0]);
+    let permissions = convert::to_int(&args[1]);
+
+    match fs::set_permissions(&filename, fs::Permissions::from_mode(permissions as u32 & 0o7777)) {
+        Ok(_) => Object::True,
+        Err(e) => {
+            error::warning(format!("chmod(): {}", reason(&e)));
+            Object::False
+        },
+    }
+}
+
+pub fn symlink(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    match std::os::unix::fs::symlink(path(&args[0]), path(&args[1])) {
+        Ok(_) => Object::True,
+        Err(e) => {
+            error::warning(format!("symlink(): {}", reason(&e)));
+            Object::False
+        },
+    }
+}
+
+pub fn readlink(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    match fs::read_link(path(&args[0])) {
+        Ok(target) => Object::String(target.to_string_lossy().as_ref().into()),
+        Err(e) => {
+            error::warning(format!("readlink(): {}", reason(&e)));
+            Object::False
+        },
+    }
+}
+
+pub fn disk_free_space(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    let directory = path(&args[0]);
+    let mut stats = std::mem::MaybeUninit::<libc::statvfs>::uninit();
+
+    let result = match CString::new(directory) {
+        Ok(directory) => unsafe { libc::statvfs(directory.as_ptr(), stats.as_mut_ptr()) },
+        Err(_) => -1,
+    };
+
+    if result != 0 {
+        error::warning(format!("disk_free_space(): {}", reason(&io::Error::last_os_error())));
+        return Object::False;
+    }
 
-    typecheck!(path, is_string);
+    let stats = unsafe { stats.assume_init() };
 
-    let path = path.to_string();
-    let path = Path::new(&path);
+    Object::Float(stats.f_bavail as f64 * stats.f_frsize as f64)
+}
 
-    let basename = path.file_name().and_then(|s| s.to_str()).unwrap();
+/// Nothing is cached between calls, so there's never anything to clear.
+pub fn clearstatcache(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
 
-    Object::String(basename.into())
-}
\ No newline at end of file
+    Object::Null
+}
@@ -0,0 +1,38 @@
+use crate::object::Object;
+use crate::vm::Machine;
+use crate::error;
+
+/// Checks a callback argument, throwing the same `TypeError` as PHP if it can't be called.
+pub(crate) fn callback(vm: &mut Machine, function: &str, position: usize, value: &Object, nullable: bool) -> Option<Object> {
+    if nullable && matches!(value, Object::Null) {
+        return None;
+    }
+
+    if vm.is_callable(value) {
+        return Some(value.clone());
+    }
+
+    let reason = match value {
+        Object::String(name) => format!("function \"{}\" not found or invalid function name", name),
+        _ => "no array or string given".into(),
+    };
+
+    error::throw("TypeError", format!("{}(): Argument #{} ($callback) must be a valid callback{}, {}", function, position, if nullable { " or null" } else { "" }, reason))
+}
+
+pub fn call_user_func(vm: &mut Machine, mut args: Vec<Object>) -> Object {
+    let callable = callback(vm, "call_user_func", 1, &args.remove(0), false).unwrap();
+
+    vm.call(&callable, args)
+}
+
+pub fn call_user_func_array(vm: &mut Machine, args: Vec<Object>) -> Object {
+    let callable = callback(vm, "call_user_func_array", 1, &args[0], false).unwrap();
+
+    let args = match &args[1] {
+        Object::Array(args) => args.borrow().values().cloned().collect(),
+        value => error::throw("TypeError", format!("call_user_func_array(): Argument #2 ($args) must be of type array, {} given", value.type_name())),
+    };
+
+    vm.call(&callable, args)
+}
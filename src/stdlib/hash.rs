@@ -0,0 +1,181 @@
+use super::arity;
+use crate::object::{Object, Array, ByteString, Key, convert};
+use crate::vm::Machine;
+use crate::error;
+use md5::Md5;
+use sha1::Sha1;
+use sha2::{Digest, Sha224, Sha256, Sha384, Sha512};
+
+pub const PASSWORD_DEFAULT: &str = "2y";
+pub const PASSWORD_BCRYPT: &str = "2y";
+pub const PASSWORD_BCRYPT_DEFAULT_COST: i64 = 10;
+
+/// The algorithms `hash()` supports, in the order `hash_algos()` lists them.
+const ALGORITHMS: [&str; 8] = ["md5", "sha1", "sha224", "sha256", "sha384", "sha512", "crc32b", "xxh64"];
+
+fn hex(bytes: &[u8]) -> ByteString {
+    bytes.iter().flat_map(|b| format!("{:02x}", b).into_bytes()).collect()
+}
+
+/// Hashes `data` with one of `ALGORITHMS`, returning the raw digest.
+fn digest(algorithm: &str, data: &[u8]) -> Option<Vec<u8>> {
+    Some(match algorithm {
+        "md5" => Md5::digest(data).to_vec(),
+        "sha1" => Sha1::digest(data).to_vec(),
+        "sha224" => Sha224::digest(data).to_vec(),
+        "sha256" => Sha256::digest(data).to_vec(),
+        "sha384" => Sha384::digest(data).to_vec(),
+        "sha512" => Sha512::digest(data).to_vec(),
+        "crc32b" => crc32fast::hash(data).to_be_bytes().to_vec(),
+        "xxh64" => xxhash_rust::xxh64::xxh64(data, 0).to_be_bytes().to_vec(),
+        _ => return None,
+    })
+}
+
+/// The block size of each of the algorithms that can be used for an HMAC.
+fn block_size(algorithm: &str) -> Option<usize> {
+    match algorithm {
+        "md5" | "sha1" | "sha224" | "sha256" => Some(64),
+        "sha384" | "sha512" => Some(128),
+        _ => None,
+    }
+}
+
+fn output(bytes: Vec<u8>, binary: bool) -> Object {
+    match binary {
+        true => Object::String(bytes.into()),
+        false => Object::String(hex(&bytes)),
+    }
+}
+
+pub fn md5(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    let binary = args.get(1).is_some_and(Object::to_bool);
+
+    output(Md5::digest(convert::to_bytes(&args[0]).as_bytes()).to_vec(), binary)
+}
+
+pub fn sha1(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    let binary = args.get(1).is_some_and(Object::to_bool);
+
+    output(Sha1::digest(convert::to_bytes(&args[0]).as_bytes()).to_vec(), binary)
+}
+
+pub fn crc32(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    Object::Integer(crc32fast::hash(&convert::to_bytes(&args[0])) as i64)
+}
+
+pub fn hash(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 3);
+
+    let algorithm = convert::to_string(&args[0]).to_lowercase();
+    let binary = args.get(2).is_some_and(Object::to_bool);
+
+    match digest(&algorithm, &convert::to_bytes(&args[1])) {
+        Some(bytes) => output(bytes, binary),
+        None => error::throw("ValueError", "hash(): Argument #1 ($algo) must be a valid hashing algorithm"),
+    }
+}
+
+pub fn hash_algos(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 0);
+
+    Object::from_array(ALGORITHMS.iter().map(|algorithm| Object::String((*algorithm).into())).collect())
+}
+
+pub fn hash_hmac(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 4);
+
+    let algorithm = convert::to_string(&args[0]).to_lowercase();
+    let data = convert::to_bytes(&args[1]);
+    let key = convert::to_bytes(&args[2]);
+    let binary = args.get(3).is_some_and(Object::to_bool);
+
+    let size = match block_size(&algorithm) {
+        Some(size) => size,
+        None => error::throw("ValueError", "hash_hmac(): Argument #1 ($algo) must be a valid cryptographic hashing algorithm"),
+    };
+
+    // Keys longer than a block are hashed first, and shorter ones are padded with zeros.
+    let mut key = match key.len() > size {
+        true => digest(&algorithm, &key).unwrap(),
+        false => key.into_bytes(),
+    };
+
+    key.resize(size, 0);
+
+    let mut inner: Vec<u8> = key.iter().map(|b| b ^ 0x36).collect();
+    inner.extend_from_slice(&data);
+
+    let mut outer: Vec<u8> = key.iter().map(|b| b ^ 0x5c).collect();
+    outer.extend(digest(&algorithm, &inner).unwrap());
+
+    output(digest(&algorithm, &outer).unwrap(), binary)
+}
+
+pub fn password_hash(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 3);
+
+    let password = convert::to_bytes(&args[0]);
+
+    match &args[1] {
+        Object::Null => (),
+        algorithm if convert::to_string(algorithm) == PASSWORD_BCRYPT => (),
+        _ => error::throw("ValueError", "password_hash(): Argument #2 ($algo) must be a valid password hashing algorithm"),
+    }
+
+    let cost = match args.get(2) {
+        Some(Object::Array(options)) => options.borrow().get(&Key::String("cost".into())).map(convert::to_int).unwrap_or(PASSWORD_BCRYPT_DEFAULT_COST),
+        _ => PASSWORD_BCRYPT_DEFAULT_COST,
+    };
+
+    if ! (4..=31).contains(&cost) {
+        error::throw("ValueError", format!("Invalid bcrypt cost parameter specified: {}", cost));
+    }
+
+    match bcrypt::hash_with_result(password.as_bytes(), cost as u32) {
+        Ok(parts) => Object::String(parts.format_for_version(bcrypt::Version::TwoY).into()),
+        Err(e) => error::throw("Error", format!("password_hash(): {}", e)),
+    }
+}
+
+pub fn password_verify(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    let password = convert::to_bytes(&args[0]);
+    let hash = convert::to_string(&args[1]);
+
+    Object::from_bool(bcrypt::verify(password.as_bytes(), &hash).unwrap_or(false))
+}
+
+pub fn password_get_info(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    let hash = convert::to_string(&args[0]);
+    let mut info = Array::new();
+    let mut options = Array::new();
+
+    // A bcrypt hash is "$2y$", a two digit cost, "$" and 53 characters of salt and hash.
+    let bcrypt = hash.len() == 60 && hash.starts_with("$2y$") && hash.as_bytes()[6] == b'$';
+
+    match bcrypt {
+        true => {
+            info.insert(Key::String("algo".into()), Object::String(PASSWORD_BCRYPT.into()));
+            info.insert(Key::String("algoName".into()), Object::String("bcrypt".into()));
+            options.insert(Key::String("cost".into()), Object::Integer(hash[4..6].parse().unwrap_or(0)));
+        },
+        false => {
+            info.insert(Key::String("algo".into()), Object::Null);
+            info.insert(Key::String("algoName".into()), Object::String("unknown".into()));
+        },
+    }
+
+    info.insert(Key::String("options".into()), Object::from_array(options));
+
+    Object::from_array(info)
+}
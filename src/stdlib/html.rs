@@ -0,0 +1,194 @@
+use super::arity;
+use crate::object::{Object, ByteString, convert};
+use crate::vm::Machine;
+
+pub const ENT_HTML_QUOTE_NONE: i64 = 0;
+pub const ENT_HTML_QUOTE_SINGLE: i64 = 1;
+pub const ENT_HTML_QUOTE_DOUBLE: i64 = 2;
+pub const ENT_COMPAT: i64 = 2;
+pub const ENT_QUOTES: i64 = 3;
+pub const ENT_NOQUOTES: i64 = 0;
+pub const ENT_IGNORE: i64 = 4;
+pub const ENT_SUBSTITUTE: i64 = 8;
+pub const ENT_DISALLOWED: i64 = 128;
+pub const ENT_HTML401: i64 = 0;
+pub const ENT_XML1: i64 = 16;
+pub const ENT_XHTML: i64 = 32;
+pub const ENT_HTML5: i64 = 48;
+
+const DEFAULT_FLAGS: i64 = ENT_QUOTES | ENT_SUBSTITUTE | ENT_HTML401;
+
+/// The named entities for U+00A0 to U+00FF, in order.
+const LATIN1: [&str; 96] = [
+    "nbsp", "iexcl", "cent", "pound", "curren", "yen", "brvbar", "sect", "uml", "copy", "ordf", "laquo", "not", "shy", "reg", "macr",
+    "deg", "plusmn", "sup2", "sup3", "acute", "micro", "para", "middot", "cedil", "sup1", "ordm", "raquo", "frac14", "frac12", "frac34", "iquest",
+    "Agrave", "Aacute", "Acirc", "Atilde", "Auml", "Aring", "AElig", "Ccedil", "Egrave", "Eacute", "Ecirc", "Euml", "Igrave", "Iacute", "Icirc", "Iuml",
+    "ETH", "Ntilde", "Ograve", "Oacute", "Ocirc", "Otilde", "Ouml", "times", "Oslash", "Ugrave", "Uacute", "Ucirc", "Uuml", "Yacute", "THORN", "szlig",
+    "agrave", "aacute", "acirc", "atilde", "auml", "aring", "aelig", "ccedil", "egrave", "eacute", "ecirc", "euml", "igrave", "iacute", "icirc", "iuml",
+    "eth", "ntilde", "ograve", "oacute", "ocirc", "otilde", "ouml", "divide", "oslash", "ugrave", "uacute", "ucirc", "uuml", "yacute", "thorn", "yuml",
+];
+
+/// The rest of the HTML 4.01 entities, apart from the Greek letters and mathematical
+/// symbols.
+const SPECIAL: [(&str, u32); 45] = [
+    ("OElig", 338), ("oelig", 339), ("Scaron", 352), ("scaron", 353), ("Yuml", 376), ("fnof", 402), ("circ", 710), ("tilde", 732),
+    ("ensp", 8194), ("emsp", 8195), ("thinsp", 8201), ("zwnj", 8204), ("zwj", 8205), ("lrm", 8206), ("rlm", 8207), ("ndash", 8211),
+    ("mdash", 8212), ("lsquo", 8216), ("rsquo", 8217), ("sbquo", 8218), ("ldquo", 8220), ("rdquo", 8221), ("bdquo", 8222), ("dagger", 8224),
+    ("Dagger", 8225), ("bull", 8226), ("hellip", 8230), ("permil", 8240), ("prime", 8242), ("Prime", 8243), ("lsaquo", 8249), ("rsaquo", 8250),
+    ("oline", 8254), ("frasl", 8260), ("euro", 8364), ("trade", 8482), ("larr", 8592), ("uarr", 8593), ("rarr", 8594), ("darr", 8595),
+    ("harr", 8596), ("spades", 9824), ("clubs", 9827), ("hearts", 9829), ("diams", 9830),
+];
+
+/// What `'` is encoded as, which depends on the document type.
+fn apostrophe(flags: i64) -> &'static str {
+    match flags & ENT_HTML5 {
+        ENT_HTML401 => "&#039;",
+        _ => "&apos;",
+    }
+}
+
+/// Finds the entity at the start of `text` (just after the '&'), returning the
+/// character it stands for and the length up to and including the ';'.
+fn entity(text: &[u8], flags: i64, all: bool) -> Option<(char, usize)> {
+    let end = text.iter().position(|b| *b == b';')?;
+    let name = std::str::from_utf8(&text[..end]).ok()?;
+
+    let character = if let Some(number) = name.strip_prefix('#') {
+        let code = match number.strip_prefix(['x', 'X']) {
+            Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+            None => number.parse().ok()?,
+        };
+
+        match char::from_u32(code)? {
+            // Without the full table, only the special characters are decoded.
+            c if all || matches!(c, '&' | '"' | '\'' | '<' | '>') => c,
+            _ => return None,
+        }
+    } else {
+        match name {
+            "amp" => '&',
+            "lt" => '<',
+            "gt" => '>',
+            "quot" => '"',
+            "apos" if flags & ENT_HTML5 != ENT_HTML401 => '\'',
+            name if all => match LATIN1.iter().position(|entity| *entity == name) {
+                Some(i) => char::from_u32(0xa0 + i as u32).unwrap(),
+                None => SPECIAL.iter().find(|(entity, _)| *entity == name).and_then(|(_, code)| char::from_u32(*code))?,
+            },
+            _ => return None,
+        }
+    };
+
+    // Quotes are only decoded when the flags ask for them.
+    match character {
+        '"' if flags & ENT_HTML_QUOTE_DOUBLE == 0 => None,
+        '\'' if flags & ENT_HTML_QUOTE_SINGLE == 0 => None,
+        _ => Some((character, end + 1)),
+    }
+}
+
+fn decode(text: &[u8], flags: i64, all: bool) -> ByteString {
+    let mut decoded = ByteString::new();
+    let mut rest = text;
+
+    while let Some(amp) = rest.iter().position(|b| *b == b'&') {
+        decoded.extend_from_slice(&rest[..amp]);
+        rest = &rest[amp + 1..];
+
+        match entity(rest, flags, all) {
+            Some((character, length)) => {
+                decoded.push_str(character.encode_utf8(&mut [0; 4]));
+                rest = &rest[length..];
+            },
+            None => decoded.push(b'&'),
+        }
+    }
+
+    decoded.extend_from_slice(rest);
+    decoded
+}
+
+/// Whether `text` (just after a '&') starts with something that's already an entity,
+/// which `htmlspecialchars()` leaves alone when `$double_encode` is off.
+fn is_entity(text: &str) -> bool {
+    let end = match text.find(';') {
+        Some(end) => end,
+        None => return false,
+    };
+
+    let name = &text[..end];
+
+    match name.strip_prefix('#') {
+        Some(number) => match number.strip_prefix(['x', 'X']) {
+            Some(hex) => ! hex.is_empty() && hex.bytes().all(|c| c.is_ascii_hexdigit()),
+            None => ! number.is_empty() && number.bytes().all(|c| c.is_ascii_digit()),
+        },
+        None => ! name.is_empty() && name.bytes().all(|c| c.is_ascii_alphanumeric()),
+    }
+}
+
+/// Decodes the subject as UTF-8. Invalid sequences are dropped with `ENT_IGNORE` or
+/// replaced with U+FFFD with `ENT_SUBSTITUTE`, and otherwise the whole string is rejected.
+fn text(subject: &ByteString, flags: i64) -> Option<String> {
+    if let Some(text) = subject.to_str() {
+        return Some(text.to_string());
+    }
+
+    if flags & (ENT_IGNORE | ENT_SUBSTITUTE) == 0 {
+        return None;
+    }
+
+    let mut text = String::with_capacity(subject.len());
+
+    for chunk in subject.utf8_chunks() {
+        text.push_str(chunk.valid());
+
+        if ! chunk.invalid().is_empty() && flags & ENT_IGNORE == 0 {
+            text.push(char::REPLACEMENT_CHARACTER);
+        }
+    }
+
+    Some(text)
+}
+
+pub fn htmlspecialchars(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 4);
+
+    let flags = args.get(1).map(convert::to_int).unwrap_or(DEFAULT_FLAGS);
+    let text = match text(&convert::to_bytes(&args[0]), flags) {
+        Some(text) => text,
+        None => return Object::String(ByteString::new()),
+    };
+    let double_encode = args.get(3).is_none_or(Object::to_bool);
+    let mut encoded = String::with_capacity(text.len());
+
+    for (i, c) in text.char_indices() {
+        match c {
+            '&' if ! double_encode && is_entity(&text[i + 1..]) => encoded.push('&'),
+            '&' => encoded.push_str("&amp;"),
+            '<' => encoded.push_str("&lt;"),
+            '>' => encoded.push_str("&gt;"),
+            '"' if flags & ENT_HTML_QUOTE_DOUBLE != 0 => encoded.push_str("&quot;"),
+            '\'' if flags & ENT_HTML_QUOTE_SINGLE != 0 => encoded.push_str(apostrophe(flags)),
+            c => encoded.push(c),
+        }
+    }
+
+    Object::String(encoded.into())
+}
+
+pub fn htmlspecialchars_decode(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    let flags = args.get(1).map(convert::to_int).unwrap_or(DEFAULT_FLAGS);
+
+    Object::String(decode(&convert::to_bytes(&args[0]), flags, false))
+}
+
+pub fn html_entity_decode(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 3);
+
+    let flags = args.get(1).map(convert::to_int).unwrap_or(DEFAULT_FLAGS);
+
+    Object::String(decode(&convert::to_bytes(&args[0]), flags, true))
+}
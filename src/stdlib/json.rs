@@ -0,0 +1,604 @@
+use super::arity;
+use crate::object::{Object, Array, Key, new_instance, convert};
+use crate::vm::Machine;
+use crate::error;
+
+pub const JSON_HEX_TAG: i64 = 1;
+pub const JSON_HEX_AMP: i64 = 2;
+pub const JSON_HEX_APOS: i64 = 4;
+pub const JSON_HEX_QUOT: i64 = 8;
+pub const JSON_FORCE_OBJECT: i64 = 16;
+pub const JSON_NUMERIC_CHECK: i64 = 32;
+pub const JSON_UNESCAPED_SLASHES: i64 = 64;
+pub const JSON_PRETTY_PRINT: i64 = 128;
+pub const JSON_UNESCAPED_UNICODE: i64 = 256;
+pub const JSON_PARTIAL_OUTPUT_ON_ERROR: i64 = 512;
+pub const JSON_PRESERVE_ZERO_FRACTION: i64 = 1024;
+pub const JSON_UNESCAPED_LINE_TERMINATORS: i64 = 2048;
+pub const JSON_OBJECT_AS_ARRAY: i64 = 1;
+pub const JSON_BIGINT_AS_STRING: i64 = 2;
+pub const JSON_INVALID_UTF8_IGNORE: i64 = 1048576;
+pub const JSON_INVALID_UTF8_SUBSTITUTE: i64 = 2097152;
+pub const JSON_THROW_ON_ERROR: i64 = 4194304;
+
+pub const JSON_ERROR_NONE: i64 = 0;
+pub const JSON_ERROR_DEPTH: i64 = 1;
+pub const JSON_ERROR_STATE_MISMATCH: i64 = 2;
+pub const JSON_ERROR_CTRL_CHAR: i64 = 3;
+pub const JSON_ERROR_SYNTAX: i64 = 4;
+pub const JSON_ERROR_UTF8: i64 = 5;
+pub const JSON_ERROR_RECURSION: i64 = 6;
+pub const JSON_ERROR_INF_OR_NAN: i64 = 7;
+pub const JSON_ERROR_UNSUPPORTED_TYPE: i64 = 8;
+pub const JSON_ERROR_INVALID_PROPERTY_NAME: i64 = 9;
+pub const JSON_ERROR_UTF16: i64 = 10;
+
+fn message(error: i64) -> &'static str {
+    match error {
+        JSON_ERROR_NONE => "No error",
+        JSON_ERROR_DEPTH => "Maximum stack depth exceeded",
+        JSON_ERROR_STATE_MISMATCH => "State mismatch (invalid or malformed JSON)",
+        JSON_ERROR_CTRL_CHAR => "Control character error, possibly incorrectly encoded",
+        JSON_ERROR_SYNTAX => "Syntax error",
+        JSON_ERROR_UTF8 => "Malformed UTF-8 characters, possibly incorrectly encoded",
+        JSON_ERROR_RECURSION => "Recursion detected",
+        JSON_ERROR_INF_OR_NAN => "Inf and NaN cannot be JSON encoded",
+        JSON_ERROR_UNSUPPORTED_TYPE => "Type is not supported",
+        JSON_ERROR_INVALID_PROPERTY_NAME => "The decoded property name is invalid",
+        JSON_ERROR_UTF16 => "Single unpaired UTF-16 surrogate in unicode escape",
+        _ => "Unknown error",
+    }
+}
+
+/// Records the outcome of an encode or decode. With `JSON_THROW_ON_ERROR` an error is
+/// thrown as a `JsonException` instead, and the last error is left alone.
+fn finish(vm: &mut Machine, flags: i64, error: i64) {
+    if flags & JSON_THROW_ON_ERROR == 0 {
+        vm.json_last_error = error;
+    } else if error != JSON_ERROR_NONE {
+        error::throw("JsonException", message(error));
+    }
+}
+
+fn depth(function: &str, value: Option<&Object>) -> usize {
+    match value.map(convert::to_int).unwrap_or(512) {
+        depth if depth <= 0 => error::throw("ValueError", format!("{}(): Argument #3 ($depth) must be greater than 0", function)),
+        depth if depth > i32::MAX as i64 => error::throw("ValueError", format!("{}(): Argument #3 ($depth) must be less than {}", function, i32::MAX)),
+        depth => depth as usize,
+    }
+}
+
+/// A port of `php_json_encode_zval()`. The first error is remembered, and when
+/// `JSON_PARTIAL_OUTPUT_ON_ERROR` is set the value that caused it is replaced.
+struct Encoder {
+    flags: i64,
+    max_depth: usize,
+    depth: usize,
+    error: i64,
+}
+
+impl Encoder {
+    fn fail(&mut self, buffer: &mut String, error: i64, substitute: &str) {
+        if self.error == JSON_ERROR_NONE {
+            self.error = error;
+        }
+
+        buffer.push_str(substitute);
+    }
+
+    fn newline(&self, buffer: &mut String) {
+        if self.flags & JSON_PRETTY_PRINT != 0 {
+            buffer.push('\n');
+            buffer.push_str(&"    ".repeat(self.depth));
+        }
+    }
+
+    fn encode(&mut self, buffer: &mut String, value: &Object) {
+        match value {
+            Object::Null => buffer.push_str("null"),
+            Object::True => buffer.push_str("true"),
+            Object::False => buffer.push_str("false"),
+            Object::Integer(i) => buffer.push_str(&i.to_string()),
+            Object::Float(f) => self.float(buffer, *f),
+            Object::String(s) => self.string(buffer, s),
+            Object::Array(items) => {
+                let items = items.borrow();
+                let list = self.flags & JSON_FORCE_OBJECT == 0
+                    && items.keys().enumerate().all(|(i, key)| *key == Key::Integer(i as i64));
+
+                self.array(buffer, &items, list);
+            },
+            Object::Instance(instance) => self.array(buffer, &instance.borrow().properties, false),
+            Object::Resource(..) => self.fail(buffer, JSON_ERROR_UNSUPPORTED_TYPE, "null"),
+        }
+    }
+
+    fn float(&mut self, buffer: &mut String, value: f64) {
+        if ! value.is_finite() {
+            return self.fail(buffer, JSON_ERROR_INF_OR_NAN, "0");
+        }
+
+        let mut formatted = convert::gcvt(value, convert::SERIALIZE_PRECISION, 'e');
+
+        if self.flags & JSON_PRESERVE_ZERO_FRACTION != 0 && ! formatted.contains(['.', 'e']) {
+            formatted.push_str(".0");
+        }
+
+        buffer.push_str(&formatted);
+    }
+
+    fn array(&mut self, buffer: &mut String, items: &Array, list: bool) {
+        let (open, close) = if list { ('[', ']') } else { ('{', '}') };
+
+        self.depth += 1;
+
+        if self.depth > self.max_depth {
+            self.fail(buffer, JSON_ERROR_DEPTH, "");
+
+            if self.flags & JSON_PARTIAL_OUTPUT_ON_ERROR == 0 {
+                self.depth -= 1;
+                return;
+            }
+        }
+
+        buffer.push(open);
+
+        for (i, (key, value)) in items.iter().enumerate() {
+            if i > 0 {
+                buffer.push(',');
+            }
+
+            self.newline(buffer);
+
+            if ! list {
+                match key {
+                    Key::Integer(i) => self.escape(buffer, i.to_string().as_bytes()),
+                    Key::String(s) => self.escape(buffer, s),
+                }
+
+                buffer.push(':');
+
+                if self.flags & JSON_PRETTY_PRINT != 0 {
+                    buffer.push(' ');
+                }
+            }
+
+            self.encode(buffer, value);
+        }
+
+        self.depth -= 1;
+
+        if ! items.is_empty() {
+            self.newline(buffer);
+        }
+
+        buffer.push(close);
+    }
+
+    fn string(&mut self, buffer: &mut String, value: &[u8]) {
+        if self.flags & JSON_NUMERIC_CHECK != 0 {
+            match convert::parse_numeric(value) {
+                Some((Object::Integer(i), false)) => return buffer.push_str(&i.to_string()),
+                Some((Object::Float(f), false)) => return self.float(buffer, f),
+                _ => (),
+            }
+        }
+
+        self.escape(buffer, value)
+    }
+
+    fn escape(&mut self, buffer: &mut String, value: &[u8]) {
+        let mut bytes = value;
+        let mut escaped = String::with_capacity(bytes.len() + 2);
+        escaped.push('"');
+
+        // Strings are validated a chunk at a time, so that invalid sequences can be
+        // skipped or substituted.
+        while ! bytes.is_empty() {
+            let (valid, invalid) = match std::str::from_utf8(bytes) {
+                Ok(valid) => (valid, 0),
+                Err(e) => (std::str::from_utf8(&bytes[..e.valid_up_to()]).unwrap(), e.error_len().unwrap_or(bytes.len() - e.valid_up_to())),
+            };
+
+            for c in valid.chars() {
+                self.character(&mut escaped, c);
+            }
+
+            bytes = &bytes[valid.len()..];
+
+            if invalid > 0 {
+                if self.flags & JSON_INVALID_UTF8_SUBSTITUTE != 0 {
+                    self.character(&mut escaped, '\u{fffd}');
+                } else if self.flags & JSON_INVALID_UTF8_IGNORE == 0 {
+                    return self.fail(buffer, JSON_ERROR_UTF8, "null");
+                }
+
+                bytes = &bytes[invalid..];
+            }
+        }
+
+        escaped.push('"');
+        buffer.push_str(&escaped);
+    }
+
+    fn character(&self, buffer: &mut String, c: char) {
+        let flag = |flag: i64| self.flags & flag != 0;
+
+        match c {
+            '"' if flag(JSON_HEX_QUOT) => buffer.push_str("\\u0022"),
+            '"' => buffer.push_str("\\\""),
+            '\\' => buffer.push_str("\\\\"),
+            '/' if ! flag(JSON_UNESCAPED_SLASHES) => buffer.push_str("\\/"),
+            '\u{8}' => buffer.push_str("\\b"),
+            '\u{c}' => buffer.push_str("\\f"),
+            '\n' => buffer.push_str("\\n"),
+            '\r' => buffer.push_str("\\r"),
+            '\t' => buffer.push_str("\\t"),
+            '<' if flag(JSON_HEX_TAG) => buffer.push_str("\\u003C"),
+            '>' if flag(JSON_HEX_TAG) => buffer.push_str("\\u003E"),
+            '&' if flag(JSON_HEX_AMP) => buffer.push_str("\\u0026"),
+            '\'' if flag(JSON_HEX_APOS) => buffer.push_str("\\u0027"),
+            c if (c as u32) < 0x20 => buffer.push_str(&format!("\\u{:04x}", c as u32)),
+            c if c.is_ascii() => buffer.push(c),
+            '\u{2028}' | '\u{2029}' if flag(JSON_UNESCAPED_UNICODE) && ! flag(JSON_UNESCAPED_LINE_TERMINATORS) => {
+                buffer.push_str(&format!("\\u{:04x}", c as u32));
+            },
+            c if flag(JSON_UNESCAPED_UNICODE) => buffer.push(c),
+            c => {
+                let mut units = [0; 2];
+
+                for unit in c.encode_utf16(&mut units) {
+                    buffer.push_str(&format!("\\u{:04x}", unit));
+                }
+            },
+        }
+    }
+}
+
+pub fn json_encode(vm: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 3);
+
+    let flags = args.get(1).map(convert::to_int).unwrap_or(0);
+    let mut encoder = Encoder { flags, max_depth: depth("json_encode", args.get(2)), depth: 0, error: JSON_ERROR_NONE };
+    let mut buffer = String::new();
+
+    encoder.encode(&mut buffer, &args[0]);
+    finish(vm, flags, encoder.error);
+
+    match encoder.error {
+        JSON_ERROR_NONE => Object::String(buffer.into()),
+        _ if flags & JSON_PARTIAL_OUTPUT_ON_ERROR != 0 => Object::String(buffer.into()),
+        _ => Object::False,
+    }
+}
+
+/// A recursive descent parser for RFC 8259 JSON, which is as strict as PHP's.
+struct Decoder<'a> {
+    input: &'a [u8],
+    position: usize,
+    assoc: bool,
+    big_int_as_string: bool,
+    max_depth: usize,
+    depth: usize,
+}
+
+impl<'a> Decoder<'a> {
+    fn whitespace(&mut self) {
+        while matches!(self.input.get(self.position), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+            self.position += 1;
+        }
+    }
+
+    fn literal(&mut self, literal: &str, value: Object) -> Result<Object, i64> {
+        match self.input[self.position..].starts_with(literal.as_bytes()) {
+            true => {
+                self.position += literal.len();
+                Ok(value)
+            },
+            false => Err(JSON_ERROR_SYNTAX),
+        }
+    }
+
+    fn value(&mut self) -> Result<Object, i64> {
+        self.whitespace();
+
+        match self.input.get(self.position) {
+            Some(b'{') => self.object(),
+            Some(b'[') => self.array(),
+            Some(b'"') => self.string().map(|string| Object::String(string.into())),
+            Some(b't') => self.literal("true", Object::True),
+            Some(b'f') => self.literal("false", Object::False),
+            Some(b'n') => self.literal("null", Object::Null),
+            Some(b'-' | b'0'..=b'9') => self.number(),
+            _ => Err(JSON_ERROR_SYNTAX),
+        }
+    }
+
+    fn enter(&mut self) -> Result<(), i64> {
+        if self.depth >= self.max_depth {
+            return Err(JSON_ERROR_DEPTH);
+        }
+
+        self.depth += 1;
+        self.position += 1;
+        self.whitespace();
+
+        Ok(())
+    }
+
+    fn array(&mut self) -> Result<Object, i64> {
+        self.enter()?;
+
+        let mut items = Array::new();
+
+        if self.input.get(self.position) == Some(&b']') {
+            self.position += 1;
+            self.depth -= 1;
+
+            return Ok(Object::from_array(items));
+        }
+
+        loop {
+            items.push(self.value()?);
+            self.whitespace();
+
+            match self.input.get(self.position) {
+                Some(b',') => self.position += 1,
+                Some(b']') => break,
+                _ => return Err(JSON_ERROR_SYNTAX),
+            }
+        }
+
+        self.position += 1;
+        self.depth -= 1;
+
+        Ok(Object::from_array(items))
+    }
+
+    fn object(&mut self) -> Result<Object, i64> {
+        self.enter()?;
+
+        let mut properties = Array::new();
+
+        // PHP creates the object once its first property has been parsed, which is
+        // visible in the object ids of nested objects.
+        let mut instance = None;
+
+        if self.input.get(self.position) != Some(&b'}') {
+            loop {
+                self.whitespace();
+
+                if self.input.get(self.position) != Some(&b'"') {
+                    return Err(JSON_ERROR_SYNTAX);
+                }
+
+                let key = self.string()?;
+                self.whitespace();
+
+                if self.input.get(self.position) != Some(&b':') {
+                    return Err(JSON_ERROR_SYNTAX);
+                }
+
+                self.position += 1;
+
+                let value = self.value()?;
+
+                if ! self.assoc && instance.is_none() {
+                    instance = Some(new_instance("stdClass", Array::new()));
+                }
+
+                // Objects always have string property names, while arrays turn numeric
+                // keys into integers like they would anywhere else.
+                let key = match self.assoc {
+                    true => Key::from(key.as_str()),
+                    false if key.starts_with('\0') => return Err(JSON_ERROR_INVALID_PROPERTY_NAME),
+                    false => Key::String(key.into()),
+                };
+
+                properties.insert(key, value);
+                self.whitespace();
+
+                match self.input.get(self.position) {
+                    Some(b',') => self.position += 1,
+                    Some(b'}') => break,
+                    _ => return Err(JSON_ERROR_SYNTAX),
+                }
+            }
+        }
+
+        self.position += 1;
+        self.depth -= 1;
+
+        if self.assoc {
+            return Ok(Object::from_array(properties));
+        }
+
+        let instance = instance.unwrap_or_else(|| new_instance("stdClass", Array::new()));
+
+        if let Object::Instance(object) = &instance {
+            object.borrow_mut().properties = properties;
+        }
+
+        Ok(instance)
+    }
+
+    fn hex(&mut self) -> Result<u32, i64> {
+        let digits = self.input.get(self.position..self.position + 4).ok_or(JSON_ERROR_SYNTAX)?;
+        let digits = std::str::from_utf8(digits).map_err(|_| JSON_ERROR_SYNTAX)?;
+        let value = u32::from_str_radix(digits, 16).map_err(|_| JSON_ERROR_SYNTAX)?;
+
+        self.position += 4;
+
+        Ok(value)
+    }
+
+    fn string(&mut self) -> Result<String, i64> {
+        self.position += 1;
+
+        let mut bytes = Vec::new();
+
+        loop {
+            let c = *self.input.get(self.position).ok_or(JSON_ERROR_CTRL_CHAR)?;
+            self.position += 1;
+
+            match c {
+                b'"' => break,
+                b'\\' => {
+                    let escape = *self.input.get(self.position).ok_or(JSON_ERROR_SYNTAX)?;
+                    self.position += 1;
+
+                    let c = match escape {
+                        b'"' => '"',
+                        b'\\' => '\\',
+                        b'/' => '/',
+                        b'b' => '\u{8}',
+                        b'f' => '\u{c}',
+                        b'n' => '\n',
+                        b'r' => '\r',
+                        b't' => '\t',
+                        b'u' => {
+                            let unit = self.hex()?;
+
+                            let code = match unit {
+                                0xD800..=0xDBFF if self.input[self.position..].starts_with(b"\\u") => {
+                                    self.position += 2;
+
+                                    match self.hex()? {
+                                        low @ 0xDC00..=0xDFFF => 0x10000 + ((unit - 0xD800) << 10) + (low - 0xDC00),
+                                        _ => return Err(JSON_ERROR_UTF16),
+                                    }
+                                },
+                                0xD800..=0xDFFF => return Err(JSON_ERROR_UTF16),
+                                unit => unit,
+                            };
+
+                            char::from_u32(code).ok_or(JSON_ERROR_UTF16)?
+                        },
+                        _ => return Err(JSON_ERROR_SYNTAX),
+                    };
+
+                    let mut encoded = [0; 4];
+                    bytes.extend_from_slice(c.encode_utf8(&mut encoded).as_bytes());
+                },
+                c if c < 0x20 => return Err(JSON_ERROR_CTRL_CHAR),
+                c => bytes.push(c),
+            }
+        }
+
+        String::from_utf8(bytes).map_err(|_| JSON_ERROR_UTF8)
+    }
+
+    fn number(&mut self) -> Result<Object, i64> {
+        let start = self.position;
+        let digits = |decoder: &mut Self| {
+            let start = decoder.position;
+
+            while decoder.input.get(decoder.position).is_some_and(u8::is_ascii_digit) {
+                decoder.position += 1;
+            }
+
+            decoder.position - start
+        };
+
+        if self.input[self.position] == b'-' {
+            self.position += 1;
+        }
+
+        let leading_zero = self.input.get(self.position) == Some(&b'0');
+
+        match digits(self) {
+            0 => return Err(JSON_ERROR_SYNTAX),
+            n if n > 1 && leading_zero => return Err(JSON_ERROR_SYNTAX),
+            _ => (),
+        }
+
+        let mut integer = true;
+
+        if self.input.get(self.position) == Some(&b'.') {
+            self.position += 1;
+            integer = false;
+
+            if digits(self) == 0 {
+                return Err(JSON_ERROR_SYNTAX);
+            }
+        }
+
+        if matches!(self.input.get(self.position), Some(b'e' | b'E')) {
+            self.position += 1;
+            integer = false;
+
+            if matches!(self.input.get(self.position), Some(b'+' | b'-')) {
+                self.position += 1;
+            }
+
+            if digits(self) == 0 {
+                return Err(JSON_ERROR_SYNTAX);
+            }
+        }
+
+        let number = std::str::from_utf8(&self.input[start..self.position]).unwrap();
+
+        Ok(match integer {
+            true => match number.parse::<i64>() {
+                Ok(i) => Object::Integer(i),
+                Err(_) if self.big_int_as_string => Object::String(number.into()),
+                Err(_) => Object::Float(number.parse().unwrap()),
+            },
+            false => Object::Float(number.parse().unwrap()),
+        })
+    }
+}
+
+pub fn json_decode(vm: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 4);
+
+    let json = convert::to_bytes(&args[0]);
+    let max_depth = depth("json_decode", args.get(2));
+    let flags = args.get(3).map(convert::to_int).unwrap_or(0);
+
+    let assoc = match args.get(1) {
+        None | Some(Object::Null) => flags & JSON_OBJECT_AS_ARRAY != 0,
+        Some(assoc) => assoc.to_bool(),
+    };
+
+    let mut decoder = Decoder {
+        input: json.as_bytes(),
+        position: 0,
+        assoc,
+        big_int_as_string: flags & JSON_BIGINT_AS_STRING != 0,
+        max_depth,
+        depth: 0,
+    };
+
+    let result = decoder.value().and_then(|value| {
+        decoder.whitespace();
+
+        match decoder.position == decoder.input.len() {
+            true => Ok(value),
+            false => Err(JSON_ERROR_SYNTAX),
+        }
+    });
+
+    match result {
+        Ok(value) => {
+            finish(vm, flags, JSON_ERROR_NONE);
+            value
+        },
+        Err(error) => {
+            finish(vm, flags, error);
+            Object::Null
+        },
+    }
+}
+
+pub fn json_last_error(vm: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 0);
+
+    Object::Integer(vm.json_last_error)
+}
+
+pub fn json_last_error_msg(vm: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 0);
+
+    Object::String(message(vm.json_last_error).into())
+}
@@ -0,0 +1,437 @@
+use super::arity;
+use crate::object::{Object, convert};
+use crate::vm::Machine;
+use crate::error;
+use std::convert::TryFrom;
+
+pub const PHP_ROUND_HALF_UP: i64 = 1;
+pub const PHP_ROUND_HALF_DOWN: i64 = 2;
+pub const PHP_ROUND_HALF_EVEN: i64 = 3;
+pub const PHP_ROUND_HALF_ODD: i64 = 4;
+
+const DIGITS: &[u8; 36] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+
+/// Coerces an `int|float` argument the same way PHP does for internal functions.
+fn number(function: &str, position: usize, name: &str, value: &Object) -> Object {
+    match value {
+        Object::Integer(..) | Object::Float(..) => value.clone(),
+        Object::True => Object::Integer(1),
+        Object::False | Object::Null => Object::Integer(0),
+        Object::String(s) => match convert::parse_numeric(s) {
+            Some((number, false)) => number,
+            Some((number, true)) => {
+                error::warning("A non-numeric value encountered");
+                number
+            },
+            None => error::throw("TypeError", format!("{}(): Argument #{} (${}) must be of type int|float, string given", function, position, name)),
+        },
+        value => error::throw("TypeError", format!("{}(): Argument #{} (${}) must be of type int|float, {} given", function, position, name, value.type_name())),
+    }
+}
+
+fn float(function: &str, position: usize, name: &str, value: &Object) -> f64 {
+    convert::to_float(&number(function, position, name, value))
+}
+
+fn integer(function: &str, position: usize, name: &str, value: &Object) -> i64 {
+    convert::to_int(&number(function, position, name, value))
+}
+
+/// Defines a function that takes one float and returns one float.
+macro_rules! float_function {
+    ($name:ident, $argument:literal, $value:ident => $body:expr) => {
+        pub fn $name(_: &mut Machine, args: Vec<Object>) -> Object {
+            arity!(args, 1);
+
+            let $value = float(stringify!($name), 1, $argument, &args[0]);
+
+            Object::Float($body)
+        }
+    };
+}
+
+float_function!(ceil, "num", value => value.ceil());
+float_function!(floor, "num", value => value.floor());
+float_function!(sqrt, "num", value => value.sqrt());
+float_function!(exp, "num", value => value.exp());
+float_function!(expm1, "num", value => value.exp_m1());
+float_function!(log10, "num", value => value.log10());
+float_function!(log1p, "num", value => value.ln_1p());
+float_function!(sin, "num", value => value.sin());
+float_function!(cos, "num", value => value.cos());
+float_function!(tan, "num", value => value.tan());
+float_function!(asin, "num", value => value.asin());
+float_function!(acos, "num", value => value.acos());
+float_function!(atan, "num", value => value.atan());
+float_function!(sinh, "num", value => value.sinh());
+float_function!(cosh, "num", value => value.cosh());
+float_function!(tanh, "num", value => value.tanh());
+float_function!(asinh, "num", value => value.asinh());
+float_function!(acosh, "num", value => value.acosh());
+float_function!(atanh, "num", value => value.atanh());
+float_function!(deg2rad, "num", value => value.to_radians());
+float_function!(rad2deg, "num", value => value.to_degrees());
+
+pub fn abs(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    match number("abs", 1, "num", &args[0]) {
+        Object::Integer(i) => match i.checked_abs() {
+            Some(i) => Object::Integer(i),
+            None => Object::Float(-(i as f64)),
+        },
+        n => Object::Float(convert::to_float(&n).abs()),
+    }
+}
+
+fn round_helper(value: f64, mode: i64) -> f64 {
+    let rounded = value.abs().floor();
+    let fraction = value.abs() - rounded;
+
+    let up = match mode {
+        _ if fraction != 0.5 => fraction > 0.5,
+        PHP_ROUND_HALF_DOWN => false,
+        PHP_ROUND_HALF_EVEN => rounded % 2.0 != 0.0,
+        PHP_ROUND_HALF_ODD => rounded % 2.0 == 0.0,
+        _ => true,
+    };
+
+    (rounded + if up { 1.0 } else { 0.0 }).copysign(value)
+}
+
+fn pow10(power: i64) -> f64 {
+    10f64.powi(power.clamp(-400, 400) as i32)
+}
+
+/// A port of PHP's `_php_math_round()`. The value is first rounded to the 15 significant
+/// digits a double can hold, so that `round(1.005, 2)` gives `1.01` as you'd expect.
+pub fn round_float(value: f64, places: i64, mode: i64) -> f64 {
+    if ! value.is_finite() || value == 0.0 {
+        return value;
+    }
+
+    let places = places.clamp(i32::MIN as i64 + 1, i32::MAX as i64);
+    let precision_places = 14 - value.abs().log10().floor() as i64;
+    let factor = pow10(places.abs());
+
+    let scaled = if precision_places > places && precision_places - 15 < places {
+        let precision = precision_places.max(-4 * f64::DIGITS as i64);
+        let basic = if precision >= 0 { value * pow10(precision) } else { value / pow10(-precision) };
+        let prerounded = round_helper(basic, mode);
+        let difference = (places - precision).max(-4 * f64::DIGITS as i64);
+
+        prerounded / pow10(difference.abs())
+    } else {
+        let scaled = if places >= 0 { value * factor } else { value / factor };
+
+        // Anything this large is already beyond the precision we could round to.
+        if scaled.abs() >= 1e15 {
+            return value;
+        }
+
+        scaled
+    };
+
+    let rounded = round_helper(scaled, mode);
+
+    let result = if places.abs() < 23 {
+        if places > 0 { rounded / factor } else { rounded * factor }
+    } else {
+        format!("{:.15}e{}", rounded, -places).parse().unwrap_or(f64::NAN)
+    };
+
+    if result.is_finite() { result } else { value }
+}
+
+pub fn round(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 3);
+
+    let value = number("round", 1, "num", &args[0]);
+    let places = args.get(1).map(|p| integer("round", 2, "precision", p)).unwrap_or(0);
+    let mode = args.get(2).map(|m| integer("round", 3, "mode", m)).unwrap_or(PHP_ROUND_HALF_UP);
+
+    if ! (PHP_ROUND_HALF_UP..=PHP_ROUND_HALF_ODD).contains(&mode) {
+        error::throw("ValueError", "round(): Argument #3 ($mode) must be a valid rounding mode (PHP_ROUND_*)");
+    }
+
+    Object::Float(match value {
+        // Integers only need rounding when the precision is negative.
+        Object::Integer(i) if places >= 0 => i as f64,
+        value => round_float(convert::to_float(&value), places, mode),
+    })
+}
+
+pub fn intdiv(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    let dividend = integer("intdiv", 1, "num1", &args[0]);
+    let divisor = integer("intdiv", 2, "num2", &args[1]);
+
+    if divisor == 0 {
+        error::throw("DivisionByZeroError", "Division by zero");
+    }
+
+    match dividend.checked_div(divisor) {
+        Some(quotient) => Object::Integer(quotient),
+        None => error::throw("ArithmeticError", "Division of PHP_INT_MIN by -1 is not an integer"),
+    }
+}
+
+pub fn fmod(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    let x = float("fmod", 1, "num1", &args[0]);
+    let y = float("fmod", 2, "num2", &args[1]);
+
+    Object::Float(x % y)
+}
+
+pub fn fdiv(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    let x = float("fdiv", 1, "num1", &args[0]);
+    let y = float("fdiv", 2, "num2", &args[1]);
+
+    Object::Float(x / y)
+}
+
+pub fn pow(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    let base = number("pow", 1, "num", &args[0]);
+    let exponent = number("pow", 2, "exponent", &args[1]);
+
+    match (base, exponent) {
+        (Object::Integer(base), Object::Integer(exponent)) if exponent >= 0 => {
+            match u32::try_from(exponent).ok().and_then(|e| base.checked_pow(e)) {
+                Some(result) => Object::Integer(result),
+                None => Object::Float((base as f64).powf(exponent as f64)),
+            }
+        },
+        (base, exponent) => Object::Float(convert::to_float(&base).powf(convert::to_float(&exponent))),
+    }
+}
+
+pub fn log(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    let value = float("log", 1, "num", &args[0]);
+
+    let base = match args.get(1) {
+        None => return Object::Float(value.ln()),
+        Some(base) => float("log", 2, "base", base),
+    };
+
+    if base <= 0.0 {
+        error::throw("ValueError", "log(): Argument #2 ($base) must be greater than 0");
+    }
+
+    Object::Float(match base {
+        1.0 => f64::NAN,
+        2.0 => value.log2(),
+        10.0 => value.log10(),
+        b => value.ln() / b.ln(),
+    })
+}
+
+pub fn atan2(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    let y = float("atan2", 1, "y", &args[0]);
+    let x = float("atan2", 2, "x", &args[1]);
+
+    Object::Float(y.atan2(x))
+}
+
+pub fn hypot(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    let x = float("hypot", 1, "x", &args[0]);
+    let y = float("hypot", 2, "y", &args[1]);
+
+    Object::Float(x.hypot(y))
+}
+
+pub fn pi(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 0);
+
+    Object::Float(std::f64::consts::PI)
+}
+
+fn extreme(function: &str, args: Vec<Object>, wanted: std::cmp::Ordering) -> Object {
+    let values: Vec<Object> = match args.len() {
+        0 => error::throw("ArgumentCountError", format!("{}() expects at least 1 argument, 0 given", function)),
+        1 => match &args[0] {
+            Object::Array(array) => array.borrow().values().cloned().collect(),
+            value => error::throw("TypeError", format!("{}(): Argument #1 ($value) must be of type array, {} given", function, value.type_name())),
+        },
+        _ => args,
+    };
+
+    let mut values = values.into_iter();
+    let first = match values.next() {
+        Some(first) => first,
+        None => error::throw("ValueError", format!("{}(): Argument #1 ($value) must contain at least one element", function)),
+    };
+
+    values.fold(first, |extreme, value| match convert::compare(&value, &extreme) == wanted {
+        true => value,
+        false => extreme,
+    })
+}
+
+pub fn min(_: &mut Machine, args: Vec<Object>) -> Object {
+    extreme("min", args, std::cmp::Ordering::Less)
+}
+
+pub fn max(_: &mut Machine, args: Vec<Object>) -> Object {
+    extreme("max", args, std::cmp::Ordering::Greater)
+}
+
+/// A port of `_php_math_basetozval()`. Invalid digits are skipped, and the result becomes
+/// a float if it doesn't fit in an integer.
+fn from_base(number: &str, base: u32) -> Object {
+    let mut digits = number.trim_matches(|c: char| c.is_ascii_whitespace()).as_bytes();
+
+    if digits.len() >= 2 && digits[0] == b'0' {
+        let prefix = digits[1].to_ascii_lowercase();
+
+        if (base == 16 && prefix == b'x') || (base == 8 && prefix == b'o') || (base == 2 && prefix == b'b') {
+            digits = &digits[2..];
+        }
+    }
+
+    let cutoff = i64::MAX / base as i64;
+    let cutlimit = i64::MAX % base as i64;
+    let (mut integer, mut float): (i64, Option<f64>) = (0, None);
+    let mut invalid = false;
+
+    for c in digits {
+        let digit = match (*c as char).to_digit(36) {
+            Some(d) if d < base => d as i64,
+            _ => {
+                invalid = true;
+                continue;
+            },
+        };
+
+        match float {
+            Some(f) => float = Some(f * base as f64 + digit as f64),
+            None if integer < cutoff || (integer == cutoff && digit <= cutlimit) => integer = integer * base as i64 + digit,
+            None => float = Some(integer as f64 * base as f64 + digit as f64),
+        }
+    }
+
+    if invalid {
+        error::deprecated("Invalid characters passed for attempted conversion, these have been ignored");
+    }
+
+    match float {
+        Some(f) => Object::Float(f),
+        None => Object::Integer(integer),
+    }
+}
+
+/// A port of `_php_math_zvaltobase()`. Integers are treated as unsigned.
+fn to_base(number: &Object, base: u32) -> String {
+    let mut digits = Vec::new();
+
+    match number {
+        Object::Float(f) => {
+            if ! f.is_finite() {
+                error::throw("ValueError", format!("An infinite value cannot be converted to base {}", base));
+            }
+
+            let mut value = f.abs().floor();
+
+            loop {
+                digits.push(DIGITS[(value % base as f64) as usize]);
+                value /= base as f64;
+
+                if value < 1.0 {
+                    break;
+                }
+            }
+        },
+        number => {
+            let mut value = convert::to_int(number) as u64;
+
+            loop {
+                digits.push(DIGITS[(value % base as u64) as usize]);
+                value /= base as u64;
+
+                if value == 0 {
+                    break;
+                }
+            }
+        },
+    }
+
+    digits.reverse();
+    String::from_utf8(digits).unwrap()
+}
+
+pub fn base_convert(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 3);
+
+    let number = convert::to_string(&args[0]);
+    let from = convert::to_int(&args[1]);
+    let to = convert::to_int(&args[2]);
+
+    if ! (2..=36).contains(&from) {
+        error::throw("ValueError", "base_convert(): Argument #2 ($from_base) must be between 2 and 36 (inclusive)");
+    }
+
+    if ! (2..=36).contains(&to) {
+        error::throw("ValueError", "base_convert(): Argument #3 ($to_base) must be between 2 and 36 (inclusive)");
+    }
+
+    Object::String(to_base(&from_base(&number.to_ascii_lowercase(), from as u32), to as u32).into())
+}
+
+macro_rules! from_base_function {
+    ($name:ident, $base:expr) => {
+        pub fn $name(_: &mut Machine, args: Vec<Object>) -> Object {
+            arity!(args, 1);
+
+            from_base(&convert::to_string(&args[0]), $base)
+        }
+    };
+}
+
+macro_rules! to_base_function {
+    ($name:ident, $base:expr) => {
+        pub fn $name(_: &mut Machine, args: Vec<Object>) -> Object {
+            arity!(args, 1);
+
+            let number = integer(stringify!($name), 1, "num", &args[0]);
+
+            Object::String(to_base(&Object::Integer(number), $base).into())
+        }
+    };
+}
+
+from_base_function!(bindec, 2);
+from_base_function!(octdec, 8);
+from_base_function!(hexdec, 16);
+to_base_function!(decbin, 2);
+to_base_function!(decoct, 8);
+to_base_function!(dechex, 16);
+
+pub fn is_nan(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    Object::from_bool(float("is_nan", 1, "num", &args[0]).is_nan())
+}
+
+pub fn is_finite(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    Object::from_bool(float("is_finite", 1, "num", &args[0]).is_finite())
+}
+
+pub fn is_infinite(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    Object::from_bool(float("is_infinite", 1, "num", &args[0]).is_infinite())
+}
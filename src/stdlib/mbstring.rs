@@ -0,0 +1,403 @@
+use super::{arity, STR_PAD_LEFT, STR_PAD_RIGHT, STR_PAD_BOTH};
+use crate::object::{Object, Array, ByteString, convert};
+use crate::vm::Machine;
+use crate::error;
+
+pub const MB_CASE_UPPER: i64 = 0;
+pub const MB_CASE_LOWER: i64 = 1;
+pub const MB_CASE_TITLE: i64 = 2;
+pub const MB_CASE_FOLD: i64 = 3;
+pub const MB_CASE_UPPER_SIMPLE: i64 = 4;
+pub const MB_CASE_LOWER_SIMPLE: i64 = 5;
+pub const MB_CASE_TITLE_SIMPLE: i64 = 6;
+pub const MB_CASE_FOLD_SIMPLE: i64 = 7;
+
+/// What characters that are invalid in the source or can't be represented in the
+/// target are replaced with, the same as mbstring's default substitute character.
+const SUBSTITUTE: char = '?';
+
+/// The encodings that the `mb_*` functions and `iconv()` understand.
+#[derive(Clone, Copy, PartialEq)]
+enum Encoding {
+    Utf8,
+    Latin1,
+    Ascii,
+}
+
+/// One character of a string: its bytes, and what it decodes to if it's valid.
+type Character<'a> = (&'a [u8], Option<char>);
+
+impl Encoding {
+    fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_uppercase().as_str() {
+            "UTF-8" | "UTF8" => Some(Encoding::Utf8),
+            "ISO-8859-1" | "ISO8859-1" | "LATIN1" => Some(Encoding::Latin1),
+            "ASCII" | "US-ASCII" => Some(Encoding::Ascii),
+            _ => None,
+        }
+    }
+
+    /// Splits a string into characters. Every byte of an invalid UTF-8 sequence counts
+    /// as a character of its own, the way mbstring counts them.
+    fn characters(self, bytes: &[u8]) -> Vec<Character<'_>> {
+        match self {
+            Encoding::Utf8 => {
+                let mut characters = Vec::with_capacity(bytes.len());
+
+                for chunk in bytes.utf8_chunks() {
+                    let valid = chunk.valid();
+
+                    for (i, c) in valid.char_indices() {
+                        characters.push((&valid.as_bytes()[i..i + c.len_utf8()], Some(c)));
+                    }
+
+                    for i in 0..chunk.invalid().len() {
+                        characters.push((&chunk.invalid()[i..i + 1], None));
+                    }
+                }
+
+                characters
+            },
+            Encoding::Latin1 => bytes.chunks(1).map(|b| (b, Some(b[0] as char))).collect(),
+            Encoding::Ascii => bytes.chunks(1).map(|b| (b, Some(b[0] as char).filter(char::is_ascii))).collect(),
+        }
+    }
+
+    /// Appends a character, returning false if the encoding can't represent it.
+    fn encode(self, c: char, buffer: &mut ByteString) -> bool {
+        match self {
+            Encoding::Utf8 => buffer.push_str(c.encode_utf8(&mut [0; 4])),
+            Encoding::Latin1 if (c as u32) < 0x100 => buffer.push(c as u8),
+            Encoding::Ascii if c.is_ascii() => buffer.push(c as u8),
+            _ => return false,
+        }
+
+        true
+    }
+}
+
+/// The encoding argument at `position`, which defaults to UTF-8.
+fn encoding(function: &str, args: &[Object], position: usize) -> Encoding {
+    let name = match args.get(position) {
+        None | Some(Object::Null) => return Encoding::Utf8,
+        Some(name) => convert::to_string(name),
+    };
+
+    match Encoding::from_name(&name) {
+        Some(encoding) => encoding,
+        None => error::throw("ValueError", format!("{}(): Argument #{} ($encoding) must be a valid encoding, \"{}\" given", function, position + 1, name)),
+    }
+}
+
+fn join(characters: &[Character]) -> Object {
+    Object::String(characters.iter().flat_map(|(bytes, _)| bytes.iter().copied()).collect())
+}
+
+/// Re-encodes a string, replacing anything that's invalid or can't be represented.
+fn convert_encoding(bytes: &[u8], from: Encoding, to: Encoding) -> ByteString {
+    let mut converted = ByteString::new();
+
+    for (_, c) in from.characters(bytes) {
+        if ! c.is_some_and(|c| to.encode(c, &mut converted)) {
+            converted.push(SUBSTITUTE as u8);
+        }
+    }
+
+    converted
+}
+
+/// Changes the case of every character. Title case uppercases the first letter of
+/// each word and lowercases the rest, where apostrophes and full stops don't end a word.
+fn convert_case(bytes: &[u8], mode: i64, encoding: Encoding) -> ByteString {
+    let mut converted = ByteString::new();
+    let mut in_word = false;
+
+    for (_, c) in encoding.characters(bytes) {
+        let c = match c {
+            Some(c) => c,
+            None => {
+                converted.push(SUBSTITUTE as u8);
+                in_word = false;
+                continue;
+            },
+        };
+
+        let upper = match mode {
+            MB_CASE_UPPER | MB_CASE_UPPER_SIMPLE => true,
+            MB_CASE_TITLE | MB_CASE_TITLE_SIMPLE => ! in_word,
+            _ => false,
+        };
+
+        let mapped: Vec<char> = match upper {
+            true => c.to_uppercase().collect(),
+            false => c.to_lowercase().collect(),
+        };
+
+        // The simple mappings never turn one character into several, like "ß" into "SS".
+        let simple = matches!(mode, MB_CASE_UPPER_SIMPLE | MB_CASE_LOWER_SIMPLE | MB_CASE_TITLE_SIMPLE | MB_CASE_FOLD_SIMPLE);
+        let mapped = match simple && mapped.len() > 1 {
+            true => vec![c],
+            false => mapped,
+        };
+
+        for c in mapped {
+            if ! encoding.encode(c, &mut converted) {
+                converted.push(SUBSTITUTE as u8);
+            }
+        }
+
+        in_word = c.is_alphabetic() || (in_word && matches!(c, '\'' | '\u{2019}' | '.' | ':'));
+    }
+
+    converted
+}
+
+pub fn mb_strlen(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    let encoding = encoding("mb_strlen", &args, 1);
+
+    Object::Integer(encoding.characters(&convert::to_bytes(&args[0])).len() as i64)
+}
+
+pub fn mb_substr(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 4);
+
+    let encoding = encoding("mb_substr", &args, 3);
+    let subject = convert::to_bytes(&args[0]);
+    let characters = encoding.characters(&subject);
+    let len = characters.len() as i64;
+
+    let start = match convert::to_int(&args[1]) {
+        start if start < 0 => (len + start).max(0),
+        start => start.min(len),
+    };
+
+    let end = match args.get(2) {
+        None | Some(Object::Null) => len,
+        Some(length) => match convert::to_int(length) {
+            l if l < 0 => len + l,
+            l => len.min(start.saturating_add(l)),
+        },
+    };
+
+    match end <= start {
+        true => Object::String(ByteString::new()),
+        false => join(&characters[start as usize..end as usize]),
+    }
+}
+
+pub fn mb_strpos(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 4);
+
+    let encoding = encoding("mb_strpos", &args, 3);
+    let haystack = convert::to_bytes(&args[0]);
+    let needle = convert::to_bytes(&args[1]);
+    let characters = encoding.characters(&haystack);
+    let len = characters.len() as i64;
+
+    let offset = match args.get(2).map(convert::to_int).unwrap_or(0) {
+        offset if offset < 0 => len + offset,
+        offset => offset,
+    };
+
+    if ! (0..=len).contains(&offset) {
+        error::throw("ValueError", "mb_strpos(): Argument #3 ($offset) must be contained in argument #1 ($haystack)");
+    }
+
+    // Searching is done on the bytes, starting at the offset's first byte.
+    let start: usize = characters[..offset as usize].iter().map(|(bytes, _)| bytes.len()).sum();
+    let found = match needle.is_empty() {
+        true => Some(start),
+        false => haystack[start..].windows(needle.len()).position(|window| window == needle.as_bytes()).map(|i| start + i),
+    };
+
+    match found {
+        Some(found) => {
+            let mut position = 0;
+            let index = characters.iter().take_while(|(bytes, _)| {
+                position += bytes.len();
+                position <= found
+            }).count();
+
+            Object::Integer(index as i64)
+        },
+        None => Object::False,
+    }
+}
+
+pub fn mb_strtoupper(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    let encoding = encoding("mb_strtoupper", &args, 1);
+
+    Object::String(convert_case(&convert::to_bytes(&args[0]), MB_CASE_UPPER, encoding))
+}
+
+pub fn mb_strtolower(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    let encoding = encoding("mb_strtolower", &args, 1);
+
+    Object::String(convert_case(&convert::to_bytes(&args[0]), MB_CASE_LOWER, encoding))
+}
+
+pub fn mb_convert_case(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 3);
+
+    let mode = convert::to_int(&args[1]);
+    let encoding = encoding("mb_convert_case", &args, 2);
+
+    if ! (MB_CASE_UPPER..=MB_CASE_FOLD_SIMPLE).contains(&mode) {
+        error::throw("ValueError", "mb_convert_case(): Argument #2 ($mode) must be one of the MB_CASE_* constants");
+    }
+
+    Object::String(convert_case(&convert::to_bytes(&args[0]), mode, encoding))
+}
+
+pub fn mb_str_split(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 3);
+
+    let length = args.get(1).map(convert::to_int).unwrap_or(1);
+    let encoding = encoding("mb_str_split", &args, 2);
+
+    if length < 1 {
+        error::throw("ValueError", "mb_str_split(): Argument #2 ($length) must be greater than 0");
+    }
+
+    let subject = convert::to_bytes(&args[0]);
+
+    Object::from_array(encoding.characters(&subject).chunks(length as usize).map(join).collect())
+}
+
+pub fn mb_str_pad(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 5);
+
+    let subject = convert::to_bytes(&args[0]);
+    let length = convert::to_int(&args[1]);
+    let padding = args.get(2).map(convert::to_bytes).unwrap_or_else(|| " ".into());
+    let r#type = args.get(3).map(convert::to_int).unwrap_or(STR_PAD_RIGHT);
+    let encoding = encoding("mb_str_pad", &args, 4);
+
+    if padding.is_empty() {
+        error::throw("ValueError", "mb_str_pad(): Argument #3 ($pad_string) must be a non-empty string");
+    }
+
+    let characters = encoding.characters(&subject);
+    let padding = encoding.characters(&padding);
+
+    if length <= characters.len() as i64 {
+        return Object::String(subject);
+    }
+
+    let total = length as usize - characters.len();
+    let (left, right) = match r#type {
+        STR_PAD_LEFT => (total, 0),
+        STR_PAD_RIGHT => (0, total),
+        STR_PAD_BOTH => (total / 2, total - total / 2),
+        _ => error::throw("ValueError", "mb_str_pad(): Argument #4 ($pad_type) must be STR_PAD_LEFT, STR_PAD_RIGHT, or STR_PAD_BOTH"),
+    };
+
+    let pad = |n: usize| padding.iter().cycle().take(n).flat_map(|(bytes, _)| bytes.iter().copied()).collect::<Vec<u8>>();
+    let mut padded = ByteString::from(pad(left));
+
+    padded.extend_from_slice(&subject);
+    padded.extend(pad(right));
+
+    Object::String(padded)
+}
+
+fn check_encoding(value: &Object, encoding: Encoding) -> bool {
+    match value {
+        Object::Array(items) => items.borrow().iter().all(|(key, value)| {
+            check_encoding(&key.to_object(), encoding) && check_encoding(value, encoding)
+        }),
+        value => encoding.characters(&convert::to_bytes(value)).iter().all(|(_, c)| c.is_some()),
+    }
+}
+
+pub fn mb_check_encoding(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    let encoding = encoding("mb_check_encoding", &args, 1);
+
+    Object::from_bool(args.first().is_some_and(|value| check_encoding(value, encoding)))
+}
+
+pub fn mb_convert_encoding(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 3);
+
+    let to = encoding("mb_convert_encoding", &args, 1);
+
+    // With several source encodings, the first one the string is valid in is used.
+    let candidates: Vec<String> = match args.get(2) {
+        None | Some(Object::Null) => vec!["UTF-8".into()],
+        Some(Object::Array(names)) => names.borrow().values().map(convert::to_string).collect(),
+        Some(names) => convert::to_string(names).split(',').map(|name| name.trim().to_string()).collect(),
+    };
+
+    let candidates: Vec<Encoding> = candidates.iter().map(|name| match Encoding::from_name(name) {
+        Some(encoding) => encoding,
+        None => error::throw("ValueError", format!("mb_convert_encoding(): Argument #3 ($from_encoding) contains invalid encoding \"{}\"", name)),
+    }).collect();
+
+    if candidates.is_empty() {
+        error::throw("ValueError", "mb_convert_encoding(): Argument #3 ($from_encoding) must specify at least one encoding");
+    }
+
+    let convert = |value: &Object| {
+        let bytes = convert::to_bytes(value);
+        let from = candidates.iter().copied().find(|from| from.characters(&bytes).iter().all(|(_, c)| c.is_some())).unwrap_or(candidates[0]);
+
+        Object::String(convert_encoding(&bytes, from, to))
+    };
+
+    match &args[0] {
+        Object::Array(items) => Object::from_array(items.borrow().iter().map(|(key, value)| (key.clone(), convert(value))).collect::<Array>()),
+        value => convert(value),
+    }
+}
+
+pub fn iconv(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 3);
+
+    let from_name = convert::to_string(&args[0]);
+    let to_name = convert::to_string(&args[1]);
+    let subject = convert::to_bytes(&args[2]);
+
+    // The target can end with "//TRANSLIT" or "//IGNORE" to say what happens to
+    // characters it can't represent.
+    let (target, suffix) = match to_name.find("//") {
+        Some(i) => (&to_name[..i], to_name[i..].to_ascii_uppercase()),
+        None => (to_name.as_str(), String::new()),
+    };
+
+    let (from, to) = match (Encoding::from_name(&from_name), Encoding::from_name(target)) {
+        (Some(from), Some(to)) => (from, to),
+        _ => {
+            error::warning(format!("iconv(): Wrong encoding, conversion from \"{}\" to \"{}\" is not allowed", from_name, to_name));
+            return Object::False;
+        },
+    };
+
+    let translit = suffix.contains("//TRANSLIT");
+    let ignore = suffix.contains("//IGNORE");
+    let mut converted = ByteString::new();
+
+    for (_, c) in from.characters(&subject) {
+        if c.is_some_and(|c| to.encode(c, &mut converted)) {
+            continue;
+        }
+
+        match (c, translit, ignore) {
+            (Some(_), true, _) => converted.push(SUBSTITUTE as u8),
+            (_, _, true) => (),
+            _ => {
+                error::notice("iconv(): Detected an illegal character in input string");
+                return Object::False;
+            },
+        }
+    }
+
+    Object::String(converted)
+}
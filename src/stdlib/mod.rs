@@ -1,12 +1,46 @@
 mod types;
 mod fs;
+mod stream;
+mod wrapper;
 mod misc;
 mod array;
+mod string;
+mod format;
+mod function;
+mod math;
+mod random;
+mod json;
+mod pcre;
+mod date;
+mod hash;
+mod url;
+mod html;
+mod var;
+mod mbstring;
+mod ctype;
+mod process;
 
 pub use types::*;
 pub use fs::*;
+pub use stream::*;
+pub use wrapper::*;
 pub use misc::*;
 pub use array::*;
+pub use string::*;
+pub use format::*;
+pub use function::*;
+pub use math::*;
+pub use random::*;
+pub use json::*;
+pub use pcre::*;
+pub use date::*;
+pub use hash::*;
+pub use url::*;
+pub use html::*;
+pub use var::*;
+pub use mbstring::*;
+pub use ctype::*;
+pub use process::*;
 
 macro_rules! arity {
     ($args:expr, $len:expr) => {
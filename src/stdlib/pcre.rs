@@ -0,0 +1,882 @@
+use super::{arity, callback};
+use crate::object::{Object, Array, ByteString, Key, convert};
+use crate::vm::Machine;
+use crate::error;
+use fancy_regex::{Captures, Regex, RegexBuilder};
+use std::collections::HashMap;
+use std::rc::Rc;
+
+pub const PREG_PATTERN_ORDER: i64 = 1;
+pub const PREG_SET_ORDER: i64 = 2;
+pub const PREG_OFFSET_CAPTURE: i64 = 256;
+pub const PREG_UNMATCHED_AS_NULL: i64 = 512;
+pub const PREG_SPLIT_NO_EMPTY: i64 = 1;
+pub const PREG_SPLIT_DELIM_CAPTURE: i64 = 2;
+pub const PREG_SPLIT_OFFSET_CAPTURE: i64 = 4;
+pub const PREG_GREP_INVERT: i64 = 1;
+
+pub const PREG_NO_ERROR: i64 = 0;
+pub const PREG_INTERNAL_ERROR: i64 = 1;
+pub const PREG_BACKTRACK_LIMIT_ERROR: i64 = 2;
+pub const PREG_RECURSION_LIMIT_ERROR: i64 = 3;
+pub const PREG_BAD_UTF8_ERROR: i64 = 4;
+pub const PREG_BAD_UTF8_OFFSET_ERROR: i64 = 5;
+pub const PREG_JIT_STACKLIMIT_ERROR: i64 = 6;
+
+/// The same default as PHP's `pcre.backtrack_limit` setting.
+const BACKTRACK_LIMIT: usize = 1_000_000;
+
+/// A compiled pattern, along with the names of its groups by index.
+pub struct Pattern {
+    regex: Regex,
+    names: Vec<Option<String>>,
+    utf8: bool,
+}
+
+/// The state behind the `preg_*` functions: compiled patterns, which are cached by
+/// their source the same way PHP does, and the error from the last call.
+#[derive(Default)]
+pub struct Pcre {
+    cache: HashMap<ByteString, Rc<Pattern>>,
+    last_error: i64,
+}
+
+/// Rewrites the parts of PCRE syntax that mean something different to the regex
+/// engine. PCRE's `$` (without the `m` or `D` modifiers) and `\Z` also match before a
+/// newline at the very end of the subject.
+///
+/// Without the `u` modifier the pattern works on bytes, which are read as the characters
+/// U+0000 to U+00FF. The character types and word boundaries are then ASCII-only, and
+/// other characters are matched without case folding.
+fn translate(pattern: &str, dollar_end_only: bool, bytes: bool) -> String {
+    let mut translated = String::with_capacity(pattern.len());
+    let mut chars = pattern.chars();
+    let mut class = false;
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some('Z') if ! class => translated.push_str("(?=\\n?\\z)"),
+                Some('h') => translated.push_str(if class { " \\t" } else { "[ \\t]" }),
+                Some(next @ ('d' | 'D' | 's' | 'S' | 'w' | 'W')) if bytes => {
+                    let name = match next.to_ascii_lowercase() {
+                        'd' => "digit",
+                        's' => "space",
+                        _ => "word",
+                    };
+
+                    let negated = if next.is_ascii_uppercase() { "^" } else { "" };
+
+                    translated.push_str(&match class {
+                        true => format!("[:{}{}:]", negated, name),
+                        false => format!("[{}[:{}:]]", negated, name),
+                    });
+                },
+                Some('b') if bytes && ! class => translated.push_str("(?:(?<=[[:word:]])(?![[:word:]])|(?<![[:word:]])(?=[[:word:]]))"),
+                Some('B') if bytes && ! class => translated.push_str("(?:(?<=[[:word:]])(?=[[:word:]])|(?<![[:word:]])(?![[:word:]]))"),
+                Some(next) => {
+                    translated.push('\\');
+                    translated.push(next);
+                },
+                None => translated.push('\\'),
+            },
+            '[' if ! class => {
+                class = true;
+                translated.push(c);
+
+                // A `]` straight after the opening bracket (or its negation) is a literal.
+                let mut rest = chars.clone();
+
+                match rest.next() {
+                    Some('^') if rest.next() == Some(']') => {
+                        translated.push_str("^\\]");
+                        chars.nth(1);
+                    },
+                    Some(']') => {
+                        translated.push_str("\\]");
+                        chars.next();
+                    },
+                    _ => (),
+                }
+            },
+            ']' if class => {
+                class = false;
+                translated.push(c);
+            },
+            '$' if ! class && ! dollar_end_only => translated.push_str("(?:$|(?=\\n\\z))"),
+            c if bytes && ! class && ! c.is_ascii() => translated.push_str(&format!("(?-i:\\x{{{:02x}}})", c as u32)),
+            c => translated.push(c),
+        }
+    }
+
+    translated
+}
+
+/// Splits a PHP pattern like `/abc/i` into the expression and its modifiers and
+/// compiles it, emitting the same warnings as PHP when it's malformed.
+fn compile(vm: &mut Machine, function: &str, source: &ByteString) -> Option<Rc<Pattern>> {
+    if let Some(pattern) = vm.pcre.cache.get(source) {
+        return Some(pattern.clone());
+    }
+
+    // The pattern is split up byte by byte, and only decoded as UTF-8 for the `u` modifier.
+    let text = latin1(source);
+    let trimmed = text.trim_start_matches(|c: char| c.is_ascii_whitespace());
+    let mut chars = trimmed.char_indices();
+
+    let delimiter = match chars.next() {
+        Some((_, c)) => c,
+        None => {
+            error::warning(format!("{}(): Empty regular expression", function));
+            return None;
+        },
+    };
+
+    if delimiter.is_ascii_alphanumeric() || delimiter == '\\' || delimiter == '\0' {
+        error::warning(format!("{}(): Delimiter must not be alphanumeric, backslash, or NUL", function));
+        return None;
+    }
+
+    let closing = match delimiter {
+        '(' => ')',
+        '[' => ']',
+        '{' => '}',
+        '<' => '>',
+        c => c,
+    };
+
+    let mut depth = 0;
+    let mut end = None;
+
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\\' => {
+                chars.next();
+            },
+            c if c == closing && depth == 0 => {
+                end = Some(i);
+                break;
+            },
+            c if c == closing => depth -= 1,
+            c if c == delimiter => depth += 1,
+            _ => (),
+        }
+    }
+
+    let end = match end {
+        Some(end) => end,
+        None if closing == delimiter => {
+            error::warning(format!("{}(): No ending delimiter '{}' found", function, delimiter));
+            return None;
+        },
+        None => {
+            error::warning(format!("{}(): No ending matching delimiter '{}' found", function, closing));
+            return None;
+        },
+    };
+
+    let expression = &trimmed[delimiter.len_utf8()..end];
+    let mut flags = String::new();
+    let mut dollar_end_only = false;
+    let mut anchored = false;
+    let mut utf8 = false;
+
+    for modifier in trimmed[end + closing.len_utf8()..].chars() {
+        match modifier {
+            'i' | 'm' | 's' | 'x' | 'U' => flags.push(modifier),
+            'u' => utf8 = true,
+            'D' => dollar_end_only = true,
+            'A' => anchored = true,
+            ' ' | '\n' | '\r' => (),
+            c => {
+                error::warning(format!("{}(): Unknown modifier '{}'", function, c));
+                return None;
+            },
+        }
+    }
+
+    let expression = match utf8 {
+        true => match String::from_utf8(expression.chars().map(|c| c as u8).collect()) {
+            Ok(expression) => expression,
+            Err(e) => {
+                error::warning(format!("{}(): Compilation failed: UTF-8 error: {} at offset {}", function, utf8_error(&e), e.utf8_error().valid_up_to()));
+                vm.pcre.last_error = PREG_INTERNAL_ERROR;
+                return None;
+            },
+        },
+        false => expression.to_string(),
+    };
+
+    let mut translated = translate(&expression, dollar_end_only || flags.contains('m'), ! utf8);
+
+    if anchored {
+        translated = format!("\\G(?:{})", translated);
+    }
+
+    if ! flags.is_empty() {
+        translated = format!("(?{}){}", flags, translated);
+    }
+
+    let regex = match RegexBuilder::new(&translated).backtrack_limit(BACKTRACK_LIMIT).build() {
+        Ok(regex) => regex,
+        Err(fancy_regex::Error::ParseError(offset, e)) => {
+            error::warning(format!("{}(): Compilation failed: {} at offset {}", function, e, offset));
+            vm.pcre.last_error = PREG_INTERNAL_ERROR;
+            return None;
+        },
+        Err(e) => {
+            error::warning(format!("{}(): Compilation failed: {}", function, e));
+            vm.pcre.last_error = PREG_INTERNAL_ERROR;
+            return None;
+        },
+    };
+
+    let names = regex.capture_names().map(|name| name.map(String::from)).collect();
+    let pattern = Rc::new(Pattern { regex, names, utf8 });
+
+    vm.pcre.cache.insert(source.clone(), pattern.clone());
+
+    Some(pattern)
+}
+
+/// Reads each byte as the character with the same value.
+fn latin1(bytes: &[u8]) -> String {
+    bytes.iter().map(|&b| b as char).collect()
+}
+
+/// The same descriptions PCRE gives for the most common kinds of invalid UTF-8.
+fn utf8_error(e: &std::string::FromUtf8Error) -> &'static str {
+    match e.as_bytes()[e.utf8_error().valid_up_to()] {
+        0x80..=0xbf => "isolated byte with 0x80 bit set",
+        0xfe | 0xff => "illegal byte (0xfe or 0xff)",
+        _ => match e.utf8_error().error_len() {
+            None => "1 byte missing at end",
+            Some(_) => "byte 2 top bits not 0x80",
+        },
+    }
+}
+
+/// A subject as the regex engine sees it. The engine only works on UTF-8 text, so
+/// without the `u` modifier each byte is read as a character from U+0000 to U+00FF,
+/// and offsets and matches are mapped back to bytes. With it, PHP rejects anything
+/// that isn't UTF-8.
+struct Subject {
+    text: String,
+    bytes: bool,
+}
+
+impl Subject {
+    fn new(vm: &mut Machine, pattern: &Pattern, subject: ByteString) -> Option<Subject> {
+        if ! pattern.utf8 {
+            return Some(Subject { text: latin1(&subject), bytes: ! subject.is_ascii() });
+        }
+
+        match String::from_utf8(subject.into_bytes()) {
+            Ok(text) => Some(Subject { text, bytes: false }),
+            Err(_) => {
+                vm.pcre.last_error = PREG_BAD_UTF8_ERROR;
+                None
+            },
+        }
+    }
+
+    /// The length of the subject in bytes.
+    fn len(&self) -> usize {
+        self.offset(self.text.len())
+    }
+
+    /// The byte offset in the subject of a position in the text.
+    fn offset(&self, position: usize) -> usize {
+        match self.bytes {
+            true => self.text[..position].chars().count(),
+            false => position,
+        }
+    }
+
+    /// The position in the text of a byte offset in the subject.
+    fn position(&self, offset: usize) -> usize {
+        match self.bytes {
+            true => self.text.char_indices().nth(offset).map_or(self.text.len(), |(i, _)| i),
+            false => offset,
+        }
+    }
+
+    /// The bytes between two positions in the text.
+    fn slice(&self, start: usize, end: usize) -> ByteString {
+        match self.bytes {
+            true => self.text[start..end].chars().map(|c| c as u8).collect(),
+            false => self.text[start..end].into(),
+        }
+    }
+}
+
+/// Every match of a pattern from `offset` onwards, with empty matches handled the way
+/// PCRE does (the search moves on by one character after one).
+fn matches<'t>(pattern: &Pattern, subject: &'t str, offset: usize, limit: Option<usize>) -> Result<Vec<Captures<'t>>, i64> {
+    let mut found = Vec::new();
+    let mut position = offset;
+
+    while position <= subject.len() && limit.is_none_or(|limit| found.len() < limit) {
+        let captures = match pattern.regex.captures_from_pos(subject, position) {
+            Ok(Some(captures)) => captures,
+            Ok(None) => break,
+            Err(_) => return Err(PREG_BACKTRACK_LIMIT_ERROR),
+        };
+
+        let whole = captures.get(0).unwrap();
+
+        position = match whole.end() == whole.start() {
+            true => whole.end() + subject[whole.end()..].chars().next().map_or(1, char::len_utf8),
+            false => whole.end(),
+        };
+
+        found.push(captures);
+    }
+
+    Ok(found)
+}
+
+/// Builds the array for one match. Groups that didn't take part become empty strings
+/// (or `null`), and trailing ones are left off entirely unless `fill` is set.
+fn match_array(pattern: &Pattern, subject: &Subject, captures: &Captures, flags: i64, fill: bool) -> Array {
+    let mut groups = Array::new();
+    let count = match fill || flags & PREG_UNMATCHED_AS_NULL != 0 {
+        true => pattern.names.len(),
+        false => (0..pattern.names.len()).rev().find(|i| captures.get(*i).is_some()).map_or(0, |i| i + 1),
+    };
+
+    for (i, name) in pattern.names.iter().enumerate().take(count) {
+        let group = captures.get(i);
+        let value = match group {
+            Some(group) => Object::String(subject.slice(group.start(), group.end())),
+            None if flags & PREG_UNMATCHED_AS_NULL != 0 => Object::Null,
+            None => Object::String(ByteString::new()),
+        };
+
+        let value = match flags & PREG_OFFSET_CAPTURE != 0 {
+            true => {
+                let offset = group.map_or(-1, |group| subject.offset(group.start()) as i64);
+
+                Object::from_array(vec![value, Object::Integer(offset)].into_iter().collect())
+            },
+            false => value,
+        };
+
+        if let Some(name) = name {
+            groups.insert(Key::String(name.as_str().into()), value.clone());
+        }
+
+        groups.insert(Key::Integer(i as i64), value);
+    }
+
+    groups
+}
+
+/// Works out where to start matching from, where negative offsets count from the end.
+fn start(vm: &mut Machine, subject: &Subject, offset: i64) -> Option<usize> {
+    let offset = match offset {
+        o if o < 0 => (subject.len() as i64 + o).max(0) as usize,
+        o => o as usize,
+    };
+
+    if offset > subject.len() {
+        vm.pcre.last_error = PREG_INTERNAL_ERROR;
+        return None;
+    }
+
+    let position = subject.position(offset);
+
+    if ! subject.text.is_char_boundary(position) {
+        vm.pcre.last_error = PREG_BAD_UTF8_OFFSET_ERROR;
+        return None;
+    }
+
+    Some(position)
+}
+
+pub fn preg_match(vm: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 5);
+
+    let source = convert::to_bytes(&args[0]);
+    let flags = args.get(3).map(convert::to_int).unwrap_or(0);
+    let offset = args.get(4).map(convert::to_int).unwrap_or(0);
+
+    vm.pcre.last_error = PREG_NO_ERROR;
+
+    let pattern = match compile(vm, "preg_match", &source) {
+        Some(pattern) => pattern,
+        None => return Object::False,
+    };
+
+    let subject = match Subject::new(vm, &pattern, convert::to_bytes(&args[1])) {
+        Some(subject) => subject,
+        None => {
+            vm.set_reference(2, Object::from_array(Array::new()));
+            return Object::False;
+        },
+    };
+
+    let offset = match start(vm, &subject, offset) {
+        Some(offset) => offset,
+        None => {
+            vm.set_reference(2, Object::from_array(Array::new()));
+            return Object::False;
+        },
+    };
+
+    match matches(&pattern, &subject.text, offset, Some(1)) {
+        Ok(found) => {
+            let groups = found.first().map(|captures| match_array(&pattern, &subject, captures, flags, false)).unwrap_or_default();
+
+            vm.set_reference(2, Object::from_array(groups));
+
+            Object::Integer(found.len() as i64)
+        },
+        Err(error) => {
+            vm.pcre.last_error = error;
+            vm.set_reference(2, Object::from_array(Array::new()));
+
+            Object::False
+        },
+    }
+}
+
+pub fn preg_match_all(vm: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 5);
+
+    let source = convert::to_bytes(&args[0]);
+    let mut flags = args.get(3).map(convert::to_int).unwrap_or(0);
+    let offset = args.get(4).map(convert::to_int).unwrap_or(0);
+
+    if flags & (PREG_PATTERN_ORDER | PREG_SET_ORDER) == PREG_PATTERN_ORDER | PREG_SET_ORDER {
+        error::throw("ValueError", "preg_match_all(): Argument #4 ($flags) must be a PREG_* constant");
+    }
+
+    if flags & PREG_SET_ORDER == 0 {
+        flags |= PREG_PATTERN_ORDER;
+    }
+
+    vm.pcre.last_error = PREG_NO_ERROR;
+
+    let pattern = match compile(vm, "preg_match_all", &source) {
+        Some(pattern) => pattern,
+        None => return Object::False,
+    };
+
+    let subject = match Subject::new(vm, &pattern, convert::to_bytes(&args[1])) {
+        Some(subject) => subject,
+        None => {
+            vm.set_reference(2, Object::from_array(Array::new()));
+            return Object::False;
+        },
+    };
+
+    let offset = match start(vm, &subject, offset) {
+        Some(offset) => offset,
+        None => {
+            vm.set_reference(2, Object::from_array(Array::new()));
+            return Object::False;
+        },
+    };
+
+    let found = match matches(&pattern, &subject.text, offset, None) {
+        Ok(found) => found,
+        Err(error) => {
+            vm.pcre.last_error = error;
+            vm.set_reference(2, Object::from_array(Array::new()));
+
+            return Object::False;
+        },
+    };
+
+    let result = match flags & PREG_SET_ORDER != 0 {
+        true => found.iter().map(|captures| Object::from_array(match_array(&pattern, &subject, captures, flags, false))).collect(),
+        false => {
+            // Each group gets a list of what it captured in every match.
+            let sets: Vec<Array> = found.iter().map(|captures| match_array(&pattern, &subject, captures, flags, true)).collect();
+            let mut result = Array::new();
+
+            for (i, name) in pattern.names.iter().enumerate() {
+                let column: Array = sets.iter().map(|set| set.get(&Key::Integer(i as i64)).cloned().unwrap()).collect();
+
+                if let Some(name) = name {
+                    result.insert(Key::String(name.as_str().into()), Object::from_array(column.clone()));
+                }
+
+                result.insert(Key::Integer(i as i64), Object::from_array(column));
+            }
+
+            result
+        },
+    };
+
+    vm.set_reference(2, Object::from_array(result));
+
+    Object::Integer(found.len() as i64)
+}
+
+/// Expands `$1`, `${1}` and `\1` references in a replacement, the same as
+/// `php_pcre_replace_impl()`. A backslash escapes a following `$` or backslash.
+fn expand(bytes: &[u8], subject: &Subject, captures: &Captures) -> ByteString {
+    let mut expanded = ByteString::new();
+    let mut last = 0u8;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let c = bytes[i];
+
+        if c == b'\\' || c == b'$' {
+            if last == b'\\' {
+                *expanded.last_mut().unwrap() = c;
+                last = 0;
+                i += 1;
+                continue;
+            }
+
+            let brace = c == b'$' && bytes.get(i + 1) == Some(&b'{');
+            let digits_start = i + 1 + brace as usize;
+            let digits = bytes[digits_start..].iter().take(2).take_while(|b| b.is_ascii_digit()).count();
+            let closed = ! brace || bytes.get(digits_start + digits) == Some(&b'}');
+
+            if digits > 0 && closed {
+                let group = bytes[digits_start..digits_start + digits].iter().fold(0, |group, digit| group * 10 + (digit - b'0') as usize);
+
+                if let Some(group) = captures.get(group) {
+                    expanded.extend_from_slice(&subject.slice(group.start(), group.end()));
+                }
+
+                i = digits_start + digits + brace as usize;
+                last = 0;
+                continue;
+            }
+        }
+
+        expanded.push(c);
+        last = c;
+        i += 1;
+    }
+
+    expanded
+}
+
+/// What each match is replaced with.
+enum Replacement {
+    Template(ByteString),
+    Callback(Object, i64),
+}
+
+/// Replaces matches of one pattern in one subject, returning `None` on failure.
+fn replace_one(vm: &mut Machine, function: &str, source: &ByteString, replacement: &Replacement, subject: ByteString, limit: Option<usize>, count: &mut i64) -> Option<ByteString> {
+    let pattern = compile(vm, function, source)?;
+    let subject = Subject::new(vm, &pattern, subject)?;
+
+    let found = match matches(&pattern, &subject.text, 0, limit) {
+        Ok(found) => found,
+        Err(error) => {
+            vm.pcre.last_error = error;
+            return None;
+        },
+    };
+
+    let mut result = ByteString::new();
+    let mut last = 0;
+
+    for captures in found.iter() {
+        let whole = captures.get(0).unwrap();
+
+        result.extend_from_slice(&subject.slice(last, whole.start()));
+
+        match replacement {
+            Replacement::Template(template) => result.extend_from_slice(&expand(template, &subject, captures)),
+            Replacement::Callback(callback, flags) => {
+                let groups = Object::from_array(match_array(&pattern, &subject, captures, *flags, false));
+                let value = vm.call(callback, vec![groups]);
+
+                result.extend_from_slice(&convert::to_bytes(&value));
+            },
+        }
+
+        last = whole.end();
+        *count += 1;
+    }
+
+    result.extend_from_slice(&subject.slice(last, subject.text.len()));
+
+    Some(result)
+}
+
+/// Runs every pattern over one subject in turn.
+fn replace_subject(vm: &mut Machine, function: &str, patterns: &[(ByteString, Replacement)], subject: &Object, limit: Option<usize>, count: &mut i64) -> Option<ByteString> {
+    let mut subject = convert::to_bytes(subject);
+
+    for (pattern, replacement) in patterns.iter() {
+        subject = replace_one(vm, function, pattern, replacement, subject, limit, count)?;
+    }
+
+    Some(subject)
+}
+
+fn replace(vm: &mut Machine, function: &str, patterns: Vec<(ByteString, Replacement)>, subject: &Object, limit: &Option<&Object>) -> (Object, i64) {
+    let limit = match limit.map(convert::to_int).unwrap_or(-1) {
+        limit if limit < 0 => None,
+        limit => Some(limit as usize),
+    };
+
+    let mut count = 0;
+
+    vm.pcre.last_error = PREG_NO_ERROR;
+
+    let result = match subject {
+        Object::Array(subjects) => {
+            let subjects = subjects.borrow().clone();
+            let mut results = Array::new();
+
+            // Subjects that fail are left out of the result.
+            for (key, subject) in subjects.iter() {
+                if let Some(replaced) = replace_subject(vm, function, &patterns, subject, limit, &mut count) {
+                    results.insert(key.clone(), Object::String(replaced));
+                }
+            }
+
+            Object::from_array(results)
+        },
+        subject => match replace_subject(vm, function, &patterns, subject, limit, &mut count) {
+            Some(replaced) => Object::String(replaced),
+            None => Object::Null,
+        },
+    };
+
+    (result, count)
+}
+
+pub fn preg_replace(vm: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 5);
+
+    let patterns = match (&args[0], &args[1]) {
+        (Object::Array(patterns), Object::Array(replacements)) => {
+            let mut replacements = replacements.borrow().values().cloned().collect::<Vec<_>>().into_iter();
+
+            patterns.borrow().values().map(|pattern| {
+                let replacement = replacements.next().map(|r| convert::to_bytes(&r)).unwrap_or_default();
+
+                (convert::to_bytes(pattern), Replacement::Template(replacement))
+            }).collect()
+        },
+        (Object::Array(patterns), replacement) => {
+            let replacement = convert::to_bytes(replacement);
+
+            patterns.borrow().values().map(|pattern| (convert::to_bytes(pattern), Replacement::Template(replacement.clone()))).collect()
+        },
+        (pattern, Object::Array(..)) => error::throw("TypeError", format!("preg_replace(): Argument #1 ($pattern) must be of type array when argument #2 ($replacement) is an array, {} given", pattern.type_name())),
+        (pattern, replacement) => vec![(convert::to_bytes(pattern), Replacement::Template(convert::to_bytes(replacement)))],
+    };
+
+    let (result, count) = replace(vm, "preg_replace", patterns, &args[2], &args.get(3));
+
+    vm.set_reference(4, Object::Integer(count));
+
+    result
+}
+
+pub fn preg_replace_callback(vm: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 6);
+
+    let callback = callback(vm, "preg_replace_callback", 2, &args[1], false).unwrap();
+    let flags = args.get(5).map(convert::to_int).unwrap_or(0);
+
+    let patterns = match &args[0] {
+        Object::Array(patterns) => patterns.borrow().values().map(|pattern| (convert::to_bytes(pattern), Replacement::Callback(callback.clone(), flags))).collect(),
+        pattern => vec![(convert::to_bytes(pattern), Replacement::Callback(callback, flags))],
+    };
+
+    let (result, count) = replace(vm, "preg_replace_callback", patterns, &args[2], &args.get(3));
+
+    vm.set_reference(4, Object::Integer(count));
+
+    result
+}
+
+pub fn preg_split(vm: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 4);
+
+    let source = convert::to_bytes(&args[0]);
+    let flags = args.get(3).map(convert::to_int).unwrap_or(0);
+    let no_empty = flags & PREG_SPLIT_NO_EMPTY != 0;
+
+    let mut limit = match args.get(2).map(convert::to_int).unwrap_or(-1) {
+        0 => -1,
+        limit => limit,
+    };
+
+    vm.pcre.last_error = PREG_NO_ERROR;
+
+    let pattern = match compile(vm, "preg_split", &source) {
+        Some(pattern) => pattern,
+        None => return Object::False,
+    };
+
+    let subject = match Subject::new(vm, &pattern, convert::to_bytes(&args[1])) {
+        Some(subject) => subject,
+        None => return Object::False,
+    };
+
+    let mut pieces = Array::new();
+    let mut add = |start: usize, end: usize| {
+        let piece = Object::String(subject.slice(start, end));
+
+        pieces.push(match flags & PREG_SPLIT_OFFSET_CAPTURE != 0 {
+            true => Object::from_array(vec![piece, Object::Integer(subject.offset(start) as i64)].into_iter().collect()),
+            false => piece,
+        });
+    };
+
+    let mut last = 0;
+    let mut position = 0;
+
+    while limit == -1 || limit > 1 {
+        let captures = match pattern.regex.captures_from_pos(&subject.text, position) {
+            Ok(Some(captures)) => captures,
+            Ok(None) => break,
+            Err(_) => {
+                vm.pcre.last_error = PREG_BACKTRACK_LIMIT_ERROR;
+                return Object::False;
+            },
+        };
+
+        let whole = captures.get(0).unwrap();
+
+        // An empty match right where the last piece started would only produce an
+        // empty piece, so the search moves on a character instead.
+        if whole.start() == whole.end() && whole.start() == last && (no_empty || whole.start() == 0 && subject.text.is_empty()) {
+            position = whole.end() + subject.text[whole.end()..].chars().next().map_or(1, char::len_utf8);
+
+            if position > subject.text.len() {
+                break;
+            }
+
+            continue;
+        }
+
+        if ! no_empty || whole.start() != last {
+            add(last, whole.start());
+
+            if limit != -1 {
+                limit -= 1;
+            }
+        }
+
+        if flags & PREG_SPLIT_DELIM_CAPTURE != 0 {
+            for i in 1..captures.len() {
+                if let Some(group) = captures.get(i) {
+                    if ! no_empty || ! group.as_str().is_empty() {
+                        add(group.start(), group.end());
+                    }
+                }
+            }
+        }
+
+        last = whole.end();
+        position = match whole.start() == whole.end() {
+            true => whole.end() + subject.text[whole.end()..].chars().next().map_or(1, char::len_utf8),
+            false => whole.end(),
+        };
+
+        if position > subject.text.len() {
+            break;
+        }
+    }
+
+    if ! no_empty || last < subject.text.len() {
+        add(last, subject.text.len());
+    }
+
+    Object::from_array(pieces)
+}
+
+pub fn preg_quote(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    let string = convert::to_bytes(&args[0]);
+    let delimiter = args.get(1).filter(|d| ! matches!(d, Object::Null)).map(convert::to_bytes).and_then(|d| d.first().copied());
+    let mut quoted = ByteString::new();
+
+    for &c in string.iter() {
+        match c {
+            b'.' | b'\\' | b'+' | b'*' | b'?' | b'[' | b'^' | b']' | b'$' | b'(' | b')' | b'{' | b'}' | b'=' | b'!' | b'<' | b'>' | b'|' | b':' | b'-' | b'#' => {
+                quoted.push(b'\\');
+                quoted.push(c);
+            },
+            b'\0' => quoted.push_str("\\000"),
+            c if Some(c) == delimiter => {
+                quoted.push(b'\\');
+                quoted.push(c);
+            },
+            c => quoted.push(c),
+        }
+    }
+
+    Object::String(quoted)
+}
+
+pub fn preg_grep(vm: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 3);
+
+    let source = convert::to_bytes(&args[0]);
+    let invert = args.get(2).map(convert::to_int).unwrap_or(0) & PREG_GREP_INVERT != 0;
+
+    let items = match &args[1] {
+        Object::Array(items) => items.borrow().clone(),
+        value => error::throw("TypeError", format!("preg_grep(): Argument #2 ($array) must be of type array, {} given", value.type_name())),
+    };
+
+    vm.pcre.last_error = PREG_NO_ERROR;
+
+    let pattern = match compile(vm, "preg_grep", &source) {
+        Some(pattern) => pattern,
+        None => return Object::False,
+    };
+
+    let mut result = Array::new();
+
+    for (key, value) in items.iter() {
+        let subject = match Subject::new(vm, &pattern, convert::to_bytes(value)) {
+            Some(subject) => subject,
+            None => break,
+        };
+
+        let matched = match pattern.regex.is_match(&subject.text) {
+            Ok(matched) => matched,
+            Err(_) => {
+                vm.pcre.last_error = PREG_BACKTRACK_LIMIT_ERROR;
+                break;
+            },
+        };
+
+        if matched != invert {
+            result.insert(key.clone(), value.clone());
+        }
+    }
+
+    Object::from_array(result)
+}
+
+pub fn preg_last_error(vm: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 0);
+
+    Object::Integer(vm.pcre.last_error)
+}
+
+pub fn preg_last_error_msg(vm: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 0);
+
+    Object::String(match vm.pcre.last_error {
+        PREG_NO_ERROR => "No error",
+        PREG_INTERNAL_ERROR => "Internal error",
+        PREG_BACKTRACK_LIMIT_ERROR => "Backtrack limit exhausted",
+        PREG_RECURSION_LIMIT_ERROR => "Recursion limit exhausted",
+        PREG_BAD_UTF8_ERROR => "Malformed UTF-8 characters, possibly incorrectly encoded",
+        PREG_BAD_UTF8_OFFSET_ERROR => "The offset did not correspond to the beginning of a valid UTF-8 code point",
+        PREG_JIT_STACKLIMIT_ERROR => "JIT stack limit exhausted",
+        _ => "Unknown error",
+    }.into())
+}
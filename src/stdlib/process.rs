@@ -0,0 +1,798 @@
+use super::{arity, Handle, Stream};
+use crate::object::{Object, Array, ByteString, Key, new_resource, convert};
+use crate::vm::Machine;
+use crate::error;
+use std::collections::HashMap;
+use std::ffi::{CStr, OsStr};
+use std::os::unix::ffi::OsStrExt;
+use std::fs::OpenOptions;
+use std::io::{self, Read, Write};
+use std::process::{Child, ChildStderr, ChildStdin, ChildStdout, Command, ExitStatus, Stdio};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+pub const PHP_VERSION: &str = "8.3.0";
+pub const PHP_MAJOR_VERSION: i64 = 8;
+pub const PHP_MINOR_VERSION: i64 = 3;
+pub const PHP_RELEASE_VERSION: i64 = 0;
+pub const PHP_VERSION_ID: i64 = 80300;
+pub const PHP_EOL: &str = "\n";
+
+/// The name PHP was built for, which is what `PHP_OS` holds.
+pub fn php_os() -> &'static str {
+    match std::env::consts::OS {
+        "linux" => "Linux",
+        "macos" => "Darwin",
+        "windows" => "WINNT",
+        "freebsd" => "FreeBSD",
+        "openbsd" => "OpenBSD",
+        "netbsd" => "NetBSD",
+        os => os,
+    }
+}
+
+pub fn php_os_family() -> &'static str {
+    match std::env::consts::OS {
+        "linux" => "Linux",
+        "macos" => "Darwin",
+        "windows" => "Windows",
+        "freebsd" | "openbsd" | "netbsd" | "dragonfly" => "BSD",
+        "solaris" | "illumos" => "Solaris",
+        _ => "Unknown",
+    }
+}
+
+impl Handle for ChildStdin {
+    fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+        Err(io::Error::from_raw_os_error(libc::EBADF))
+    }
+
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        self.write_all(bytes).map(|_| bytes.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Write::flush(self)
+    }
+}
+
+impl Handle for ChildStdout {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        Read::read(self, buffer)
+    }
+
+    fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+        Err(io::Error::from_raw_os_error(libc::EBADF))
+    }
+
+    fn greedy(&self) -> bool {
+        false
+    }
+}
+
+impl Handle for ChildStderr {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        Read::read(self, buffer)
+    }
+
+    fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+        Err(io::Error::from_raw_os_error(libc::EBADF))
+    }
+
+    fn greedy(&self) -> bool {
+        false
+    }
+}
+
+/// A process started with `proc_open()`, and the exit code once it's been collected.
+struct Process {
+    command: String,
+    child: Child,
+    status: Option<ExitStatus>,
+}
+
+/// The table of processes started with `proc_open()`, keyed by resource id.
+#[derive(Default)]
+pub struct Processes(HashMap<usize, Process>);
+
+/// The exit code PHP reports for a finished process, which is -1 if a signal ended it.
+fn exit_code(status: &ExitStatus) -> i64 {
+    status.code().map_or(-1, i64::from)
+}
+
+/// Commands given as a string are run by the shell, the same way PHP does.
+fn shell(command: &str) -> Command {
+    let mut shell = Command::new("/bin/sh");
+    shell.arg("-c").arg(command);
+    shell
+}
+
+fn command_argument(function: &str, value: &Object) -> String {
+    let command = convert::to_string(value);
+
+    if command.is_empty() {
+        error::throw("ValueError", format!("{}(): Argument #1 ($command) cannot be empty", function));
+    }
+
+    if command.contains('\0') {
+        error::throw("ValueError", format!("{}(): Argument #1 ($command) must not contain any null bytes", function));
+    }
+
+    command
+}
+
+/// Runs a command with its output piped back, leaving stderr attached to ours.
+/// Anything that's been echoed so far is flushed first so that it comes out in order.
+fn spawn(function: &str, command: &str) -> Option<Child> {
+    let _ = Write::flush(&mut io::stdout());
+
+    match shell(command).stdin(Stdio::inherit()).stdout(Stdio::piped()).stderr(Stdio::inherit()).spawn() {
+        Ok(child) => Some(child),
+        Err(_) => {
+            error::warning(format!("{}(): Unable to fork [{}]", function, command));
+            None
+        },
+    }
+}
+
+/// Strips trailing whitespace from a line of output, which is what `exec()` and
+/// `system()` return.
+fn trim_end(line: &[u8]) -> &[u8] {
+    let end = line.iter().rposition(|b| ! b.is_ascii_whitespace()).map_or(0, |i| i + 1);
+
+    &line[..end]
+}
+
+pub fn exec(vm: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 3);
+
+    let command = command_argument("exec", &args[0]);
+
+    let mut child = match spawn("exec", &command) {
+        Some(child) => child,
+        None => return Object::False,
+    };
+
+    let mut output = Vec::new();
+    let _ = child.stdout.take().unwrap().read_to_end(&mut output);
+    let status = child.wait().map(|status| exit_code(&status)).unwrap_or(-1);
+
+    // Lines are added to whatever is already in the array.
+    let mut lines = match args.get(1) {
+        Some(Object::Array(lines)) => lines.borrow().clone(),
+        _ => Array::new(),
+    };
+
+    // A trailing newline ends the last line rather than starting an empty one.
+    let output = output.strip_suffix(b"\n").unwrap_or(&output);
+    let mut last = ByteString::new();
+
+    if ! output.is_empty() {
+        for line in output.split(|b| *b == b'\n') {
+            last = trim_end(line).into();
+            lines.push(Object::String(last.clone()));
+        }
+    }
+
+    vm.set_reference(1, Object::from_array(lines));
+    vm.set_reference(2, Object::Integer(status));
+
+    Object::String(last)
+}
+
+pub fn shell_exec(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    let command = command_argument("shell_exec", &args[0]);
+
+    let mut child = match spawn("shell_exec", &command) {
+        Some(child) => child,
+        None => return Object::False,
+    };
+
+    let mut output = Vec::new();
+    let _ = child.stdout.take().unwrap().read_to_end(&mut output);
+    let _ = child.wait();
+
+    match output.is_empty() {
+        true => Object::Null,
+        false => Object::String(output.into()),
+    }
+}
+
+pub fn system(vm: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    let command = command_argument("system", &args[0]);
+
+    let mut child = match spawn("system", &command) {
+        Some(child) => child,
+        None => return Object::False,
+    };
+
+    // The output is passed through as it arrives, keeping hold of the last line.
+    let mut stdout = child.stdout.take().unwrap();
+    let mut chunk = [0; 8192];
+    let mut line = Vec::new();
+    let mut last = Vec::new();
+
+    while let Ok(read) = Read::read(&mut stdout, &mut chunk) {
+        if read == 0 {
+            break;
+        }
+
+        let _ = io::stdout().write_all(&chunk[..read]);
+        let _ = Write::flush(&mut io::stdout());
+
+        for b in &chunk[..read] {
+            match b {
+                b'\n' => last = std::mem::take(&mut line),
+                b => line.push(*b),
+            }
+        }
+    }
+
+    if ! line.is_empty() {
+        last = line;
+    }
+
+    let status = child.wait().map(|status| exit_code(&status)).unwrap_or(-1);
+
+    vm.set_reference(1, Object::Integer(status));
+
+    Object::String(trim_end(&last).into())
+}
+
+pub fn passthru(vm: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    let command = command_argument("passthru", &args[0]);
+
+    let _ = Write::flush(&mut io::stdout());
+
+    match shell(&command).status() {
+        Ok(status) => {
+            vm.set_reference(1, Object::Integer(exit_code(&status)));
+            Object::Null
+        },
+        Err(_) => {
+            error::warning(format!("passthru(): Unable to fork [{}]", command));
+            Object::False
+        },
+    }
+}
+
+pub fn escapeshellarg(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    let arg = convert::to_bytes(&args[0]);
+    let mut escaped = ByteString::from("'");
+
+    // A single quote can't appear inside single quotes, so it's closed, escaped and reopened.
+    for b in arg.iter() {
+        match b {
+            b'\'' => escaped.push_str("'\\''"),
+            b => escaped.push(*b),
+        }
+    }
+
+    escaped.push(b'\'');
+
+    Object::String(escaped)
+}
+
+pub fn escapeshellcmd(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    let command = convert::to_bytes(&args[0]);
+    let mut escaped = ByteString::new();
+    // The position of the quote that closes the one that's currently open.
+    let mut closing: Option<usize> = None;
+
+    for (i, b) in command.iter().enumerate() {
+        match b {
+            // Quotes are only escaped when they don't have a partner.
+            b'"' | b'\'' => {
+                if closing == Some(i) {
+                    closing = None;
+                } else if closing.is_none() {
+                    closing = command[i + 1..].iter().position(|c| c == b).map(|p| i + 1 + p);
+
+                    if closing.is_none() {
+                        escaped.push(b'\\');
+                    }
+                } else {
+                    escaped.push(b'\\');
+                }
+            },
+            b'#' | b'&' | b';' | b'`' | b'|' | b'*' | b'?' | b'~' | b'<' | b'>' | b'^' | b'(' | b')' |
+            b'[' | b']' | b'{' | b'}' | b'$' | b'\\' | b',' | b'\n' | b'\xff' => escaped.push(b'\\'),
+            _ => (),
+        }
+
+        escaped.push(*b);
+    }
+
+    Object::String(escaped)
+}
+
+/// Opens the file a `["file", path, mode]` descriptor refers to.
+fn open_file(path: &str, mode: &str) -> io::Result<std::fs::File> {
+    let mut options = OpenOptions::new();
+
+    match mode.trim_end_matches('b') {
+        "r" => options.read(true),
+        "r+" => options.read(true).write(true),
+        "w" => options.write(true).create(true).truncate(true),
+        "w+" => options.read(true).write(true).create(true).truncate(true),
+        "a" => options.append(true).create(true),
+        "a+" => options.read(true).append(true).create(true),
+        "x" => options.write(true).create_new(true),
+        "x+" => options.read(true).write(true).create_new(true),
+        _ => return Err(io::Error::from(io::ErrorKind::InvalidInput)),
+    };
+
+    options.open(path)
+}
+
+/// What a descriptor from `proc_open()`'s `$descriptor_spec` connects the child to,
+/// and whether the script gets a pipe for it.
+fn descriptor(spec: &Object) -> Result<(Stdio, bool), String> {
+    let spec = match spec {
+        Object::Array(spec) => spec.borrow().values().cloned().collect::<Vec<Object>>(),
+        _ => return Err("Descriptor item must be either an array or a File-Handle".into()),
+    };
+
+    let kind = spec.first().map(convert::to_string).unwrap_or_default();
+
+    match kind.as_str() {
+        "pipe" => Ok((Stdio::piped(), true)),
+        "file" => {
+            let path = spec.get(1).map(convert::to_string).ok_or("Missing file name parameter for 'file'")?;
+            let mode = spec.get(2).map(convert::to_string).ok_or("Missing mode parameter for 'file'")?;
+
+            match open_file(&path, &mode) {
+                Ok(file) => Ok((Stdio::from(file), false)),
+                Err(e) => Err(format!("Failed to open {}: {}", path, super::reason(&e))),
+            }
+        },
+        "null" => Ok((Stdio::null(), false)),
+        kind => Err(format!("{} is not a valid descriptor spec/mode", kind)),
+    }
+}
+
+pub fn proc_open(vm: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 6);
+
+    // An array is run directly, without going through the shell.
+    let (mut command, name) = match &args[0] {
+        Object::Array(parts) => {
+            let parts: Vec<String> = parts.borrow().values().map(convert::to_string).collect();
+
+            if parts.is_empty() {
+                error::throw("ValueError", "proc_open(): Argument #1 ($command) must have at least one element");
+            }
+
+            let mut command = Command::new(&parts[0]);
+            command.args(&parts[1..]);
+
+            (command, parts.join(" "))
+        },
+        value => {
+            let name = command_argument("proc_open", value);
+
+            (shell(&name), name)
+        },
+    };
+
+    let specs = match &args[1] {
+        Object::Array(specs) => specs.borrow().clone(),
+        value => error::throw("TypeError", format!("proc_open(): Argument #2 ($descriptor_spec) must be of type array, {} given", value.type_name())),
+    };
+
+    let mut piped = Vec::new();
+
+    for (key, spec) in specs.iter() {
+        let (stdio, pipe) = match descriptor(spec) {
+            Ok(descriptor) => descriptor,
+            Err(message) => {
+                error::warning(format!("proc_open(): {}", message));
+                return Object::False;
+            },
+        };
+
+        match key {
+            Key::Integer(0) => command.stdin(stdio),
+            Key::Integer(1) => command.stdout(stdio),
+            Key::Integer(2) => command.stderr(stdio),
+            _ => {
+                error::warning("proc_open(): Only descriptors 0, 1 and 2 are supported");
+                return Object::False;
+            },
+        };
+
+        if pipe {
+            piped.push(key.clone());
+        }
+    }
+
+    if let Some(cwd) = args.get(3).filter(|cwd| ! matches!(cwd, Object::Null)) {
+        command.current_dir(convert::to_string(cwd));
+    }
+
+    if let Some(Object::Array(env)) = args.get(4) {
+        command.env_clear();
+
+        for (key, value) in env.borrow().iter() {
+            let name = key.to_string();
+            let value = convert::to_bytes(value);
+
+            if name.contains('\0') || value.contains(&0) {
+                error::throw("ValueError", "proc_open(): Argument #5 ($env) must not contain any null bytes");
+            }
+
+            command.env(name, OsStr::from_bytes(&value));
+        }
+    }
+
+    let _ = Write::flush(&mut io::stdout());
+
+    let mut child = match command.spawn() {
+        Ok(child) => child,
+        Err(e) => {
+            error::warning(format!("proc_open(): Exec failed: {}", super::reason(&e)));
+            return Object::False;
+        },
+    };
+
+    let mut pipes = Array::new();
+
+    for key in piped {
+        let handle: Box<dyn Handle> = match key {
+            Key::Integer(0) => Box::new(child.stdin.take().unwrap()),
+            Key::Integer(1) => Box::new(child.stdout.take().unwrap()),
+            _ => Box::new(child.stderr.take().unwrap()),
+        };
+
+        pipes.insert(key, vm.streams.insert(Stream::new(handle)));
+    }
+
+    vm.set_reference(2, Object::from_array(pipes));
+
+    let resource = new_resource("process");
+
+    if let Object::Resource(r) = &resource {
+        vm.processes.0.insert(r.borrow().id, Process { command: name, child, status: None });
+    }
+
+    resource
+}
+
+fn process<'a>(vm: &'a mut Machine, function: &str, value: &Object) -> &'a mut Process {
+    let id = match value {
+        Object::Resource(resource) => resource.borrow().id,
+        value => error::throw("TypeError", format!("{}(): Argument #1 ($process) must be of type resource, {} given", function, value.type_name())),
+    };
+
+    match vm.processes.0.get_mut(&id) {
+        Some(process) => process,
+        None => error::throw("TypeError", format!("{}(): supplied resource is not a valid process resource", function)),
+    }
+}
+
+pub fn proc_close(vm: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    let process = process(vm, "proc_close", &args[0]);
+
+    let status = match process.status {
+        Some(status) => exit_code(&status),
+        None => process.child.wait().map(|status| exit_code(&status)).unwrap_or(-1),
+    };
+
+    if let Object::Resource(resource) = &args[0] {
+        let mut resource = resource.borrow_mut();
+
+        vm.processes.0.remove(&resource.id);
+        resource.kind = "Unknown".into();
+    }
+
+    Object::Integer(status)
+}
+
+pub fn proc_get_status(vm: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    let process = process(vm, "proc_get_status", &args[0]);
+
+    if process.status.is_none() {
+        process.status = process.child.try_wait().ok().flatten();
+    }
+
+    let signal = process.status.as_ref().and_then(std::os::unix::process::ExitStatusExt::signal);
+    let mut status = Array::new();
+
+    status.insert(Key::from("command"), Object::String(process.command.as_str().into()));
+    status.insert(Key::from("pid"), Object::Integer(process.child.id() as i64));
+    status.insert(Key::from("running"), Object::from_bool(process.status.is_none()));
+    status.insert(Key::from("signaled"), Object::from_bool(signal.is_some()));
+    status.insert(Key::from("stopped"), Object::False);
+    status.insert(Key::from("exitcode"), Object::Integer(process.status.as_ref().map_or(-1, exit_code)));
+    status.insert(Key::from("termsig"), Object::Integer(signal.unwrap_or(0) as i64));
+    status.insert(Key::from("stopsig"), Object::Integer(0));
+
+    Object::from_array(status)
+}
+
+/// Every environment variable, the same as `$_ENV`.
+pub fn environment() -> Object {
+    Object::from_array(std::env::vars_os().map(|(name, value)| {
+        (Key::from(name.as_encoded_bytes()), Object::String(value.as_encoded_bytes().into()))
+    }).collect())
+}
+
+/// The CLI's `$_SERVER`, which is the environment followed by details of the script
+/// and how it was run. The arguments start with the script's path.
+pub fn server(arguments: Array) -> Object {
+    let script = arguments.first().map(|(_, script)| script.clone()).unwrap_or_else(|| Object::String(ByteString::new()));
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+
+    let mut server = environment().to_hash().borrow().clone();
+
+    server.insert(Key::from("PHP_SELF"), script.clone());
+    server.insert(Key::from("SCRIPT_NAME"), script.clone());
+    server.insert(Key::from("SCRIPT_FILENAME"), script.clone());
+    server.insert(Key::from("PATH_TRANSLATED"), script);
+    server.insert(Key::from("DOCUMENT_ROOT"), Object::String(ByteString::new()));
+    server.insert(Key::from("REQUEST_TIME_FLOAT"), Object::Float(now.as_secs_f64()));
+    server.insert(Key::from("REQUEST_TIME"), Object::Integer(now.as_secs() as i64));
+    server.insert(Key::from("argc"), Object::Integer(arguments.len() as i64));
+    server.insert(Key::from("argv"), Object::from_array(arguments));
+
+    Object::from_array(server)
+}
+
+/// Whether an option given to `getopt()` takes a value.
+#[derive(Clone, Copy, PartialEq)]
+enum Value {
+    None,
+    Required,
+    Optional,
+}
+
+/// Splits an option's name from the colons that say whether it takes a value.
+fn option(spec: &[u8]) -> (&[u8], Value) {
+    if let Some(name) = spec.strip_suffix(b"::") {
+        (name, Value::Optional)
+    } else if let Some(name) = spec.strip_suffix(b":") {
+        (name, Value::Required)
+    } else {
+        (spec, Value::None)
+    }
+}
+
+/// Adds an option to `getopt()`'s result. Options given more than once collect their
+/// values in an array.
+fn add_option(options: &mut Array, name: &[u8], value: Object) {
+    let key = Key::from_object(&Object::String(name.into()));
+
+    match options.get(&key) {
+        Some(Object::Array(values)) => values.borrow_mut().push(value),
+        Some(previous) => {
+            let values = vec![previous.clone(), value].into_iter().collect();
+            options.insert(key, Object::from_array(values));
+        },
+        None => options.insert(key, value),
+    }
+}
+
+pub fn getopt(vm: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 3);
+
+    let short = convert::to_bytes(&args[0]);
+    let mut shorts = HashMap::new();
+    let mut i = 0;
+
+    while i < short.len() {
+        let colons = short[i + 1..].iter().take(2).take_while(|c| **c == b':').count();
+        shorts.insert(short[i], option(&short[i..i + 1 + colons]).1);
+        i += 1 + colons;
+    }
+
+    let longs: Vec<ByteString> = match args.get(1) {
+        Some(Object::Array(longs)) => longs.borrow().values().map(convert::to_bytes).collect(),
+        _ => Vec::new(),
+    };
+
+    let longs: HashMap<&[u8], Value> = longs.iter().map(|spec| option(spec)).collect();
+
+    // Like PHP, the arguments are read from `$_SERVER['argv']`.
+    let argv: Vec<ByteString> = match vm.get_variable("$_SERVER").map(|server| server.to_hash().borrow().get(&Key::from("argv")).cloned()) {
+        Some(Some(Object::Array(argv))) => argv.borrow().values().map(convert::to_bytes).collect(),
+        _ => Vec::new(),
+    };
+
+    let mut options = Array::new();
+    let mut index = 1;
+
+    // Parsing stops at the first argument that isn't an option, or just after `--`.
+    while index < argv.len() {
+        let argument = &argv[index][..];
+
+        if argument == b"--" {
+            index += 1;
+            break;
+        }
+
+        if argument.len() < 2 || argument[0] != b'-' {
+            break;
+        }
+
+        if let Some(long) = argument.strip_prefix(b"--") {
+            let (name, value) = match long.iter().position(|c| *c == b'=') {
+                Some(equals) => (&long[..equals], Some(&long[equals + 1..])),
+                None => (long, None),
+            };
+
+            // Unknown options, and values that are missing or not allowed, are skipped.
+            match (longs.get(name), value) {
+                (Some(Value::None), None) => add_option(&mut options, name, Object::False),
+                (Some(Value::Required), Some(value)) => add_option(&mut options, name, Object::String(value.into())),
+                (Some(Value::Required), None) if index + 1 < argv.len() => {
+                    index += 1;
+                    add_option(&mut options, name, Object::String(argv[index].clone()));
+                },
+                (Some(Value::Optional), value) => add_option(&mut options, name, value.map_or(Object::False, |value| Object::String(value.into()))),
+                _ => (),
+            }
+
+            index += 1;
+            continue;
+        }
+
+        // Short options can be grouped together, as in `-abc`, until one takes a value.
+        for (position, c) in argument.iter().enumerate().skip(1) {
+            let rest = &argument[position + 1..];
+            let rest = rest.strip_prefix(b"=").unwrap_or(rest);
+
+            match shorts.get(c) {
+                Some(Value::None) => add_option(&mut options, &[*c], Object::False),
+                Some(Value::Required) => {
+                    if ! rest.is_empty() {
+                        add_option(&mut options, &[*c], Object::String(rest.into()));
+                    } else if index + 1 < argv.len() {
+                        index += 1;
+                        add_option(&mut options, &[*c], Object::String(argv[index].clone()));
+                    }
+
+                    break;
+                },
+                Some(Value::Optional) => {
+                    add_option(&mut options, &[*c], match rest.is_empty() {
+                        true => Object::False,
+                        false => Object::String(rest.into()),
+                    });
+
+                    break;
+                },
+                None => (),
+            }
+        }
+
+        index += 1;
+    }
+
+    vm.set_reference(2, Object::Integer(index as i64));
+
+    Object::from_array(options)
+}
+
+pub fn getenv(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    let name = match args.first() {
+        None | Some(Object::Null) => return environment(),
+        Some(name) => convert::to_string(name),
+    };
+
+    match std::env::var_os(name) {
+        Some(value) => Object::String(value.as_encoded_bytes().into()),
+        None => Object::False,
+    }
+}
+
+pub fn putenv(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    let assignment = convert::to_bytes(&args[0]);
+
+    // The environment is made of C strings, so a null byte can't be stored.
+    if assignment.is_empty() || assignment.starts_with(b"=") || assignment.contains(&0) {
+        error::throw("ValueError", "putenv(): Argument #1 ($assignment) must have a valid syntax");
+    }
+
+    // "NAME=value" sets a variable and a plain "NAME" removes it.
+    match assignment.iter().position(|b| *b == b'=') {
+        Some(equals) => std::env::set_var(OsStr::from_bytes(&assignment[..equals]), OsStr::from_bytes(&assignment[equals + 1..])),
+        None => std::env::remove_var(OsStr::from_bytes(&assignment)),
+    }
+
+    Object::True
+}
+
+pub fn getmypid(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 0);
+
+    Object::Integer(std::process::id() as i64)
+}
+
+/// The fields of `uname()`: the system, node name, release, version and machine.
+fn uname() -> [String; 5] {
+    let mut name: libc::utsname = unsafe { std::mem::zeroed() };
+
+    unsafe {
+        libc::uname(&mut name);
+    }
+
+    let field = |field: &[libc::c_char]| unsafe { CStr::from_ptr(field.as_ptr()) }.to_string_lossy().into_owned();
+
+    [field(&name.sysname), field(&name.nodename), field(&name.release), field(&name.version), field(&name.machine)]
+}
+
+pub fn php_uname(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    let mode = args.first().map(convert::to_string).unwrap_or_else(|| "a".into());
+    let [system, node, release, version, machine] = uname();
+
+    Object::String(match mode.as_str() {
+        "a" => format!("{} {} {} {} {}", system, node, release, version, machine),
+        "s" => system,
+        "n" => node,
+        "r" => release,
+        "v" => version,
+        "m" => machine,
+        _ => error::throw("ValueError", "php_uname(): Argument #1 ($mode) must be a single character, and one of \"a\", \"m\", \"n\", \"r\", \"s\", or \"v\""),
+    }.into())
+}
+
+pub fn php_sapi_name(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 0);
+
+    Object::String("cli".into())
+}
+
+pub fn gethostname(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 0);
+
+    let [_, node, ..] = uname();
+
+    Object::String(node.into())
+}
+
+pub fn sleep(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    let seconds = convert::to_int(&args[0]);
+
+    if seconds < 0 {
+        error::throw("ValueError", "sleep(): Argument #1 ($seconds) must be greater than or equal to 0");
+    }
+
+    std::thread::sleep(Duration::from_secs(seconds as u64));
+
+    Object::Integer(0)
+}
+
+pub fn usleep(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    let microseconds = convert::to_int(&args[0]);
+
+    if microseconds < 0 {
+        error::throw("ValueError", "usleep(): Argument #1 ($microseconds) must be greater than or equal to 0");
+    }
+
+    std::thread::sleep(Duration::from_micros(microseconds as u64));
+
+    Object::Null
+}
@@ -0,0 +1,413 @@
+use super::arity;
+use crate::object::{Object, Array, Key, convert};
+use crate::vm::Machine;
+use crate::error;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub const MT_RAND_MT19937: i64 = 0;
+pub const MT_RAND_PHP: i64 = 1;
+
+const MT_RAND_MAX: i64 = 0x7FFF_FFFF;
+
+const N: usize = 624;
+const M: usize = 397;
+
+/// The Mersenne Twister behind `mt_rand()`, ported from PHP so that seeded sequences are
+/// identical. `MT_RAND_PHP` mode reproduces the incorrect twist PHP used before 7.1.
+pub struct Mt19937 {
+    state: [u32; N],
+    count: usize,
+    mode: i64,
+}
+
+impl Mt19937 {
+    pub fn new(seed: u32, mode: i64) -> Self {
+        let mut state = [0u32; N];
+        state[0] = seed;
+
+        for i in 1..N {
+            state[i] = 1_812_433_253u32
+                .wrapping_mul(state[i - 1] ^ (state[i - 1] >> 30))
+                .wrapping_add(i as u32);
+        }
+
+        let mut mt = Self { state, count: N, mode };
+        mt.reload();
+        mt
+    }
+
+    fn twist(&self, m: u32, u: u32, v: u32) -> u32 {
+        let mixed = (u & 0x8000_0000) | (v & 0x7FFF_FFFF);
+        let bit = if self.mode == MT_RAND_PHP { u & 1 } else { v & 1 };
+
+        m ^ (mixed >> 1) ^ ((bit as i32).wrapping_neg() as u32 & 0x9908_B0DF)
+    }
+
+    fn reload(&mut self) {
+        for i in 0..N {
+            let next = if i + 1 < N { self.state[i + 1] } else { self.state[0] };
+
+            self.state[i] = self.twist(self.state[(i + M) % N], self.state[i], next);
+        }
+
+        self.count = 0;
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        if self.count >= N {
+            self.reload();
+        }
+
+        let mut s = self.state[self.count];
+        self.count += 1;
+
+        s ^= s >> 11;
+        s ^= (s << 7) & 0x9D2C_5680;
+        s ^= (s << 15) & 0xEFC6_0000;
+        s ^ (s >> 18)
+    }
+
+    fn range32(&mut self, umax: u32) -> u32 {
+        let mut result = self.next_u32();
+
+        if umax == u32::MAX {
+            return result;
+        }
+
+        let umax = umax + 1;
+
+        if umax & (umax - 1) == 0 {
+            return result & (umax - 1);
+        }
+
+        let limit = u32::MAX - (u32::MAX % umax) - 1;
+
+        while result > limit {
+            result = self.next_u32();
+        }
+
+        result % umax
+    }
+
+    fn range64(&mut self, umax: u64) -> u64 {
+        let mut next = || self.next_u32() as u64 | (self.next_u32() as u64) << 32;
+        let mut result = next();
+
+        if umax == u64::MAX {
+            return result;
+        }
+
+        let umax = umax + 1;
+
+        if umax & (umax - 1) == 0 {
+            return result & (umax - 1);
+        }
+
+        let limit = u64::MAX - (u64::MAX % umax) - 1;
+
+        while result > limit {
+            result = next();
+        }
+
+        result % umax
+    }
+
+    /// A uniformly distributed integer between `min` and `max` inclusive.
+    pub fn range(&mut self, min: i64, max: i64) -> i64 {
+        let umax = (max as u64).wrapping_sub(min as u64);
+
+        match umax > u32::MAX as u64 {
+            true => self.range64(umax).wrapping_add(min as u64) as i64,
+            false => (self.range32(umax as u32) as u64).wrapping_add(min as u64) as i64,
+        }
+    }
+}
+
+/// The state of the random number generators, which is kept on the `Machine`.
+#[derive(Default)]
+pub struct Random {
+    mt: Option<Mt19937>,
+    lcg: Option<(i64, i64)>,
+}
+
+impl Random {
+    /// The shared Mersenne Twister, which is seeded randomly the first time it's used.
+    pub fn mt(&mut self) -> &mut Mt19937 {
+        self.mt.get_or_insert_with(|| Mt19937::new(secure_u64() as u32, MT_RAND_MT19937))
+    }
+
+    /// PHP's combined linear congruential generator, used by `lcg_value()`.
+    fn lcg(&mut self) -> f64 {
+        let (s1, s2) = self.lcg.get_or_insert_with(|| {
+            let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+            let later = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+
+            (
+                (now.as_secs() as i64 ^ ((now.subsec_micros() as i64) << 11)) & 0x7FFF_FFFF,
+                (std::process::id() as i64 ^ ((later.subsec_micros() as i64) << 11)) & 0x7FFF_FFFF,
+            )
+        });
+
+        let modmult = |s: &mut i64, a: i64, b: i64, c: i64, m: i64| {
+            let q = *s / a;
+            *s = b * (*s - a * q) - c * q;
+
+            if *s < 0 {
+                *s += m;
+            }
+        };
+
+        modmult(s1, 53668, 40014, 12211, 2_147_483_563);
+        modmult(s2, 52774, 40692, 3791, 2_147_483_399);
+
+        let mut z = *s1 - *s2;
+
+        if z < 1 {
+            z += 2_147_483_562;
+        }
+
+        z as f64 * 4.656613e-10
+    }
+}
+
+fn secure_bytes(buffer: &mut [u8]) {
+    if let Err(e) = getrandom::getrandom(buffer) {
+        error::throw("Random\\RandomException", format!("Cannot open source device ({})", e));
+    }
+}
+
+fn secure_u64() -> u64 {
+    let mut buffer = [0u8; 8];
+    secure_bytes(&mut buffer);
+
+    u64::from_le_bytes(buffer)
+}
+
+/// A uniformly distributed integer from the operating system's CSPRNG.
+fn secure_range(min: i64, max: i64) -> i64 {
+    let umax = (max as u64).wrapping_sub(min as u64);
+
+    if umax == u64::MAX {
+        return secure_u64() as i64;
+    }
+
+    let range = umax + 1;
+    let limit = u64::MAX - (u64::MAX % range) - 1;
+    let mut result = secure_u64();
+
+    while result > limit {
+        result = secure_u64();
+    }
+
+    (result % range).wrapping_add(min as u64) as i64
+}
+
+fn mt_range(vm: &mut Machine, min: i64, max: i64) -> i64 {
+    let mt = vm.random.mt();
+
+    if mt.mode == MT_RAND_PHP {
+        // Legacy mode scales the value instead of using a uniform range.
+        let n = (mt.next_u32() >> 1) as f64;
+
+        return min + ((max as f64 - min as f64 + 1.0) * (n / (MT_RAND_MAX as f64 + 1.0))) as i64;
+    }
+
+    mt.range(min, max)
+}
+
+pub fn mt_srand(vm: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    let seed = match args.first() {
+        None | Some(Object::Null) => secure_u64() as u32,
+        Some(seed) => convert::to_int(seed) as u32,
+    };
+    let mode = args.get(1).map(convert::to_int).unwrap_or(MT_RAND_MT19937);
+
+    vm.random.mt = Some(Mt19937::new(seed, if mode == MT_RAND_PHP { MT_RAND_PHP } else { MT_RAND_MT19937 }));
+
+    Object::Null
+}
+
+pub fn srand(vm: &mut Machine, args: Vec<Object>) -> Object {
+    mt_srand(vm, args)
+}
+
+pub fn mt_rand(vm: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    match args.len() {
+        0 => Object::Integer((vm.random.mt().next_u32() >> 1) as i64),
+        1 => error::throw("ArgumentCountError", "mt_rand() expects exactly 2 arguments, 1 given"),
+        _ => {
+            let (min, max) = (convert::to_int(&args[0]), convert::to_int(&args[1]));
+
+            if max < min {
+                error::throw("ValueError", "mt_rand(): Argument #2 ($max) must be greater than or equal to argument #1 ($min)");
+            }
+
+            Object::Integer(mt_range(vm, min, max))
+        },
+    }
+}
+
+pub fn rand(vm: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    match args.len() {
+        0 => Object::Integer((vm.random.mt().next_u32() >> 1) as i64),
+        1 => error::throw("ArgumentCountError", "rand() expects exactly 2 arguments, 1 given"),
+        _ => {
+            let (min, max) = (convert::to_int(&args[0]), convert::to_int(&args[1]));
+
+            // Unlike `mt_rand()`, the bounds can be given in either order.
+            Object::Integer(if max < min { mt_range(vm, max, min) } else { mt_range(vm, min, max) })
+        },
+    }
+}
+
+pub fn mt_getrandmax(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 0);
+
+    Object::Integer(MT_RAND_MAX)
+}
+
+pub fn getrandmax(vm: &mut Machine, args: Vec<Object>) -> Object {
+    mt_getrandmax(vm, args)
+}
+
+pub fn random_int(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    let (min, max) = (convert::to_int(&args[0]), convert::to_int(&args[1]));
+
+    if min > max {
+        error::throw("ValueError", "random_int(): Argument #1 ($min) must be less than or equal to argument #2 ($max)");
+    }
+
+    Object::Integer(secure_range(min, max))
+}
+
+pub fn random_bytes(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    let length = convert::to_int(&args[0]);
+
+    if length < 1 {
+        error::throw("ValueError", "random_bytes(): Argument #1 ($length) must be greater than 0");
+    }
+
+    let mut buffer = vec![0u8; length as usize];
+    secure_bytes(&mut buffer);
+
+    Object::String(buffer.into())
+}
+
+pub fn lcg_value(vm: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 0);
+
+    Object::Float(vm.random.lcg())
+}
+
+pub fn uniqid(vm: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    let prefix = args.first().map(convert::to_string).unwrap_or_default();
+
+    // PHP waits for the clock to move on so that two calls never return the same id.
+    let start = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+    let mut now = start;
+
+    while now.as_micros() == start.as_micros() {
+        now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+    }
+
+    let id = format!("{}{:08x}{:05x}", prefix, now.as_secs(), now.subsec_micros());
+
+    match args.get(1).is_some_and(Object::to_bool) {
+        true => Object::String(format!("{}{:.8}", id, vm.random.lcg() * 10.0).into()),
+        false => Object::String(id.into()),
+    }
+}
+
+/// The Fisher-Yates shuffle used by `shuffle()` and `str_shuffle()`, drawing from `mt_rand()`.
+fn shuffle_in_place<T>(vm: &mut Machine, items: &mut [T]) {
+    let mut left = items.len();
+
+    while left > 1 {
+        left -= 1;
+
+        let index = vm.random.mt().range(0, left as i64) as usize;
+
+        if index != left {
+            items.swap(left, index);
+        }
+    }
+}
+
+pub fn shuffle(vm: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    let mut values: Vec<Object> = match &args[0] {
+        Object::Array(array) => array.borrow().values().cloned().collect(),
+        value => error::throw("TypeError", format!("shuffle(): Argument #1 ($array) must be of type array, {} given", value.type_name())),
+    };
+
+    shuffle_in_place(vm, &mut values);
+    vm.set_reference(0, Object::from_array(values.into_iter().collect()));
+
+    Object::True
+}
+
+pub fn str_shuffle(vm: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    let mut bytes = convert::to_bytes(&args[0]).into_bytes();
+
+    shuffle_in_place(vm, &mut bytes);
+
+    Object::String(bytes.into())
+}
+
+pub fn array_rand(vm: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    let keys: Vec<Key> = match &args[0] {
+        Object::Array(array) => array.borrow().keys().cloned().collect(),
+        value => error::throw("TypeError", format!("array_rand(): Argument #1 ($array) must be of type array, {} given", value.type_name())),
+    };
+    let wanted = args.get(1).map(convert::to_int).unwrap_or(1);
+
+    if keys.is_empty() {
+        error::throw("ValueError", "array_rand(): Argument #1 ($array) cannot be empty");
+    }
+
+    if wanted == 1 {
+        let index = vm.random.mt().range(0, keys.len() as i64 - 1) as usize;
+
+        return keys[index].to_object();
+    }
+
+    if wanted <= 0 || wanted > keys.len() as i64 {
+        error::throw("ValueError", "array_rand(): Argument #2 ($num) must be between 1 and the number of elements in argument #1 ($array)");
+    }
+
+    // When more than half of the keys are wanted, it's quicker to pick the ones to leave out.
+    let negative = wanted as usize > keys.len() / 2;
+    let mut remaining = if negative { keys.len() - wanted as usize } else { wanted as usize };
+    let mut selected = vec![false; keys.len()];
+
+    while remaining > 0 {
+        let index = vm.random.mt().range(0, keys.len() as i64 - 1) as usize;
+
+        if ! selected[index] {
+            selected[index] = true;
+            remaining -= 1;
+        }
+    }
+
+    Object::from_array(keys.iter().zip(selected)
+        .filter(|(_, selected)| *selected != negative)
+        .map(|(key, _)| key.to_object())
+        .collect::<Array>())
+}
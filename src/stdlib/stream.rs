@@ -0,0 +1,750 @@
+use super::{arity, reason, scheme, FileWrapper, Wrapper};
+use crate::object::{Object, Array, Resource, new_resource, convert};
+use crate::vm::Machine;
+use crate::error;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs::{File, TryLockError};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::rc::Rc;
+
+pub const SEEK_SET: i64 = 0;
+pub const SEEK_CUR: i64 = 1;
+pub const SEEK_END: i64 = 2;
+
+pub const LOCK_SH: i64 = 1;
+pub const LOCK_EX: i64 = 2;
+pub const LOCK_UN: i64 = 3;
+pub const LOCK_NB: i64 = 4;
+
+// The resource ids of the standard streams, which are opened before the script runs.
+pub const STDIN: usize = 1;
+pub const STDOUT: usize = 2;
+pub const STDERR: usize = 3;
+
+/// How much is read from a stream at a time when filling its read buffer.
+const CHUNK_SIZE: usize = 8192;
+
+fn unsupported(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::Unsupported, message)
+}
+
+/// The file, pipe or buffer behind a stream resource.
+pub trait Handle {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize>;
+
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize>;
+
+    fn seek(&mut self, _: SeekFrom) -> io::Result<u64> {
+        Err(unsupported("stream does not support seeking"))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn truncate(&mut self, _: u64) -> io::Result<()> {
+        Err(unsupported("stream does not support truncation"))
+    }
+
+    /// The next entry from a directory stream, or `None` once they've all been read.
+    fn read_entry(&mut self) -> io::Result<Option<String>> {
+        Err(unsupported("not a directory stream"))
+    }
+
+    /// Applies one of the `LOCK_*` operations, returning `false` if a non-blocking
+    /// lock couldn't be taken straight away.
+    fn lock(&mut self, _: i64) -> io::Result<bool> {
+        Err(unsupported("stream does not support locking"))
+    }
+
+    /// Whether a read should keep going until it has everything that was asked for.
+    /// Interactive streams return whatever is available instead.
+    fn greedy(&self) -> bool {
+        true
+    }
+}
+
+impl Handle for File {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        Read::read(self, buffer)
+    }
+
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        Write::write_all(self, bytes).map(|_| bytes.len())
+    }
+
+    fn seek(&mut self, position: SeekFrom) -> io::Result<u64> {
+        Seek::seek(self, position)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Write::flush(self)
+    }
+
+    fn truncate(&mut self, size: u64) -> io::Result<()> {
+        self.set_len(size)
+    }
+
+    fn lock(&mut self, operation: i64) -> io::Result<bool> {
+        let blocking = operation & LOCK_NB == 0;
+
+        let result = match (operation & 3, blocking) {
+            (LOCK_UN, _) => return self.unlock().map(|_| true),
+            (LOCK_SH, true) => return self.lock_shared().map(|_| true),
+            (LOCK_EX, true) => return File::lock(self).map(|_| true),
+            (LOCK_SH, false) => self.try_lock_shared(),
+            (_, false) => self.try_lock(),
+            _ => return Err(unsupported("invalid lock operation")),
+        };
+
+        match result {
+            Ok(_) => Ok(true),
+            Err(TryLockError::WouldBlock) => Ok(false),
+            Err(TryLockError::Error(e)) => Err(e),
+        }
+    }
+}
+
+fn bad_descriptor() -> io::Error {
+    io::Error::from_raw_os_error(9)
+}
+
+impl Handle for io::Stdin {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        Read::read(self, buffer)
+    }
+
+    fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+        Err(bad_descriptor())
+    }
+
+    fn greedy(&self) -> bool {
+        false
+    }
+}
+
+impl Handle for io::Stdout {
+    fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+        Err(bad_descriptor())
+    }
+
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        self.write_all(bytes).map(|_| bytes.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Write::flush(self)
+    }
+}
+
+impl Handle for io::Stderr {
+    fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+        Err(bad_descriptor())
+    }
+
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        self.write_all(bytes).map(|_| bytes.len())
+    }
+}
+
+/// An open stream. Reads go through a buffer so that `fgets()` can stop at a newline
+/// without losing whatever was read past it.
+pub struct Stream {
+    handle: Box<dyn Handle>,
+    buffer: Vec<u8>,
+    position: u64,
+    eof: bool,
+}
+
+impl Stream {
+    pub fn new(handle: Box<dyn Handle>) -> Self {
+        Self { handle, buffer: Vec::new(), position: 0, eof: false }
+    }
+
+    /// Reads another chunk into the buffer, returning how many bytes were added.
+    fn fill(&mut self) -> io::Result<usize> {
+        let mut chunk = [0; CHUNK_SIZE];
+        let read = self.handle.read(&mut chunk)?;
+
+        if read == 0 {
+            self.eof = true;
+        }
+
+        self.buffer.extend_from_slice(&chunk[..read]);
+
+        Ok(read)
+    }
+
+    fn take(&mut self, length: usize) -> Vec<u8> {
+        let length = length.min(self.buffer.len());
+        self.position += length as u64;
+
+        self.buffer.drain(..length).collect()
+    }
+
+    pub fn read(&mut self, length: usize) -> io::Result<Vec<u8>> {
+        let mut bytes = self.take(length);
+
+        while bytes.len() < length && ! self.eof {
+            let mut chunk = vec![0; length - bytes.len()];
+            let read = self.handle.read(&mut chunk)?;
+
+            if read == 0 {
+                self.eof = true;
+            }
+
+            bytes.extend_from_slice(&chunk[..read]);
+            self.position += read as u64;
+
+            if ! self.handle.greedy() {
+                break;
+            }
+        }
+
+        Ok(bytes)
+    }
+
+    pub fn read_to_end(&mut self) -> io::Result<Vec<u8>> {
+        let mut bytes = self.take(self.buffer.len());
+
+        while ! self.eof {
+            self.fill()?;
+            bytes.extend(self.take(self.buffer.len()));
+        }
+
+        Ok(bytes)
+    }
+
+    /// Reads up to and including the next newline, or `limit` bytes if that comes
+    /// first. Returns `None` once there's nothing left to read.
+    pub fn read_line(&mut self, limit: Option<usize>) -> io::Result<Option<Vec<u8>>> {
+        let mut searched = 0;
+
+        loop {
+            let newline = self.buffer[searched..].iter().position(|b| *b == b'\n').map(|p| searched + p + 1);
+
+            let length = match (newline, limit) {
+                (Some(newline), Some(limit)) => Some(newline.min(limit)),
+                (Some(newline), None) => Some(newline),
+                (None, Some(limit)) if self.buffer.len() >= limit => Some(limit),
+                _ => None,
+            };
+
+            if let Some(length) = length {
+                return Ok(Some(self.take(length)));
+            }
+
+            searched = self.buffer.len();
+
+            if self.eof || self.fill()? == 0 {
+                let length = self.buffer.len();
+
+                return Ok(if length > 0 { Some(self.take(length)) } else { None });
+            }
+        }
+    }
+
+    pub fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        // Anything that was read ahead has to be thrown away, so that the write
+        // happens at the position the script thinks it's at.
+        if ! self.buffer.is_empty() {
+            self.buffer.clear();
+            self.handle.seek(SeekFrom::Start(self.position))?;
+        }
+
+        let written = self.handle.write(bytes)?;
+        self.position += written as u64;
+
+        Ok(written)
+    }
+
+    pub fn seek(&mut self, offset: i64, whence: i64) -> io::Result<u64> {
+        let position = match whence {
+            SEEK_SET if offset >= 0 => SeekFrom::Start(offset as u64),
+            SEEK_CUR if self.position as i64 + offset >= 0 => SeekFrom::Start((self.position as i64 + offset) as u64),
+            SEEK_END => SeekFrom::End(offset),
+            _ => return Err(io::Error::from(io::ErrorKind::InvalidInput)),
+        };
+
+        self.position = self.handle.seek(position)?;
+        self.buffer.clear();
+        self.eof = false;
+
+        Ok(self.position)
+    }
+
+    pub fn tell(&self) -> u64 {
+        self.position
+    }
+
+    pub fn eof(&self) -> bool {
+        self.eof && self.buffer.is_empty()
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.handle.flush()
+    }
+
+    pub fn read_entry(&mut self) -> io::Result<Option<String>> {
+        self.handle.read_entry()
+    }
+
+    pub fn truncate(&mut self, size: u64) -> io::Result<()> {
+        self.handle.truncate(size)
+    }
+
+    pub fn lock(&mut self, operation: i64) -> io::Result<bool> {
+        self.handle.lock(operation)
+    }
+}
+
+/// The table of open streams, keyed by resource id.
+pub struct Streams {
+    streams: HashMap<usize, Stream>,
+    // The last handle returned by `opendir()`, which the directory functions fall
+    // back to when they're called without one.
+    pub directory: Option<Object>,
+}
+
+impl Default for Streams {
+    fn default() -> Self {
+        let mut streams = HashMap::new();
+
+        streams.insert(STDIN, Stream::new(Box::new(io::stdin())));
+        streams.insert(STDOUT, Stream::new(Box::new(io::stdout())));
+        streams.insert(STDERR, Stream::new(Box::new(io::stderr())));
+
+        Self { streams, directory: None }
+    }
+}
+
+impl Streams {
+    /// Adds a stream to the table and returns the resource that refers to it.
+    pub fn insert(&mut self, stream: Stream) -> Object {
+        let resource = new_resource("stream");
+
+        if let Object::Resource(r) = &resource {
+            self.streams.insert(r.borrow().id, stream);
+        }
+
+        resource
+    }
+
+    pub fn get(&mut self, id: usize) -> Option<&mut Stream> {
+        self.streams.get_mut(&id)
+    }
+
+    pub fn remove(&mut self, id: usize) -> Option<Stream> {
+        self.streams.remove(&id)
+    }
+}
+
+/// The resource for one of the standard streams, used for the `STDIN`, `STDOUT` and
+/// `STDERR` constants.
+pub fn standard_stream(id: usize) -> Object {
+    Object::Resource(Rc::new(RefCell::new(Resource { id, kind: "stream".into() })))
+}
+
+/// Opens a stream with whichever wrapper handles the URL's scheme, returning the reason
+/// it failed otherwise. Paths without a scheme are plain files.
+pub(crate) fn open(vm: &mut Machine, function: &str, url: &str, mode: &str) -> Result<Stream, String> {
+    let wrapper: Rc<dyn Wrapper> = match scheme(url) {
+        Some(scheme) => match vm.globals.get_wrapper(scheme) {
+            Some(wrapper) => wrapper,
+            None => {
+                error::warning(format!("{}(): Unable to find the wrapper \"{}\" - did you forget to enable it when you configured PHP?", function, scheme));
+
+                Rc::new(FileWrapper)
+            },
+        },
+        None => Rc::new(FileWrapper),
+    };
+
+    wrapper.open(url, mode).map(Stream::new)
+}
+
+/// Looks up the stream that an argument refers to, throwing if it isn't an open stream.
+pub(crate) fn stream<'a>(vm: &'a mut Machine, function: &str, value: &Object) -> &'a mut Stream {
+    let id = match value {
+        Object::Resource(resource) => resource.borrow().id,
+        value => error::throw("TypeError", format!("{}(): Argument #1 ($stream) must be of type resource, {} given", function, value.type_name())),
+    };
+
+    match vm.streams.get(id) {
+        Some(stream) => stream,
+        None => error::throw("TypeError", format!("{}(): supplied resource is not a valid stream resource", function)),
+    }
+}
+
+fn string(bytes: Vec<u8>) -> Object {
+    Object::String(bytes.into())
+}
+
+fn read_failed(function: &str, length: usize, e: &io::Error) -> Object {
+    error::notice(format!("{}(): Read of {} bytes failed with errno={} {}", function, length, e.raw_os_error().unwrap_or(0), reason(e)));
+
+    Object::False
+}
+
+/// Writes to a stream on behalf of `function`, returning the number of bytes written
+/// or `false` with a notice if the write fails.
+pub(crate) fn write(vm: &mut Machine, function: &str, handle: &Object, bytes: &[u8]) -> Object {
+    match stream(vm, function, handle).write(bytes) {
+        Ok(written) => Object::Integer(written as i64),
+        Err(e) => {
+            error::notice(format!("{}(): Write of {} bytes failed with errno={} {}", function, bytes.len(), e.raw_os_error().unwrap_or(0), reason(&e)));
+
+            Object::False
+        },
+    }
+}
+
+pub fn fopen(vm: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 4);
+
+    let filename = convert::to_string(&args[0]);
+    let mode = convert::to_string(&args[1]);
+
+    if filename.is_empty() {
+        error::throw("ValueError", "Path cannot be empty");
+    }
+
+    match open(vm, "fopen", &filename, &mode) {
+        Ok(stream) => vm.streams.insert(stream),
+        Err(reason) => {
+            error::warning(format!("fopen({}): Failed to open stream: {}", filename, reason));
+
+            Object::False
+        },
+    }
+}
+
+pub fn fclose(vm: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    stream(vm, "fclose", &args[0]);
+
+    if let Object::Resource(resource) = &args[0] {
+        let mut resource = resource.borrow_mut();
+
+        if let Some(mut stream) = vm.streams.remove(resource.id) {
+            let _ = stream.flush();
+        }
+
+        resource.kind = "Unknown".into();
+    }
+
+    Object::True
+}
+
+pub fn fread(vm: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    let length = convert::to_int(&args[1]);
+
+    if length <= 0 {
+        error::throw("ValueError", "fread(): Argument #2 ($length) must be greater than 0");
+    }
+
+    match stream(vm, "fread", &args[0]).read(length as usize) {
+        Ok(bytes) => string(bytes),
+        Err(e) => read_failed("fread", length as usize, &e),
+    }
+}
+
+pub fn fgets(vm: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    let limit = match args.get(1) {
+        None | Some(Object::Null) => None,
+        Some(length) => match convert::to_int(length) {
+            length if length <= 0 => error::throw("ValueError", "fgets(): Argument #2 ($length) must be greater than 0"),
+            length => Some(length as usize - 1),
+        },
+    };
+
+    match stream(vm, "fgets", &args[0]).read_line(limit) {
+        Ok(Some(line)) => string(line),
+        Ok(None) => Object::False,
+        Err(e) => read_failed("fgets", CHUNK_SIZE, &e),
+    }
+}
+
+pub fn fgetc(vm: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    match stream(vm, "fgetc", &args[0]).read(1) {
+        Ok(byte) if byte.is_empty() => Object::False,
+        Ok(byte) => string(byte),
+        Err(e) => read_failed("fgetc", CHUNK_SIZE, &e),
+    }
+}
+
+pub fn fwrite(vm: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 3);
+
+    let data = convert::to_bytes(&args[1]);
+    let mut bytes = data.as_bytes();
+
+    if let Some(length) = args.get(2).filter(|l| ! matches!(l, Object::Null)) {
+        bytes = &bytes[..(convert::to_int(length).max(0) as usize).min(bytes.len())];
+    }
+
+    if bytes.is_empty() {
+        stream(vm, "fwrite", &args[0]);
+
+        return Object::Integer(0);
+    }
+
+    write(vm, "fwrite", &args[0], bytes)
+}
+
+pub fn fputs(vm: &mut Machine, args: Vec<Object>) -> Object {
+    fwrite(vm, args)
+}
+
+pub fn feof(vm: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    Object::from_bool(stream(vm, "feof", &args[0]).eof())
+}
+
+pub fn fseek(vm: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 3);
+
+    let offset = convert::to_int(&args[1]);
+    let whence = args.get(2).map(convert::to_int).unwrap_or(SEEK_SET);
+
+    match stream(vm, "fseek", &args[0]).seek(offset, whence) {
+        Ok(_) => Object::Integer(0),
+        Err(_) => Object::Integer(-1),
+    }
+}
+
+pub fn ftell(vm: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    Object::Integer(stream(vm, "ftell", &args[0]).tell() as i64)
+}
+
+pub fn rewind(vm: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    Object::from_bool(stream(vm, "rewind", &args[0]).seek(0, SEEK_SET).is_ok())
+}
+
+pub fn fflush(vm: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    Object::from_bool(stream(vm, "fflush", &args[0]).flush().is_ok())
+}
+
+pub fn flock(vm: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 3);
+
+    let operation = convert::to_int(&args[1]);
+
+    if ! (LOCK_SH..=LOCK_UN).contains(&(operation & 3)) {
+        error::throw("ValueError", "flock(): Argument #2 ($operation) must be one of LOCK_SH, LOCK_EX, or LOCK_UN");
+    }
+
+    let result = stream(vm, "flock", &args[0]).lock(operation);
+
+    vm.set_reference(2, Object::Integer(matches!(result, Ok(false)) as i64));
+
+    Object::from_bool(matches!(result, Ok(true)))
+}
+
+/// Checks the single-character arguments that the CSV functions take.
+fn csv_character(function: &str, position: usize, name: &str, value: Option<&Object>, default: u8, optional: bool) -> Option<u8> {
+    let value = match value {
+        Some(value) => convert::to_bytes(value),
+        None => return Some(default),
+    };
+
+    match value.as_bytes() {
+        [c] => Some(*c),
+        [] if optional => None,
+        _ if optional => error::throw("ValueError", format!("{}(): Argument #{} (${}) must be empty or a single character", function, position, name)),
+        _ => error::throw("ValueError", format!("{}(): Argument #{} (${}) must be a single character", function, position, name)),
+    }
+}
+
+/// Splits a line of CSV into fields, reading further lines from the stream while a
+/// quoted field spans more than one. This follows `php_fgetcsv()`, including its habit
+/// of keeping escape characters in the output.
+fn parse_csv(stream: &mut Stream, mut line: Vec<u8>, delimiter: u8, enclosure: u8, escape: Option<u8>) -> io::Result<Array> {
+    let line_end = |line: &[u8]| {
+        let mut end = line.len();
+
+        if end > 0 && line[end - 1] == b'\n' {
+            end -= 1;
+        }
+
+        if end > 0 && line[end - 1] == b'\r' {
+            end -= 1;
+        }
+
+        end
+    };
+
+    let mut fields = Array::new();
+
+    if line_end(&line) == 0 {
+        fields.push(Object::Null);
+
+        return Ok(fields);
+    }
+
+    let mut i = 0;
+
+    loop {
+        let mut field = Vec::new();
+        let end = line_end(&line);
+
+        // Whitespace in front of an enclosure is skipped.
+        let start = line[i..end].iter().position(|c| *c == delimiter || ! c.is_ascii_whitespace()).map(|p| i + p);
+
+        if let Some(start) = start.filter(|s| line[*s] == enclosure) {
+            i = start + 1;
+
+            loop {
+                if i >= line.len() {
+                    match stream.read_line(None)? {
+                        Some(next) => line.extend(next),
+                        None => break,
+                    }
+
+                    continue;
+                }
+
+                let c = line[i];
+
+                if Some(c) == escape && escape != Some(enclosure) && i + 1 < line.len() {
+                    field.extend_from_slice(&line[i..i + 2]);
+                    i += 2;
+                } else if c == enclosure && line.get(i + 1) == Some(&enclosure) {
+                    field.push(enclosure);
+                    i += 2;
+                } else if c == enclosure {
+                    i += 1;
+                    break;
+                } else {
+                    field.push(c);
+                    i += 1;
+                }
+            }
+
+            // Anything between the closing enclosure and the delimiter is kept as-is.
+            let end = line_end(&line);
+
+            while i < end && line[i] != delimiter {
+                field.push(line[i]);
+                i += 1;
+            }
+        } else {
+            while i < end && line[i] != delimiter {
+                field.push(line[i]);
+                i += 1;
+            }
+        }
+
+        fields.push(string(field));
+
+        if i < line_end(&line) && line[i] == delimiter {
+            i += 1;
+        } else {
+            break;
+        }
+    }
+
+    Ok(fields)
+}
+
+pub fn fgetcsv(vm: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 5);
+
+    let limit = match args.get(1) {
+        None | Some(Object::Null) => None,
+        Some(length) => match convert::to_int(length) {
+            length if length < 0 => error::throw("ValueError", "fgetcsv(): Argument #2 ($length) must be between 0 and 2147483647"),
+            0 => None,
+            length => Some(length as usize),
+        },
+    };
+
+    let delimiter = csv_character("fgetcsv", 3, "separator", args.get(2), b',', false).unwrap();
+    let enclosure = csv_character("fgetcsv", 4, "enclosure", args.get(3), b'"', false).unwrap();
+    let escape = csv_character("fgetcsv", 5, "escape", args.get(4), b'\\', true);
+
+    let stream = stream(vm, "fgetcsv", &args[0]);
+
+    let line = match stream.read_line(limit) {
+        Ok(Some(line)) => line,
+        Ok(None) => return Object::False,
+        Err(e) => return read_failed("fgetcsv", CHUNK_SIZE, &e),
+    };
+
+    match parse_csv(stream, line, delimiter, enclosure, escape) {
+        Ok(fields) => Object::from_array(fields),
+        Err(e) => read_failed("fgetcsv", CHUNK_SIZE, &e),
+    }
+}
+
+pub fn fputcsv(vm: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 6);
+
+    let fields = match &args[1] {
+        Object::Array(fields) => fields.borrow().clone(),
+        value => error::throw("TypeError", format!("fputcsv(): Argument #2 ($fields) must be of type array, {} given", value.type_name())),
+    };
+
+    let delimiter = csv_character("fputcsv", 3, "separator", args.get(2), b',', false).unwrap();
+    let enclosure = csv_character("fputcsv", 4, "enclosure", args.get(3), b'"', false).unwrap();
+    let escape = csv_character("fputcsv", 5, "escape", args.get(4), b'\\', true);
+    let eol = args.get(5).map(convert::to_bytes).unwrap_or_else(|| "\n".into());
+
+    let mut line = Vec::new();
+
+    for (i, field) in fields.values().enumerate() {
+        if i > 0 {
+            line.push(delimiter);
+        }
+
+        let field = convert::to_bytes(field);
+        let quote = field.iter().any(|&c| c == delimiter || c == enclosure || Some(c) == escape || b"\n\r\t ".contains(&c));
+
+        if ! quote {
+            line.extend_from_slice(field.as_bytes());
+            continue;
+        }
+
+        let mut escaped = false;
+        line.push(enclosure);
+
+        for c in field.iter().copied() {
+            if Some(c) == escape {
+                escaped = true;
+            } else if ! escaped && c == enclosure {
+                line.push(enclosure);
+            } else {
+                escaped = false;
+            }
+
+            line.push(c);
+        }
+
+        line.push(enclosure);
+    }
+
+    line.extend_from_slice(eol.as_bytes());
+
+    write(vm, "fputcsv", &args[0], &line)
+}
+
+pub fn stream_get_wrappers(vm: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 0);
+
+    Object::from_array(vm.globals.get_wrappers().into_iter().map(|wrapper| Object::String(wrapper.into())).collect())
+}
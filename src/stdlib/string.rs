@@ -0,0 +1,1428 @@
+use super::arity;
+use crate::object::{Object, Array, ByteString, Key, convert};
+use std::collections::HashMap;
+use crate::vm::Machine;
+use crate::error;
+use std::cmp::Ordering;
+
+// PHP strings are byte strings, so everything in here works on bytes rather than
+// characters. Multibyte-aware versions live in the `mb_*` functions.
+
+pub const STR_PAD_LEFT: i64 = 0;
+pub const STR_PAD_RIGHT: i64 = 1;
+pub const STR_PAD_BOTH: i64 = 2;
+
+fn string(bytes: &[u8]) -> Object {
+    Object::String(bytes.into())
+}
+
+fn find(haystack: &[u8], needle: &[u8], from: usize) -> Option<usize> {
+    if needle.is_empty() {
+        return Some(from);
+    }
+
+    haystack.get(from..)?
+        .windows(needle.len())
+        .position(|window| window == needle)
+        .map(|p| p + from)
+}
+
+/// Finds the last occurrence of `needle` that lies entirely within `haystack[from..to]`.
+fn rfind(haystack: &[u8], needle: &[u8], from: usize, to: usize) -> Option<usize> {
+    if needle.is_empty() {
+        return Some(to);
+    }
+
+    haystack.get(from..to)?
+        .windows(needle.len())
+        .rposition(|window| window == needle)
+        .map(|p| p + from)
+}
+
+fn offset_error(function: &str, argument: usize, name: &str) -> ! {
+    error::throw("ValueError", format!("{}(): Argument #{} (${}) must be contained in argument #1 ($haystack)", function, argument, name))
+}
+
+/// The set of bytes described by a character list such as `"a..z\n"`.
+fn character_mask(characters: &[u8]) -> [bool; 256] {
+    let mut mask = [false; 256];
+    let mut i = 0;
+
+    while i < characters.len() {
+        let c = characters[i];
+
+        if i + 3 < characters.len() && characters[i + 1] == b'.' && characters[i + 2] == b'.' && characters[i + 3] >= c {
+            for r in c..=characters[i + 3] {
+                mask[r as usize] = true;
+            }
+
+            i += 4;
+        } else {
+            mask[c as usize] = true;
+            i += 1;
+        }
+    }
+
+    mask
+}
+
+fn ordering(ordering: Ordering) -> Object {
+    Object::Integer(ordering as i64)
+}
+
+pub fn strlen(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    let subject = convert::to_bytes(args.first().unwrap());
+
+    Object::Integer(subject.len() as i64)
+}
+
+pub fn substr(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 3);
+
+    let subject = convert::to_bytes(&args[0]);
+    let bytes = subject.as_bytes();
+    let len = bytes.len() as i64;
+
+    let mut start = convert::to_int(&args[1]);
+
+    if start > len {
+        return Object::String(ByteString::new());
+    } else if start < 0 {
+        start = (len + start).max(0);
+    }
+
+    let end = match args.get(2) {
+        None | Some(Object::Null) => len,
+        Some(length) => match convert::to_int(length) {
+            l if l < 0 => len + l,
+            l => len.min(start.saturating_add(l)),
+        },
+    };
+
+    if end <= start {
+        return Object::String(ByteString::new());
+    }
+
+    string(&bytes[start as usize..end as usize])
+}
+
+fn position(function: &str, args: Vec<Object>, insensitive: bool, reverse: bool) -> Object {
+    let mut haystack = convert::to_bytes(&args[0]).into_bytes();
+    let mut needle = convert::to_bytes(&args[1]).into_bytes();
+    let offset = args.get(2).map(convert::to_int).unwrap_or(0);
+    let len = haystack.len() as i64;
+
+    if insensitive {
+        haystack.make_ascii_lowercase();
+        needle.make_ascii_lowercase();
+    }
+
+    let found = if reverse {
+        let (from, to) = match offset {
+            o if o >= 0 && o <= len => (o as usize, haystack.len()),
+            o if o < 0 && -o <= len => match (-o) < needle.len() as i64 {
+                true => (0, haystack.len()),
+                false => (0, (len + o) as usize + needle.len()),
+            },
+            _ => offset_error(function, 3, "offset"),
+        };
+
+        rfind(&haystack, &needle, from, to)
+    } else {
+        let from = match offset {
+            o if o < 0 && -o <= len => len + o,
+            o if o >= 0 && o <= len => o,
+            _ => offset_error(function, 3, "offset"),
+        };
+
+        find(&haystack, &needle, from as usize)
+    };
+
+    match found {
+        Some(position) => Object::Integer(position as i64),
+        None => Object::False,
+    }
+}
+
+pub fn strpos(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 3);
+
+    position("strpos", args, false, false)
+}
+
+pub fn stripos(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 3);
+
+    position("stripos", args, true, false)
+}
+
+pub fn strrpos(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 3);
+
+    position("strrpos", args, false, true)
+}
+
+pub fn strripos(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 3);
+
+    position("strripos", args, true, true)
+}
+
+pub fn str_contains(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    let haystack = convert::to_bytes(&args[0]);
+    let needle = convert::to_bytes(&args[1]);
+
+    Object::from_bool(find(haystack.as_bytes(), needle.as_bytes(), 0).is_some())
+}
+
+pub fn str_starts_with(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    let haystack = convert::to_bytes(&args[0]);
+    let needle = convert::to_bytes(&args[1]);
+
+    Object::from_bool(haystack.as_bytes().starts_with(needle.as_bytes()))
+}
+
+pub fn str_ends_with(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    let haystack = convert::to_bytes(&args[0]);
+    let needle = convert::to_bytes(&args[1]);
+
+    Object::from_bool(haystack.as_bytes().ends_with(needle.as_bytes()))
+}
+
+fn replace_in(subject: &[u8], search: &[u8], replace: &[u8], insensitive: bool, count: &mut i64) -> Vec<u8> {
+    if search.is_empty() || search.len() > subject.len() {
+        return subject.to_vec();
+    }
+
+    let (haystack, needle) = match insensitive {
+        true => (subject.to_ascii_lowercase(), search.to_ascii_lowercase()),
+        false => (subject.to_vec(), search.to_vec()),
+    };
+
+    let mut result = Vec::with_capacity(subject.len());
+    let mut last = 0;
+
+    while let Some(position) = find(&haystack, &needle, last) {
+        result.extend_from_slice(&subject[last..position]);
+        result.extend_from_slice(replace);
+        last = position + needle.len();
+        *count += 1;
+    }
+
+    result.extend_from_slice(&subject[last..]);
+    result
+}
+
+fn replace(args: Vec<Object>, insensitive: bool, vm: &mut Machine) -> Object {
+    let search = &args[0];
+    let replace = &args[1];
+    let subject = &args[2];
+    let mut count = 0;
+
+    let pairs: Vec<(ByteString, ByteString)> = match (search, replace) {
+        (Object::Array(search), Object::Array(replace)) => {
+            let replace: Vec<ByteString> = replace.borrow().iter().map(|(_, r)| convert::to_bytes(r)).collect();
+
+            search.borrow().iter().enumerate()
+                .map(|(i, (_, s))| (convert::to_bytes(s), replace.get(i).cloned().unwrap_or_default()))
+                .collect()
+        },
+        (Object::Array(search), replace) => {
+            let replace = convert::to_bytes(replace);
+
+            search.borrow().iter().map(|(_, s)| (convert::to_bytes(s), replace.clone())).collect()
+        },
+        (_, Object::Array(_)) => error::throw("TypeError", format!("{}(): Argument #2 ($replace) must be of type string when argument #1 ($search) is a string", if insensitive { "str_ireplace" } else { "str_replace" })),
+        (search, replace) => vec![(convert::to_bytes(search), convert::to_bytes(replace))],
+    };
+
+    let mut replace_all = |value: &Object| {
+        let mut subject = convert::to_bytes(value).into_bytes();
+
+        for (search, replace) in pairs.iter() {
+            subject = replace_in(&subject, search.as_bytes(), replace.as_bytes(), insensitive, &mut count);
+        }
+
+        string(&subject)
+    };
+
+    let result = match subject {
+        Object::Array(items) => {
+            let mut result = Array::new();
+
+            for (key, value) in items.borrow().iter() {
+                result.insert(key.clone(), match value {
+                    Object::Array(..) => value.clone(),
+                    value => replace_all(value),
+                });
+            }
+
+            Object::from_array(result)
+        },
+        subject => replace_all(subject),
+    };
+
+    vm.set_reference(3, Object::Integer(count));
+
+    result
+}
+
+pub fn str_replace(vm: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 4);
+
+    replace(args, false, vm)
+}
+
+pub fn str_ireplace(vm: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 4);
+
+    replace(args, true, vm)
+}
+
+pub fn explode(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 3);
+
+    let separator = convert::to_bytes(&args[0]);
+    let subject = convert::to_bytes(&args[1]);
+    let limit = args.get(2).map(convert::to_int).unwrap_or(i64::MAX);
+
+    if separator.is_empty() {
+        error::throw("ValueError", "explode(): Argument #1 ($separator) cannot be empty");
+    }
+
+    let (separator, subject) = (separator.as_bytes(), subject.as_bytes());
+    let mut parts = Vec::new();
+    let mut last = 0;
+
+    while let Some(position) = find(subject, separator, last) {
+        parts.push(&subject[last..position]);
+        last = position + separator.len();
+    }
+
+    parts.push(&subject[last..]);
+
+    let parts = match limit {
+        l if l > 0 && (l as usize) < parts.len() => {
+            let rest = parts[l as usize - 1..].join(separator);
+
+            let mut parts: Vec<Vec<u8>> = parts[..l as usize - 1].iter().map(|p| p.to_vec()).collect();
+            parts.push(rest);
+            parts
+        },
+        l if l < 0 => parts[..parts.len().saturating_sub(l.unsigned_abs() as usize)].iter().map(|p| p.to_vec()).collect(),
+        0 => vec![subject.to_vec()],
+        _ => parts.iter().map(|p| p.to_vec()).collect(),
+    };
+
+    Object::from_array(parts.iter().map(|p| string(p)).collect())
+}
+
+pub fn implode(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    let (separator, pieces) = match (&args[0], args.get(1)) {
+        (Object::Array(pieces), None) => (ByteString::new(), pieces.clone()),
+        (separator, Some(Object::Array(pieces))) => (convert::to_bytes(separator), pieces.clone()),
+        _ => error::throw("TypeError", "implode(): Argument #2 ($array) must be of type ?array, string given"),
+    };
+
+    let pieces: Vec<ByteString> = pieces.borrow().iter().map(|(_, p)| convert::to_bytes(p)).collect();
+
+    Object::String(pieces.join(separator.as_bytes()).into())
+}
+
+pub fn join(vm: &mut Machine, args: Vec<Object>) -> Object {
+    implode(vm, args)
+}
+
+fn trim_with(args: Vec<Object>, left: bool, right: bool) -> Object {
+    let subject = convert::to_bytes(&args[0]);
+    let characters = args.get(1).map(convert::to_bytes).unwrap_or_else(|| " \n\r\t\x0b\0".into());
+    let mask = character_mask(characters.as_bytes());
+    let mut bytes = subject.as_bytes();
+
+    if left {
+        while let Some(b) = bytes.first() {
+            if ! mask[*b as usize] {
+                break;
+            }
+
+            bytes = &bytes[1..];
+        }
+    }
+
+    if right {
+        while let Some(b) = bytes.last() {
+            if ! mask[*b as usize] {
+                break;
+            }
+
+            bytes = &bytes[..bytes.len() - 1];
+        }
+    }
+
+    string(bytes)
+}
+
+pub fn trim(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    trim_with(args, true, true)
+}
+
+pub fn ltrim(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    trim_with(args, true, false)
+}
+
+pub fn rtrim(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    trim_with(args, false, true)
+}
+
+pub fn chop(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    trim_with(args, false, true)
+}
+
+pub fn strtoupper(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    Object::String(convert::to_bytes(&args[0]).to_ascii_uppercase().into())
+}
+
+pub fn strtolower(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    Object::String(convert::to_bytes(&args[0]).to_ascii_lowercase().into())
+}
+
+pub fn ucfirst(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    let mut subject = convert::to_bytes(&args[0]).into_bytes();
+
+    if let Some(first) = subject.first_mut() {
+        first.make_ascii_uppercase();
+    }
+
+    string(&subject)
+}
+
+pub fn lcfirst(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    let mut subject = convert::to_bytes(&args[0]).into_bytes();
+
+    if let Some(first) = subject.first_mut() {
+        first.make_ascii_lowercase();
+    }
+
+    string(&subject)
+}
+
+pub fn ucwords(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    let mut subject = convert::to_bytes(&args[0]).into_bytes();
+    let delimiters = args.get(1).map(convert::to_bytes).unwrap_or_else(|| " \t\r\n\x0c\x0b".into());
+    let mask = character_mask(delimiters.as_bytes());
+    let mut capitalise = true;
+
+    for b in subject.iter_mut() {
+        if capitalise {
+            b.make_ascii_uppercase();
+        }
+
+        capitalise = mask[*b as usize];
+    }
+
+    string(&subject)
+}
+
+pub fn str_repeat(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    let subject = convert::to_bytes(&args[0]);
+    let times = convert::to_int(&args[1]);
+
+    if times < 0 {
+        error::throw("ValueError", "str_repeat(): Argument #2 ($times) must be greater than or equal to 0");
+    }
+
+    Object::String(subject.repeat(times as usize).into())
+}
+
+pub fn str_pad(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 4);
+
+    let subject = convert::to_bytes(&args[0]).into_bytes();
+    let length = convert::to_int(&args[1]);
+    let padding = args.get(2).map(convert::to_bytes).unwrap_or_else(|| " ".into()).into_bytes();
+    let r#type = args.get(3).map(convert::to_int).unwrap_or(STR_PAD_RIGHT);
+
+    if length <= subject.len() as i64 {
+        return string(&subject);
+    }
+
+    if padding.is_empty() {
+        error::throw("ValueError", "str_pad(): Argument #3 ($pad_string) must be a non-empty string");
+    }
+
+    let total = length as usize - subject.len();
+    let (left, right) = match r#type {
+        STR_PAD_LEFT => (total, 0),
+        STR_PAD_RIGHT => (0, total),
+        STR_PAD_BOTH => (total / 2, total - total / 2),
+        _ => error::throw("ValueError", "str_pad(): Argument #4 ($pad_type) must be STR_PAD_LEFT, STR_PAD_RIGHT, or STR_PAD_BOTH"),
+    };
+
+    let pad = |n: usize| padding.iter().cycle().take(n).copied().collect::<Vec<u8>>();
+
+    string(&[pad(left), subject.clone(), pad(right)].concat())
+}
+
+pub fn str_split(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    let subject = convert::to_bytes(&args[0]);
+    let length = args.get(1).map(convert::to_int).unwrap_or(1);
+
+    if length < 1 {
+        error::throw("ValueError", "str_split(): Argument #2 ($length) must be greater than 0");
+    }
+
+    Object::from_array(subject.as_bytes().chunks(length as usize).map(string).collect())
+}
+
+pub fn strrev(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    let mut subject = convert::to_bytes(&args[0]).into_bytes();
+    subject.reverse();
+
+    string(&subject)
+}
+
+pub fn wordwrap(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 4);
+
+    let text = convert::to_bytes(&args[0]).into_bytes();
+    let width = args.get(1).map(convert::to_int).unwrap_or(75);
+    let r#break = args.get(2).map(convert::to_bytes).unwrap_or_else(|| "\n".into()).into_bytes();
+    let cut = args.get(3).map(|c| c.to_bool()).unwrap_or(false);
+
+    if text.is_empty() {
+        return Object::String(ByteString::new());
+    }
+
+    if r#break.is_empty() {
+        error::throw("ValueError", "wordwrap(): Argument #3 ($break) cannot be empty");
+    }
+
+    if width == 0 && cut {
+        error::throw("ValueError", "wordwrap(): Argument #4 ($cut_long_words) cannot be true when argument #2 ($width) is 0");
+    }
+
+    // This is a direct port of PHP's implementation so that lines break in exactly the same places.
+    let (mut last_start, mut last_space) = (0i64, 0i64);
+
+    if r#break.len() == 1 && ! cut {
+        let mut wrapped = text.clone();
+
+        for current in 0..text.len() as i64 {
+            let c = text[current as usize];
+
+            if c == r#break[0] {
+                last_start = current + 1;
+                last_space = current + 1;
+            } else if c == b' ' {
+                if current - last_start >= width {
+                    wrapped[current as usize] = r#break[0];
+                    last_start = current + 1;
+                }
+
+                last_space = current;
+            } else if current - last_start >= width && last_start != last_space {
+                wrapped[last_space as usize] = r#break[0];
+                last_start = last_space + 1;
+            }
+        }
+
+        return string(&wrapped);
+    }
+
+    let mut wrapped = Vec::new();
+    let mut current = 0i64;
+    let len = text.len() as i64;
+
+    while current < len {
+        let c = text[current as usize];
+
+        if c == r#break[0] && current + (r#break.len() as i64) < len && text[current as usize..].starts_with(&r#break) {
+            wrapped.extend_from_slice(&text[last_start as usize..(current as usize + r#break.len())]);
+            current += r#break.len() as i64 - 1;
+            last_start = current + 1;
+            last_space = current + 1;
+        } else if c == b' ' {
+            if current - last_start >= width {
+                wrapped.extend_from_slice(&text[last_start as usize..current as usize]);
+                wrapped.extend_from_slice(&r#break);
+                last_start = current + 1;
+            }
+
+            last_space = current;
+        } else if current - last_start >= width && cut && last_start >= last_space {
+            wrapped.extend_from_slice(&text[last_start as usize..current as usize]);
+            wrapped.extend_from_slice(&r#break);
+            last_start = current;
+            last_space = current;
+        } else if current - last_start >= width && last_start < last_space {
+            wrapped.extend_from_slice(&text[last_start as usize..last_space as usize]);
+            wrapped.extend_from_slice(&r#break);
+            last_start = last_space + 1;
+            last_space = last_start;
+        }
+
+        current += 1;
+    }
+
+    if last_start != current {
+        wrapped.extend_from_slice(&text[last_start as usize..current as usize]);
+    }
+
+    string(&wrapped)
+}
+
+pub fn nl2br(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    let subject = convert::to_bytes(&args[0]).into_bytes();
+    let xhtml = args.get(1).map(|x| x.to_bool()).unwrap_or(true);
+    let br: &[u8] = if xhtml { b"<br />" } else { b"<br>" };
+    let mut result = Vec::with_capacity(subject.len());
+    let mut i = 0;
+
+    while i < subject.len() {
+        let c = subject[i];
+
+        if c == b'\r' || c == b'\n' {
+            result.extend_from_slice(br);
+            result.push(c);
+
+            // `\r\n` and `\n\r` only get a single break.
+            if let Some(&next) = subject.get(i + 1) {
+                if (next == b'\r' || next == b'\n') && next != c {
+                    result.push(next);
+                    i += 1;
+                }
+            }
+        } else {
+            result.push(c);
+        }
+
+        i += 1;
+    }
+
+    string(&result)
+}
+
+pub fn strcmp(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    let (a, b) = (convert::to_bytes(&args[0]), convert::to_bytes(&args[1]));
+
+    ordering(a.as_bytes().cmp(b.as_bytes()))
+}
+
+pub fn strcasecmp(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    let (a, b) = (convert::to_bytes(&args[0]), convert::to_bytes(&args[1]));
+
+    ordering(a.to_ascii_lowercase().cmp(&b.to_ascii_lowercase()))
+}
+
+fn compare_prefix(function: &str, args: Vec<Object>, insensitive: bool) -> Object {
+    let (mut a, mut b) = (convert::to_bytes(&args[0]).into_bytes(), convert::to_bytes(&args[1]).into_bytes());
+    let length = convert::to_int(&args[2]);
+
+    if length < 0 {
+        error::throw("ValueError", format!("{}(): Argument #3 ($length) must be greater than or equal to 0", function));
+    }
+
+    if insensitive {
+        a.make_ascii_lowercase();
+        b.make_ascii_lowercase();
+    }
+
+    a.truncate(length as usize);
+    b.truncate(length as usize);
+
+    ordering(a.cmp(&b))
+}
+
+pub fn strncmp(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 3);
+
+    compare_prefix("strncmp", args, false)
+}
+
+pub fn strncasecmp(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 3);
+
+    compare_prefix("strncasecmp", args, true)
+}
+
+/// Compares two strings in "natural order" (so `img12` comes after `img2`), using the
+/// same algorithm as PHP's `strnatcmp_ex()`.
+pub fn natural_compare(a: &[u8], b: &[u8], insensitive: bool) -> Ordering {
+    if a.is_empty() || b.is_empty() {
+        return a.len().cmp(&b.len());
+    }
+
+    let at = |s: &[u8], i: usize| s.get(i).copied().unwrap_or(0);
+    let is_digit = |s: &[u8], i: usize| at(s, i).is_ascii_digit();
+
+    // Leading zeros are significant in "fractional" numbers, so those are compared
+    // digit by digit. Otherwise the longest run of digits wins.
+    let compare_left = |ai: &mut usize, bi: &mut usize| loop {
+        match (is_digit(a, *ai), is_digit(b, *bi)) {
+            (false, false) => return Ordering::Equal,
+            (false, true) => return Ordering::Less,
+            (true, false) => return Ordering::Greater,
+            _ => match at(a, *ai).cmp(&at(b, *bi)) {
+                Ordering::Equal => (),
+                ordering => return ordering,
+            },
+        }
+
+        *ai += 1;
+        *bi += 1;
+    };
+
+    let compare_right = |ai: &mut usize, bi: &mut usize| {
+        let mut bias = Ordering::Equal;
+
+        loop {
+            match (is_digit(a, *ai), is_digit(b, *bi)) {
+                (false, false) => return bias,
+                (false, true) => return Ordering::Less,
+                (true, false) => return Ordering::Greater,
+                _ => if bias == Ordering::Equal {
+                    bias = at(a, *ai).cmp(&at(b, *bi));
+                },
+            }
+
+            *ai += 1;
+            *bi += 1;
+        }
+    };
+
+    let (mut ai, mut bi) = (0, 0);
+    let mut leading = true;
+
+    loop {
+        if leading {
+            while at(a, ai) == b'0' && ai + 1 < a.len() && is_digit(a, ai + 1) {
+                ai += 1;
+            }
+
+            while at(b, bi) == b'0' && bi + 1 < b.len() && is_digit(b, bi + 1) {
+                bi += 1;
+            }
+
+            leading = false;
+        }
+
+        while at(a, ai).is_ascii_whitespace() || at(a, ai) == 0x0b {
+            ai += 1;
+        }
+
+        while at(b, bi).is_ascii_whitespace() || at(b, bi) == 0x0b {
+            bi += 1;
+        }
+
+        if is_digit(a, ai) && is_digit(b, bi) {
+            let fractional = at(a, ai) == b'0' || at(b, bi) == b'0';
+            let result = match fractional {
+                true => compare_left(&mut ai, &mut bi),
+                false => compare_right(&mut ai, &mut bi),
+            };
+
+            if result != Ordering::Equal {
+                return result;
+            }
+
+            match (ai >= a.len(), bi >= b.len()) {
+                (true, true) => return Ordering::Equal,
+                (true, false) => return Ordering::Less,
+                (false, true) => return Ordering::Greater,
+                _ => (),
+            }
+        }
+
+        let (mut ca, mut cb) = (at(a, ai), at(b, bi));
+
+        if insensitive {
+            ca = ca.to_ascii_uppercase();
+            cb = cb.to_ascii_uppercase();
+        }
+
+        match ca.cmp(&cb) {
+            Ordering::Equal => (),
+            ordering => return ordering,
+        }
+
+        ai += 1;
+        bi += 1;
+
+        match (ai >= a.len(), bi >= b.len()) {
+            (true, true) => return Ordering::Equal,
+            (true, false) => return Ordering::Less,
+            (false, true) => return Ordering::Greater,
+            _ => (),
+        }
+    }
+}
+
+pub fn strnatcmp(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    let (a, b) = (convert::to_bytes(&args[0]), convert::to_bytes(&args[1]));
+
+    ordering(natural_compare(a.as_bytes(), b.as_bytes(), false))
+}
+
+pub fn strnatcasecmp(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    let (a, b) = (convert::to_bytes(&args[0]), convert::to_bytes(&args[1]));
+
+    ordering(natural_compare(a.as_bytes(), b.as_bytes(), true))
+}
+
+pub fn substr_count(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 4);
+
+    let haystack = convert::to_bytes(&args[0]).into_bytes();
+    let needle = convert::to_bytes(&args[1]).into_bytes();
+    let len = haystack.len() as i64;
+
+    if needle.is_empty() {
+        error::throw("ValueError", "substr_count(): Argument #2 ($needle) cannot be empty");
+    }
+
+    let offset = match args.get(2).map(convert::to_int).unwrap_or(0) {
+        o if o < 0 => len + o,
+        o => o,
+    };
+
+    if offset < 0 || offset > len {
+        offset_error("substr_count", 3, "offset");
+    }
+
+    let end = match args.get(3) {
+        None | Some(Object::Null) => len,
+        Some(length) => match convert::to_int(length) {
+            l if l < 0 => len + l,
+            l => offset + l,
+        },
+    };
+
+    if end < offset || end > len {
+        error::throw("ValueError", "substr_count(): Argument #4 ($length) must be contained in argument #1 ($haystack)");
+    }
+
+    let haystack = &haystack[offset as usize..end as usize];
+    let (mut count, mut last) = (0, 0);
+
+    while let Some(position) = find(haystack, &needle, last) {
+        count += 1;
+        last = position + needle.len();
+    }
+
+    Object::Integer(count)
+}
+
+fn similar(a: &[u8], b: &[u8]) -> usize {
+    let (mut max, mut count, mut position_a, mut position_b) = (0, 0, 0, 0);
+
+    for i in 0..a.len() {
+        for j in 0..b.len() {
+            let l = a[i..].iter().zip(b[j..].iter()).take_while(|(x, y)| x == y).count();
+
+            if l > max {
+                max = l;
+                count += 1;
+                position_a = i;
+                position_b = j;
+            }
+        }
+    }
+
+    if max == 0 {
+        return 0;
+    }
+
+    let mut sum = max;
+
+    if position_a > 0 && position_b > 0 && count > 1 {
+        sum += similar(&a[..position_a], &b[..position_b]);
+    }
+
+    if position_a + max < a.len() && position_b + max < b.len() {
+        sum += similar(&a[position_a + max..], &b[position_b + max..]);
+    }
+
+    sum
+}
+
+pub fn similar_text(vm: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 3);
+
+    let (a, b) = (convert::to_bytes(&args[0]), convert::to_bytes(&args[1]));
+    let similarity = similar(a.as_bytes(), b.as_bytes());
+
+    let percent = match a.len() + b.len() {
+        0 => 0.0,
+        total => similarity as f64 * 2.0 * 100.0 / total as f64,
+    };
+
+    vm.set_reference(2, Object::Float(percent));
+
+    Object::Integer(similarity as i64)
+}
+
+pub fn levenshtein(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 5);
+
+    let (a, b) = (convert::to_bytes(&args[0]).into_bytes(), convert::to_bytes(&args[1]).into_bytes());
+    let insertion = args.get(2).map(convert::to_int).unwrap_or(1);
+    let replacement = args.get(3).map(convert::to_int).unwrap_or(1);
+    let deletion = args.get(4).map(convert::to_int).unwrap_or(1);
+
+    if a.is_empty() {
+        return Object::Integer(b.len() as i64 * insertion);
+    }
+
+    if b.is_empty() {
+        return Object::Integer(a.len() as i64 * deletion);
+    }
+
+    let mut previous: Vec<i64> = (0..=b.len() as i64).map(|i| i * insertion).collect();
+    let mut current = vec![0; b.len() + 1];
+
+    for ca in a.iter() {
+        current[0] = previous[0] + deletion;
+
+        for (j, cb) in b.iter().enumerate() {
+            let cost = previous[j] + if ca == cb { 0 } else { replacement };
+
+            current[j + 1] = cost.min(previous[j + 1] + deletion).min(current[j] + insertion);
+        }
+
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    Object::Integer(previous[b.len()])
+}
+
+pub fn bin2hex(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    Object::String(convert::to_bytes(&args[0]).iter().map(|b| format!("{:02x}", b)).collect::<String>().into())
+}
+
+pub fn hex2bin(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    let hex = convert::to_bytes(&args[0]).into_bytes();
+
+    if ! hex.len().is_multiple_of(2) {
+        error::warning("hex2bin(): Hexadecimal input string must have an even length");
+        return Object::False;
+    }
+
+    let bytes: Option<Vec<u8>> = hex.chunks(2)
+        .map(|pair| std::str::from_utf8(pair).ok().and_then(|pair| u8::from_str_radix(pair, 16).ok()))
+        .collect();
+
+    match bytes {
+        Some(bytes) => string(&bytes),
+        None => {
+            error::warning("hex2bin(): Input string must be hexadecimal string");
+            Object::False
+        },
+    }
+}
+
+pub fn addslashes(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    let bytes = convert::to_bytes(&args[0]).into_bytes();
+    let mut escaped = Vec::with_capacity(bytes.len());
+
+    for b in bytes {
+        match b {
+            b'\0' => escaped.extend_from_slice(b"\\0"),
+            b'\'' | b'"' | b'\\' => escaped.extend_from_slice(&[b'\\', b]),
+            b => escaped.push(b),
+        }
+    }
+
+    string(&escaped)
+}
+
+pub fn stripslashes(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    let bytes = convert::to_bytes(&args[0]).into_bytes();
+    let mut unescaped = Vec::with_capacity(bytes.len());
+    let mut bytes = bytes.into_iter();
+
+    while let Some(b) = bytes.next() {
+        match b {
+            b'\\' => match bytes.next() {
+                Some(b'0') => unescaped.push(b'\0'),
+                Some(next) => unescaped.push(next),
+                None => (),
+            },
+            b => unescaped.push(b),
+        }
+    }
+
+    string(&unescaped)
+}
+
+pub fn quotemeta(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    let bytes = convert::to_bytes(&args[0]).into_bytes();
+    let mut escaped = Vec::with_capacity(bytes.len());
+
+    for b in bytes {
+        match b {
+            b'.' | b'\\' | b'+' | b'*' | b'?' | b'[' | b'^' | b']' | b'$' | b'(' | b')' => escaped.extend_from_slice(&[b'\\', b]),
+            b => escaped.push(b),
+        }
+    }
+
+    string(&escaped)
+}
+
+pub fn addcslashes(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    let bytes = convert::to_bytes(&args[0]).into_bytes();
+    let mask = character_mask(&convert::to_bytes(&args[1]));
+    let mut escaped = Vec::with_capacity(bytes.len());
+
+    for b in bytes {
+        if ! mask[b as usize] {
+            escaped.push(b);
+            continue;
+        }
+
+        escaped.push(b'\\');
+
+        // Control characters and anything outside ASCII are written the way C would.
+        match b {
+            b'\x07' => escaped.push(b'a'),
+            b'\x08' => escaped.push(b'b'),
+            b'\t' => escaped.push(b't'),
+            b'\n' => escaped.push(b'n'),
+            b'\x0b' => escaped.push(b'v'),
+            b'\x0c' => escaped.push(b'f'),
+            b'\r' => escaped.push(b'r'),
+            b if ! (32..=126).contains(&b) => escaped.extend_from_slice(format!("{:03o}", b).as_bytes()),
+            b => escaped.push(b),
+        }
+    }
+
+    string(&escaped)
+}
+
+pub fn chunk_split(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 3);
+
+    let subject = convert::to_bytes(&args[0]);
+    let length = args.get(1).map(convert::to_int).unwrap_or(76);
+    let separator = args.get(2).map(convert::to_bytes).unwrap_or_else(|| "\r\n".into());
+
+    if length < 1 {
+        error::throw("ValueError", "chunk_split(): Argument #2 ($length) must be greater than 0");
+    }
+
+    let mut split = Vec::with_capacity(subject.len() + separator.len() * (subject.len() / length as usize + 1));
+
+    for chunk in subject.chunks(length as usize) {
+        split.extend_from_slice(chunk);
+        split.extend_from_slice(&separator);
+    }
+
+    // The empty string has no chunks, but still gets a separator.
+    if subject.is_empty() {
+        split.extend_from_slice(&separator);
+    }
+
+    string(&split)
+}
+
+pub fn ord(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    Object::Integer(convert::to_bytes(&args[0]).first().copied().unwrap_or(0) as i64)
+}
+
+pub fn chr(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    string(&[convert::to_int(&args[0]).rem_euclid(256) as u8])
+}
+
+/// Replaces the longest matching key at each position, without looking at anything
+/// that's already been replaced. Empty keys are ignored.
+fn translate(subject: &[u8], pairs: &Array) -> Vec<u8> {
+    let mut replacements = HashMap::new();
+
+    for (key, value) in pairs.iter() {
+        let key = match key {
+            Key::Integer(i) => i.to_string().into_bytes(),
+            Key::String(s) => s.to_vec(),
+        };
+
+        if ! key.is_empty() {
+            replacements.insert(key, convert::to_bytes(value));
+        }
+    }
+
+    let longest = replacements.keys().map(Vec::len).max().unwrap_or(0);
+    let shortest = replacements.keys().map(Vec::len).min().unwrap_or(0);
+    let mut translated = Vec::with_capacity(subject.len());
+    let mut i = 0;
+
+    'outer: while i < subject.len() {
+        if shortest > 0 {
+            for length in (shortest..=longest.min(subject.len() - i)).rev() {
+                if let Some(replacement) = replacements.get(&subject[i..i + length]) {
+                    translated.extend_from_slice(replacement);
+                    i += length;
+                    continue 'outer;
+                }
+            }
+        }
+
+        translated.push(subject[i]);
+        i += 1;
+    }
+
+    translated
+}
+
+pub fn strtr(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 3);
+
+    let subject = convert::to_bytes(&args[0]);
+
+    if args.len() == 2 {
+        return match &args[1] {
+            Object::Array(pairs) => string(&translate(&subject, &pairs.borrow())),
+            value => error::throw("TypeError", format!("strtr(): Argument #2 ($from) must be of type array, {} given", value.type_name())),
+        };
+    }
+
+    // Bytes in `$from` without a counterpart in `$to` are left alone.
+    let from = convert::to_bytes(&args[1]);
+    let to = convert::to_bytes(&args[2]);
+    let mut table: Vec<u8> = (0..=255).collect();
+
+    for (f, t) in from.iter().zip(to.iter()) {
+        table[*f as usize] = *t;
+    }
+
+    string(&subject.iter().map(|b| table[*b as usize]).collect::<Vec<u8>>())
+}
+
+/// The part of the subject that `strspn()` and `strcspn()` look at, which is chosen
+/// the same way as `substr()`'s.
+fn span_range(args: &[Object], length: usize) -> (usize, usize) {
+    let length = length as i64;
+
+    let start = match args.get(2).map(convert::to_int).unwrap_or(0) {
+        offset if offset < 0 => (length + offset).max(0),
+        offset => offset.min(length),
+    };
+
+    let end = match args.get(3) {
+        None | Some(Object::Null) => length,
+        Some(count) => match convert::to_int(count) {
+            count if count < 0 => (length + count).max(start),
+            count => length.min(start.saturating_add(count)),
+        },
+    };
+
+    (start as usize, end as usize)
+}
+
+fn span(args: Vec<Object>, accept: bool) -> Object {
+    let subject = convert::to_bytes(&args[0]);
+    let characters = convert::to_bytes(&args[1]);
+    let (start, end) = span_range(&args, subject.len());
+
+    let length = subject[start..end].iter().take_while(|b| characters.contains(b) == accept).count();
+
+    Object::Integer(length as i64)
+}
+
+pub fn strspn(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 4);
+
+    span(args, true)
+}
+
+pub fn strcspn(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 4);
+
+    span(args, false)
+}
+
+pub fn str_word_count(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 3);
+
+    let subject = convert::to_bytes(&args[0]);
+    let format = args.get(1).map(convert::to_int).unwrap_or(0);
+    let mask = match args.get(2) {
+        None | Some(Object::Null) => [false; 256],
+        Some(characters) => character_mask(&convert::to_bytes(characters)),
+    };
+
+    if ! (0..=2).contains(&format) {
+        error::throw("ValueError", "str_word_count(): Argument #2 ($format) must be a valid format value");
+    }
+
+    // Words are letters, apostrophes and hyphens, but the string can't start with an
+    // apostrophe or hyphen or end with a hyphen unless they're in the extra characters.
+    let mut start = 0;
+    let mut end = subject.len();
+
+    if subject.first().is_some_and(|b| matches!(b, b'\'' | b'-') && ! mask[*b as usize]) {
+        start += 1;
+    }
+
+    if end > start && subject[end - 1] == b'-' && ! mask[b'-' as usize] {
+        end -= 1;
+    }
+
+    let mut words = Array::new();
+    let mut count = 0;
+    let mut i = start;
+
+    while i < end {
+        let from = i;
+
+        while i < end && (subject[i].is_ascii_alphabetic() || mask[subject[i] as usize] || matches!(subject[i], b'\'' | b'-')) {
+            i += 1;
+        }
+
+        if i > from {
+            match format {
+                1 => words.push(string(&subject[from..i])),
+                2 => words.insert(Key::Integer(from as i64), string(&subject[from..i])),
+                _ => count += 1,
+            }
+        }
+
+        i += 1;
+    }
+
+    match format {
+        0 => Object::Integer(count),
+        _ => Object::from_array(words),
+    }
+}
+
+pub fn soundex(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    // The digit for each letter, where vowels and H, W and Y are 0.
+    const TABLE: &[u8; 26] = b"01230120022455012623010202";
+
+    let subject = convert::to_bytes(&args[0]);
+
+    if subject.is_empty() {
+        return Object::String(ByteString::new());
+    }
+
+    let mut code = Vec::with_capacity(4);
+    let mut last = 0;
+
+    for b in subject.iter().filter(|b| b.is_ascii_alphabetic()).map(u8::to_ascii_uppercase) {
+        let digit = TABLE[(b - b'A') as usize];
+
+        if code.is_empty() {
+            code.push(b);
+        } else if digit != last && digit != b'0' {
+            code.push(digit);
+        }
+
+        // Letters that don't have a digit still separate repeated ones.
+        last = digit;
+
+        if code.len() == 4 {
+            break;
+        }
+    }
+
+    code.resize(4, b'0');
+
+    string(&code)
+}
+
+/// A port of PHP's `metaphone()`, with the same quirks: vowels are only kept at the
+/// start of a word, and an X can take the result one letter over the limit.
+fn metaphone_of(word: &[u8], limit: usize) -> Vec<u8> {
+    let letter = |i: usize| word.get(i).map_or(0, u8::to_ascii_uppercase);
+    let vowel = |c: u8| matches!(c, b'A' | b'E' | b'I' | b'O' | b'U');
+    let soft = |c: u8| matches!(c, b'E' | b'I' | b'Y');
+
+    let mut code = Vec::new();
+    let mut i = match word.iter().position(u8::is_ascii_alphabetic) {
+        Some(i) => i,
+        None => return code,
+    };
+
+    // Some letters at the start of a word are handled differently.
+    match letter(i) {
+        b'A' if letter(i + 1) == b'E' => {
+            code.push(b'E');
+            i += 2;
+        },
+        b'A' => {
+            code.push(b'A');
+            i += 1;
+        },
+        b'G' | b'K' | b'P' if letter(i + 1) == b'N' => {
+            code.push(b'N');
+            i += 2;
+        },
+        b'W' if letter(i + 1) == b'R' => {
+            code.push(b'R');
+            i += 2;
+        },
+        b'W' if letter(i + 1) == b'H' || vowel(letter(i + 1)) => {
+            code.push(b'W');
+            i += 2;
+        },
+        b'X' => {
+            code.push(b'S');
+            i += 1;
+        },
+        c @ (b'E' | b'I' | b'O' | b'U') => {
+            code.push(c);
+            i += 1;
+        },
+        _ => (),
+    }
+
+    while i < word.len() && (limit == 0 || code.len() < limit) {
+        let current = letter(i);
+        let previous = if i > 0 { letter(i - 1) } else { 0 };
+        let next = letter(i + 1);
+        let after_next = if next != 0 { letter(i + 2) } else { 0 };
+        let back = |n: usize| if i >= n { letter(i - n) } else { 0 };
+        let mut skip = 0;
+
+        // Anything that isn't a letter is ignored, and so are doubled letters other than C.
+        if ! current.is_ascii_alphabetic() || (current == previous && current != b'C') {
+            i += 1;
+            continue;
+        }
+
+        match current {
+            b'B' if previous != b'M' => code.push(b'B'),
+            b'C' if soft(next) => {
+                if next == b'I' && after_next == b'A' {
+                    code.push(b'X');
+                } else if previous != b'S' {
+                    code.push(b'S');
+                }
+            },
+            b'C' if next == b'H' => {
+                code.push(b'X');
+                skip = 1;
+            },
+            b'C' => code.push(b'K'),
+            b'D' if next == b'G' && soft(after_next) => {
+                code.push(b'J');
+                skip = 1;
+            },
+            b'D' => code.push(b'T'),
+            b'G' if next == b'H' && ! (matches!(back(3), b'B' | b'D' | b'H') || back(4) == b'H') => {
+                code.push(b'F');
+                skip = 1;
+            },
+            b'G' if next == b'H' => (),
+            b'G' if next == b'N' => {
+                let silent = ! after_next.is_ascii_alphabetic() || (after_next == b'E' && letter(i + 3) == b'D');
+
+                if ! silent {
+                    code.push(b'K');
+                }
+            },
+            b'G' if soft(next) && previous != b'G' => code.push(b'J'),
+            b'G' => code.push(b'K'),
+            b'H' if vowel(next) && ! matches!(previous, b'C' | b'G' | b'P' | b'S' | b'T') => code.push(b'H'),
+            b'K' if previous != b'C' => code.push(b'K'),
+            b'P' if next == b'H' => code.push(b'F'),
+            b'P' => code.push(b'P'),
+            b'Q' => code.push(b'K'),
+            b'S' if next == b'I' && matches!(after_next, b'O' | b'A') => code.push(b'X'),
+            b'S' if next == b'H' => {
+                code.push(b'X');
+                skip = 1;
+            },
+            b'S' => code.push(b'S'),
+            b'T' if next == b'I' && matches!(after_next, b'O' | b'A') => code.push(b'X'),
+            b'T' if next == b'H' => {
+                code.push(b'0');
+                skip = 1;
+            },
+            b'T' if ! (next == b'C' && after_next == b'H') => code.push(b'T'),
+            b'V' => code.push(b'F'),
+            b'W' | b'Y' if vowel(next) => code.push(current),
+            b'X' => code.extend_from_slice(b"KS"),
+            b'Z' => code.push(b'S'),
+            b'F' | b'J' | b'L' | b'M' | b'N' | b'R' => code.push(current),
+            _ => (),
+        }
+
+        i += 1 + skip;
+    }
+
+    code
+}
+
+pub fn metaphone(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    let subject = convert::to_bytes(&args[0]);
+    let limit = args.get(1).map(convert::to_int).unwrap_or(0);
+
+    if limit < 0 {
+        error::throw("ValueError", "metaphone(): Argument #2 ($max_phonemes) must be greater than or equal to 0");
+    }
+
+    string(&metaphone_of(&subject, limit as usize))
+}
@@ -1,13 +1,45 @@
-use crate::object::Object;
+use crate::object::{Object, convert};
 use crate::vm::Machine;
+use crate::error;
 use super::arity;
 
-pub fn is_string(_: &mut Machine, args: Vec<Object>) -> Object {
-    arity!(args, 1);
+macro_rules! is {
+    ($name:ident, $subject:ident => $check:expr) => {
+        pub fn $name(_: &mut Machine, args: Vec<Object>) -> Object {
+            arity!(args, 1);
+
+            let $subject = args.first().unwrap();
+
+            Object::from_bool($check)
+        }
+    };
+}
+
+is!(is_string, subject => subject.is_string());
+is!(is_int, subject => matches!(subject, Object::Integer(..)));
+is!(is_integer, subject => matches!(subject, Object::Integer(..)));
+is!(is_long, subject => matches!(subject, Object::Integer(..)));
+is!(is_float, subject => matches!(subject, Object::Float(..)));
+is!(is_double, subject => matches!(subject, Object::Float(..)));
+is!(is_bool, subject => matches!(subject, Object::True | Object::False));
+is!(is_array, subject => subject.is_array());
+is!(is_object, subject => subject.is_instance());
+is!(is_resource, subject => matches!(subject, Object::Resource(resource) if ! resource.borrow().is_closed()));
+is!(is_null, subject => matches!(subject, Object::Null));
+is!(is_iterable, subject => subject.is_array());
+is!(is_scalar, subject => matches!(subject, Object::String(..) | Object::Integer(..) | Object::Float(..) | Object::True | Object::False));
+is!(is_numeric, subject => match subject {
+    Object::Integer(..) | Object::Float(..) => true,
+    Object::String(s) => convert::is_numeric(s),
+    _ => false,
+});
+
+pub fn is_callable(vm: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
 
     let subject = args.first().unwrap();
 
-    Object::from_bool(subject.is_string())
+    Object::from_bool(vm.is_callable(subject))
 }
 
 pub fn strval(_: &mut Machine, args: Vec<Object>) -> Object {
@@ -15,5 +47,87 @@ pub fn strval(_: &mut Machine, args: Vec<Object>) -> Object {
 
     let subject = args.first().unwrap();
 
-    Object::String(subject.to_string())
-}
\ No newline at end of file
+    Object::String(convert::to_bytes(subject))
+}
+
+pub fn intval(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    let subject = args.first().unwrap();
+    let base = args.get(1).map(convert::to_int).unwrap_or(10);
+
+    Object::Integer(match subject {
+        Object::String(s) if base != 10 => convert::parse_int(s, base as u32),
+        _ => convert::to_int(subject),
+    })
+}
+
+pub fn floatval(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    let subject = args.first().unwrap();
+
+    Object::Float(convert::to_float(subject))
+}
+
+pub fn doubleval(vm: &mut Machine, args: Vec<Object>) -> Object {
+    floatval(vm, args)
+}
+
+pub fn boolval(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    let subject = args.first().unwrap();
+
+    Object::from_bool(subject.to_bool())
+}
+
+pub fn gettype(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    let subject = args.first().unwrap();
+
+    Object::String(match subject {
+        Object::String(..) => "string",
+        Object::Integer(..) => "integer",
+        Object::Float(..) => "double",
+        Object::True | Object::False => "boolean",
+        Object::Null => "NULL",
+        Object::Array(..) => "array",
+        Object::Instance(..) => "object",
+        Object::Resource(resource) => match resource.borrow().is_closed() {
+            true => "resource (closed)",
+            false => "resource",
+        },
+    }.into())
+}
+
+pub fn get_debug_type(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    let subject = args.first().unwrap();
+
+    Object::String(subject.type_name().into())
+}
+
+pub fn settype(vm: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    let subject = args.first().unwrap();
+    let r#type = convert::to_string(args.get(1).unwrap());
+
+    let value = match r#type.to_lowercase().as_str() {
+        "bool" | "boolean" => Object::from_bool(subject.to_bool()),
+        "int" | "integer" => Object::Integer(convert::to_int(subject)),
+        "float" | "double" => Object::Float(convert::to_float(subject)),
+        "string" => Object::String(convert::to_bytes(subject)),
+        "array" => convert::to_array(subject),
+        "object" => convert::to_instance(subject),
+        "null" => Object::Null,
+        _ => error::throw("ValueError", "settype(): Argument #2 ($type) must be a valid type"),
+    };
+
+    vm.set_reference(0, value);
+
+    Object::True
+}
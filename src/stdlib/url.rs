@@ -0,0 +1,461 @@
+use super::arity;
+use crate::object::{Object, Array, ByteString, Key, convert};
+use crate::vm::Machine;
+use crate::error;
+
+pub const PHP_URL_SCHEME: i64 = 0;
+pub const PHP_URL_HOST: i64 = 1;
+pub const PHP_URL_PORT: i64 = 2;
+pub const PHP_URL_USER: i64 = 3;
+pub const PHP_URL_PASS: i64 = 4;
+pub const PHP_URL_PATH: i64 = 5;
+pub const PHP_URL_QUERY: i64 = 6;
+pub const PHP_URL_FRAGMENT: i64 = 7;
+pub const PHP_QUERY_RFC1738: i64 = 1;
+pub const PHP_QUERY_RFC3986: i64 = 2;
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn string(bytes: &[u8]) -> Object {
+    Object::String(bytes.into())
+}
+
+pub(crate) fn encode_base64(input: &[u8]) -> String {
+    let mut output = String::with_capacity(input.len().div_ceil(3) * 4);
+
+    for chunk in input.chunks(3) {
+        let bytes = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+        let group = (bytes[0] as u32) << 16 | (bytes[1] as u32) << 8 | bytes[2] as u32;
+
+        for i in 0..4 {
+            match i <= chunk.len() {
+                true => output.push(BASE64[(group >> (18 - i * 6) & 63) as usize] as char),
+                false => output.push('='),
+            }
+        }
+    }
+
+    output
+}
+
+/// Decodes base64. In strict mode anything outside the alphabet, misplaced padding or
+/// a dangling character fails, while otherwise they're skipped over like PHP does.
+pub(crate) fn decode_base64(input: &[u8], strict: bool) -> Option<Vec<u8>> {
+    let mut output = Vec::with_capacity(input.len() / 4 * 3);
+    let mut accumulator = 0u32;
+    let mut bits = 0;
+    let mut padding = 0;
+
+    for c in input.iter() {
+        let value = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            b'=' => {
+                padding += 1;
+                continue;
+            },
+            _ if strict => return None,
+            _ => continue,
+        };
+
+        if padding > 0 && strict {
+            return None;
+        }
+
+        accumulator = (accumulator << 6) | value as u32;
+        bits += 6;
+
+        if bits >= 8 {
+            bits -= 8;
+            output.push((accumulator >> bits) as u8);
+        }
+    }
+
+    match strict && (bits >= 6 || padding > 2) {
+        true => None,
+        false => Some(output),
+    }
+}
+
+/// Decodes %XX escapes, and '+' as a space unless `raw` is set.
+pub(crate) fn decode_url(input: &[u8], raw: bool) -> Vec<u8> {
+    let mut output = Vec::with_capacity(input.len());
+    let mut i = 0;
+
+    while i < input.len() {
+        let hex = input.get(i + 1..i + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+
+        match (input[i], hex) {
+            (b'%', Some(byte)) => {
+                output.push(byte);
+                i += 3;
+            },
+            (b'+', _) if ! raw => {
+                output.push(b' ');
+                i += 1;
+            },
+            (c, _) => {
+                output.push(c);
+                i += 1;
+            },
+        }
+    }
+
+    output
+}
+
+/// Escapes everything but letters, digits and "-_.", with spaces as '+' unless `raw`
+/// is set, in which case '~' is also left alone (RFC 3986).
+fn encode_url(input: &[u8], raw: bool) -> String {
+    let mut output = String::with_capacity(input.len());
+
+    for &c in input {
+        match c {
+            b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'-' | b'_' | b'.' => output.push(c as char),
+            b'~' if raw => output.push('~'),
+            b' ' if ! raw => output.push('+'),
+            c => output.push_str(&format!("%{:02X}", c)),
+        }
+    }
+
+    output
+}
+
+pub fn base64_encode(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    Object::String(encode_base64(&convert::to_bytes(&args[0])).into())
+}
+
+pub fn base64_decode(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    let strict = args.get(1).is_some_and(Object::to_bool);
+
+    match decode_base64(&convert::to_bytes(&args[0]), strict) {
+        Some(bytes) => string(&bytes),
+        None => Object::False,
+    }
+}
+
+pub fn urlencode(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    Object::String(encode_url(&convert::to_bytes(&args[0]), false).into())
+}
+
+pub fn rawurlencode(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    Object::String(encode_url(&convert::to_bytes(&args[0]), true).into())
+}
+
+pub fn urldecode(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    string(&decode_url(&convert::to_bytes(&args[0]), false))
+}
+
+pub fn rawurldecode(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    string(&decode_url(&convert::to_bytes(&args[0]), true))
+}
+
+/// Builds the pairs for one level of `http_build_query()`. Nested arrays (and objects)
+/// become `parent[key]=value`, with the brackets encoded.
+fn build_query(pairs: &mut Vec<String>, items: &Array, parent: Option<&str>, prefix: &str, raw: bool) {
+    for (key, value) in items.iter() {
+        let key = match (key, parent) {
+            (Key::Integer(i), None) => format!("{}{}", prefix, i),
+            (Key::String(s), None) => encode_url(s.as_bytes(), raw),
+            (key, Some(parent)) => format!("{}%5B{}%5D", parent, encode_url(&convert::to_bytes(&key.to_object()), raw)),
+        };
+
+        match value {
+            Object::Null => (),
+            Object::Array(children) => build_query(pairs, &children.borrow(), Some(&key), prefix, raw),
+            Object::Instance(instance) => build_query(pairs, &instance.borrow().properties, Some(&key), prefix, raw),
+            Object::True => pairs.push(format!("{}=1", key)),
+            Object::False => pairs.push(format!("{}=0", key)),
+            value => pairs.push(format!("{}={}", key, encode_url(&convert::to_bytes(value), raw))),
+        }
+    }
+}
+
+pub fn http_build_query(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 4);
+
+    let items = match &args[0] {
+        Object::Array(items) => items.borrow().clone(),
+        Object::Instance(instance) => instance.borrow().properties.clone(),
+        value => error::throw("TypeError", format!("http_build_query(): Argument #1 ($data) must be of type array, {} given", value.type_name())),
+    };
+
+    let prefix = args.get(1).map(convert::to_string).unwrap_or_default();
+    let separator = match args.get(2) {
+        Some(Object::Null) | None => "&".into(),
+        Some(separator) => convert::to_string(separator),
+    };
+    let raw = args.get(3).map(convert::to_int).unwrap_or(PHP_QUERY_RFC1738) == PHP_QUERY_RFC3986;
+
+    let mut pairs = Vec::new();
+
+    build_query(&mut pairs, &items, None, &prefix, raw);
+
+    Object::String(pairs.join(&separator).into())
+}
+
+/// The parts of a URL, as found by `parse_url()`.
+#[derive(Default)]
+struct Url {
+    scheme: Option<String>,
+    host: Option<String>,
+    port: Option<i64>,
+    user: Option<String>,
+    pass: Option<String>,
+    path: Option<String>,
+    query: Option<String>,
+    fragment: Option<String>,
+}
+
+/// Splits a URL into its parts the same way `php_url_parse_ex2()` does, which is
+/// forgiving about most things but rejects bad ports and empty hosts.
+fn parse(url: &str) -> Option<Url> {
+    let mut parsed = Url::default();
+    let mut rest = url;
+
+    // A scheme is letters, digits, '+', '-' and '.' before a ':', unless what
+    // follows looks like a port ("localhost:8080").
+    if let Some(colon) = url.find(':') {
+        let scheme = &url[..colon];
+        let after = &url[colon + 1..];
+        let valid = ! scheme.is_empty() && scheme.bytes().all(|c| c.is_ascii_alphanumeric() || matches!(c, b'+' | b'-' | b'.'));
+        let port = after.bytes().take_while(u8::is_ascii_digit).count();
+        let port_like = port > 0 && port <= 5 && matches!(after.as_bytes().get(port), None | Some(b'/'));
+
+        if valid && ! port_like {
+            parsed.scheme = Some(scheme.into());
+            rest = after;
+        } else if valid && port_like {
+            // "host:port/path" without a scheme.
+            parsed.host = Some(scheme.into());
+            parsed.port = Some(after[..port].parse().ok().filter(|port| *port <= 65535)?);
+
+            return path(parsed, &after[port..]);
+        }
+    }
+
+    if let Some(authority) = rest.strip_prefix("//") {
+        let end = authority.find(['/', '?', '#']).unwrap_or(authority.len());
+        let mut host = &authority[..end];
+
+        rest = &authority[end..];
+
+        if let Some(at) = host.rfind('@') {
+            let userinfo = &host[..at];
+
+            match userinfo.split_once(':') {
+                Some((user, pass)) => {
+                    parsed.user = Some(user.into());
+                    parsed.pass = Some(pass.into());
+                },
+                None => parsed.user = Some(userinfo.into()),
+            }
+
+            host = &host[at + 1..];
+        }
+
+        // The port comes after the last ':', except inside an IPv6 address.
+        let port_start = match host.rfind(':') {
+            Some(colon) if ! host[colon..].contains(']') => Some(colon),
+            _ => None,
+        };
+
+        if let Some(colon) = port_start {
+            let port = &host[colon + 1..];
+
+            if ! port.is_empty() {
+                match port.parse::<i64>() {
+                    Ok(port) if (0..=65535).contains(&port) => parsed.port = Some(port),
+                    _ => return None,
+                }
+            }
+
+            host = &host[..colon];
+        }
+
+        if host.is_empty() {
+            // "file:///path" is the only way to leave the host out.
+            if parsed.scheme.as_deref() != Some("file") || parsed.user.is_some() || parsed.port.is_some() {
+                return None;
+            }
+        } else {
+            parsed.host = Some(host.into());
+        }
+    }
+
+    path(parsed, rest)
+}
+
+fn path(mut parsed: Url, rest: &str) -> Option<Url> {
+    let (rest, fragment) = match rest.split_once('#') {
+        Some((rest, fragment)) => (rest, Some(fragment.to_string())),
+        None => (rest, None),
+    };
+
+    let (path, query) = match rest.split_once('?') {
+        Some((path, query)) => (path, Some(query.to_string())),
+        None => (rest, None),
+    };
+
+    if ! path.is_empty() {
+        parsed.path = Some(path.into());
+    }
+
+    parsed.query = query;
+    parsed.fragment = fragment;
+
+    Some(parsed)
+}
+
+pub fn parse_url(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    let url = match parse(&convert::to_string(&args[0])) {
+        Some(url) => url,
+        None => return Object::False,
+    };
+
+    let text = |part: Option<String>| part.map_or(Object::Null, |part| Object::String(part.into()));
+
+    match args.get(1).map(convert::to_int).unwrap_or(-1) {
+        -1 => {
+            let mut parts = Array::new();
+            let fields = [
+                ("scheme", text(url.scheme)),
+                ("host", text(url.host)),
+                ("port", url.port.map_or(Object::Null, Object::Integer)),
+                ("user", text(url.user)),
+                ("pass", text(url.pass)),
+                ("path", text(url.path)),
+                ("query", text(url.query)),
+                ("fragment", text(url.fragment)),
+            ];
+
+            for (name, value) in fields {
+                if ! matches!(value, Object::Null) {
+                    parts.insert(Key::String(name.into()), value);
+                }
+            }
+
+            Object::from_array(parts)
+        },
+        PHP_URL_SCHEME => text(url.scheme),
+        PHP_URL_HOST => text(url.host),
+        PHP_URL_PORT => url.port.map_or(Object::Null, Object::Integer),
+        PHP_URL_USER => text(url.user),
+        PHP_URL_PASS => text(url.pass),
+        PHP_URL_PATH => text(url.path),
+        PHP_URL_QUERY => text(url.query),
+        PHP_URL_FRAGMENT => text(url.fragment),
+        component => error::throw("ValueError", format!("parse_url(): Argument #2 ($component) must be a valid URL component identifier, {} given", component)),
+    }
+}
+
+/// Stores a value under a chain of keys from a query string like `a[b][]=1`, where
+/// `None` stands for `[]` (append).
+fn assign(items: &mut Array, keys: &[Option<ByteString>], value: Object) {
+    let key = keys[0].as_ref().map(|key| Key::from(key.as_bytes()));
+
+    if keys.len() == 1 {
+        match key {
+            Some(key) => items.insert(key, value),
+            None => items.push(value),
+        }
+
+        return;
+    }
+
+    let child = match key.as_ref().and_then(|key| items.get(key)) {
+        Some(Object::Array(child)) => child.clone(),
+        _ => {
+            let child = Object::from_array(Array::new());
+
+            match key {
+                Some(key) => items.insert(key, child.clone()),
+                None => items.push(child.clone()),
+            }
+
+            child.to_hash().clone()
+        },
+    };
+
+    assign(&mut child.borrow_mut(), &keys[1..], value);
+}
+
+pub fn parse_str(vm: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    let query = convert::to_bytes(&args[0]);
+    let mut result = Array::new();
+
+    for pair in query.split(|b| *b == b'&').filter(|pair| ! pair.is_empty()) {
+        let (name, value) = match pair.iter().position(|b| *b == b'=') {
+            Some(equals) => (&pair[..equals], &pair[equals + 1..]),
+            None => (pair, &b""[..]),
+        };
+
+        let name = decode_url(name, false);
+        let value = decode_url(value, false);
+        let name = &name[name.iter().position(|b| *b != b' ').unwrap_or(name.len())..];
+
+        // The base name has its spaces and dots turned into underscores, as does a '['
+        // that's never closed.
+        let (base, mut rest) = match name.iter().position(|b| *b == b'[') {
+            Some(open) if name[open..].contains(&b']') => (&name[..open], &name[open..]),
+            _ => (name, &b""[..]),
+        };
+
+        if base.is_empty() {
+            continue;
+        }
+
+        let mut base = base.to_vec();
+
+        for b in base.iter_mut().filter(|b| matches!(b, b' ' | b'.')) {
+            *b = b'_';
+        }
+
+        if let Some(open) = base.iter().position(|b| *b == b'[') {
+            base[open] = b'_';
+        }
+
+        let mut keys = vec![Some(ByteString::from(base))];
+
+        while let Some(inner) = rest.strip_prefix(b"[") {
+            let close = match inner.iter().position(|b| *b == b']') {
+                Some(close) => close,
+                None => break,
+            };
+
+            keys.push(match &inner[..close] {
+                [] => None,
+                key => Some(key.into()),
+            });
+
+            rest = &inner[close + 1..];
+        }
+
+        assign(&mut result, &keys, Object::String(value.into()));
+    }
+
+    vm.set_reference(1, Object::from_array(result));
+
+    Object::Null
+}
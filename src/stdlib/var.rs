@@ -0,0 +1,589 @@
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::io::{self, Write};
+use super::arity;
+use crate::object::{Object, Array, ByteString, Key, new_instance, convert};
+use crate::vm::Machine;
+use crate::error;
+
+/// The built-in classes that `unserialize()` can recreate. Anything else becomes a
+/// `__PHP_Incomplete_Class` that remembers the original name.
+const CLASSES: [&str; 5] = ["stdClass", "DateTime", "DateTimeImmutable", "DateTimeZone", "DateInterval"];
+
+const INCOMPLETE_CLASS: &str = "__PHP_Incomplete_Class";
+const INCOMPLETE_CLASS_NAME: &str = "__PHP_Incomplete_Class_Name";
+
+pub fn var_dump(_: &mut Machine, args: Vec<Object>) -> Object {
+    let mut stdout = io::stdout();
+
+    for arg in args {
+        let _ = stdout.write_all(&arg.dump());
+        let _ = stdout.write_all(b"\n");
+    }
+
+    Object::Null
+}
+
+/// A port of `zend_print_zval_r_to_buf()`. Nested arrays and objects are indented by
+/// eight spaces a level, with their elements four spaces further in.
+fn print_r_into(buffer: &mut ByteString, value: &Object, indent: usize, seen: &mut Vec<usize>) {
+    match value {
+        Object::Array(items) => {
+            buffer.push_str("Array\n");
+            print_r_elements(buffer, &items.borrow(), indent, seen);
+        },
+        Object::Instance(instance) => {
+            let instance = instance.borrow();
+
+            buffer.push_str(&format!("{} Object\n", instance.class));
+
+            if seen.contains(&instance.id) {
+                buffer.push_str(" *RECURSION*");
+                return;
+            }
+
+            seen.push(instance.id);
+            print_r_elements(buffer, &instance.properties, indent, seen);
+            seen.pop();
+        },
+        value => buffer.extend_from_slice(&convert::to_bytes(value)),
+    }
+}
+
+fn print_r_elements(buffer: &mut ByteString, items: &Array, indent: usize, seen: &mut Vec<usize>) {
+    buffer.push_str(&" ".repeat(indent));
+    buffer.push_str("(\n");
+
+    for (key, value) in items.iter() {
+        buffer.push_str(&" ".repeat(indent + 4));
+        buffer.push(b'[');
+
+        match key {
+            Key::Integer(i) => buffer.push_str(&i.to_string()),
+            Key::String(s) => buffer.extend_from_slice(s),
+        }
+
+        buffer.push_str("] => ");
+        print_r_into(buffer, value, indent + 8, seen);
+        buffer.push(b'\n');
+    }
+
+    buffer.push_str(&" ".repeat(indent));
+    buffer.push_str(")\n");
+}
+
+pub fn print_r(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    let mut buffer = ByteString::new();
+
+    print_r_into(&mut buffer, &args[0], 0, &mut Vec::new());
+
+    match args.get(1).is_some_and(Object::to_bool) {
+        true => Object::String(buffer),
+        false => {
+            let _ = io::stdout().write_all(&buffer);
+            Object::True
+        },
+    }
+}
+
+/// Quotes a string for `var_export()`. Null bytes can't appear in a single quoted
+/// literal, so they're concatenated in from a double quoted one.
+fn export_string(buffer: &mut ByteString, s: &[u8]) {
+    buffer.push(b'\'');
+
+    for &c in s {
+        match c {
+            b'\\' => buffer.push_str("\\\\"),
+            b'\'' => buffer.push_str("\\'"),
+            b'\0' => buffer.push_str("' . \"\\0\" . '"),
+            c => buffer.push(c),
+        }
+    }
+
+    buffer.push(b'\'');
+}
+
+/// A port of `php_var_export_ex()`. `level` starts at 1, and nested arrays and objects
+/// go on their own line, indented by `level - 1` spaces.
+fn var_export_into(buffer: &mut ByteString, value: &Object, level: usize, seen: &mut Vec<usize>) {
+    match value {
+        Object::Null | Object::Resource(..) => buffer.push_str("NULL"),
+        Object::True => buffer.push_str("true"),
+        Object::False => buffer.push_str("false"),
+        // The smallest integer can't be written as a literal, since `-` is an operator.
+        Object::Integer(i64::MIN) => buffer.push_str(&format!("{}-1", i64::MIN + 1)),
+        Object::Integer(i) => buffer.push_str(&i.to_string()),
+        Object::Float(f) => {
+            let mut number = convert::format_float(*f, convert::SERIALIZE_PRECISION);
+
+            // Whole numbers keep a ".0" so that they're read back as floats.
+            if f.is_finite() && ! number.contains(['.', 'e', 'E']) {
+                number.push_str(".0");
+            }
+
+            buffer.push_str(&number);
+        },
+        Object::String(s) => export_string(buffer, s),
+        Object::Array(items) => {
+            if level > 1 {
+                buffer.push(b'\n');
+                buffer.push_str(&" ".repeat(level - 1));
+            }
+
+            buffer.push_str("array (\n");
+
+            for (key, value) in items.borrow().iter() {
+                buffer.push_str(&" ".repeat(level + 1));
+
+                match key {
+                    Key::Integer(i) => buffer.push_str(&i.to_string()),
+                    Key::String(s) => export_string(buffer, s),
+                }
+
+                buffer.push_str(" => ");
+                var_export_into(buffer, value, level + 2, seen);
+                buffer.push_str(",\n");
+            }
+
+            if level > 1 {
+                buffer.push_str(&" ".repeat(level - 1));
+            }
+
+            buffer.push(b')');
+        },
+        Object::Instance(instance) => {
+            let instance = instance.borrow();
+
+            if seen.contains(&instance.id) {
+                error::warning("var_export does not handle circular references");
+                buffer.push_str("NULL");
+                return;
+            }
+
+            if level > 1 {
+                buffer.push(b'\n');
+                buffer.push_str(&" ".repeat(level - 1));
+            }
+
+            let standard = instance.class == "stdClass";
+
+            match standard {
+                true => buffer.push_str("(object) array(\n"),
+                false => buffer.push_str(&format!("\\{}::__set_state(array(\n", instance.class)),
+            }
+
+            seen.push(instance.id);
+
+            for (key, value) in instance.properties.iter() {
+                buffer.push_str(&" ".repeat(level + 2));
+
+                match key {
+                    Key::Integer(i) => buffer.push_str(&i.to_string()),
+                    Key::String(s) => export_string(buffer, s),
+                }
+
+                buffer.push_str(" => ");
+                var_export_into(buffer, value, level + 2, seen);
+                buffer.push_str(",\n");
+            }
+
+            seen.pop();
+
+            if level > 1 {
+                buffer.push_str(&" ".repeat(level - 1));
+            }
+
+            buffer.push_str(if standard { ")" } else { "))" });
+        },
+    }
+}
+
+pub fn var_export(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    let mut buffer = ByteString::new();
+
+    var_export_into(&mut buffer, &args[0], 1, &mut Vec::new());
+
+    match args.get(1).is_some_and(Object::to_bool) {
+        true => Object::String(buffer),
+        false => {
+            let _ = io::stdout().write_all(&buffer);
+            Object::Null
+        },
+    }
+}
+
+/// A port of `php_var_serialize_intern()`. Every value is numbered as it's written,
+/// and an object that has already been written is replaced with an `r:` back-reference
+/// to its number, so that `unserialize()` gives back the same instance.
+#[derive(Default)]
+struct Serializer {
+    buffer: ByteString,
+    count: usize,
+    objects: HashMap<usize, usize>,
+}
+
+impl Serializer {
+    fn string(&mut self, s: &[u8]) {
+        self.buffer.push_str(&format!("s:{}:\"", s.len()));
+        self.buffer.extend_from_slice(s);
+        self.buffer.push_str("\";");
+    }
+
+    fn key(&mut self, key: &Key) {
+        match key {
+            Key::Integer(i) => self.buffer.push_str(&format!("i:{};", i)),
+            Key::String(s) => self.string(s),
+        }
+    }
+
+    fn elements(&mut self, items: &Array) {
+        self.buffer.push_str(&format!("{}:{{", items.len()));
+
+        for (key, value) in items.iter() {
+            self.key(key);
+            self.value(value);
+        }
+
+        self.buffer.push(b'}');
+    }
+
+    fn value(&mut self, value: &Object) {
+        self.count += 1;
+
+        match value {
+            Object::Null => self.buffer.push_str("N;"),
+            Object::True => self.buffer.push_str("b:1;"),
+            Object::False => self.buffer.push_str("b:0;"),
+            Object::Integer(i) => self.buffer.push_str(&format!("i:{};", i)),
+            Object::Float(f) => self.buffer.push_str(&format!("d:{};", convert::format_float(*f, convert::SERIALIZE_PRECISION))),
+            Object::String(s) => self.string(s),
+            Object::Resource(..) => self.buffer.push_str("i:0;"),
+            Object::Array(items) => {
+                self.buffer.push_str("a:");
+                self.elements(&items.borrow());
+            },
+            Object::Instance(instance) => {
+                let instance = instance.borrow();
+
+                if let Some(number) = self.objects.get(&instance.id) {
+                    self.buffer.push_str(&format!("r:{};", number));
+                    return;
+                }
+
+                self.objects.insert(instance.id, self.count);
+
+                // An incomplete class is written back out under its original name.
+                let name = Key::from(INCOMPLETE_CLASS_NAME);
+
+                match instance.properties.get(&name) {
+                    Some(Object::String(class)) if instance.class == INCOMPLETE_CLASS => {
+                        let properties = instance.properties.iter()
+                            .filter(|(key, _)| **key != name)
+                            .map(|(key, value)| (key.clone(), value.clone()))
+                            .collect();
+
+                        self.buffer.push_str(&format!("O:{}:\"{}\":", class.len(), class));
+                        self.elements(&properties);
+                    },
+                    _ => {
+                        self.buffer.push_str(&format!("O:{}:\"{}\":", instance.class.len(), instance.class));
+                        self.elements(&instance.properties);
+                    },
+                }
+            },
+        }
+    }
+}
+
+pub fn serialize(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 1);
+
+    let mut serializer = Serializer::default();
+
+    serializer.value(&args[0]);
+
+    Object::String(serializer.buffer)
+}
+
+/// Which classes `unserialize()` is allowed to recreate, from its `allowed_classes` option.
+enum Allowed {
+    All,
+    None,
+    Only(Vec<String>),
+}
+
+impl Allowed {
+    fn contains(&self, class: &str) -> bool {
+        match self {
+            Allowed::All => true,
+            Allowed::None => false,
+            Allowed::Only(classes) => classes.iter().any(|allowed| allowed.eq_ignore_ascii_case(class)),
+        }
+    }
+}
+
+/// A port of `php_var_unserialize()`. `mark` is the end of the last thing that was read
+/// successfully, which is where errors are reported, and `values` holds everything that's
+/// been read so far for `r:` and `R:` back-references to refer to.
+struct Unserializer<'a> {
+    input: &'a [u8],
+    position: usize,
+    mark: usize,
+    values: Vec<Object>,
+    allowed: Allowed,
+}
+
+impl<'a> Unserializer<'a> {
+    fn new(input: &'a [u8], allowed: Allowed) -> Self {
+        Self { input, position: 0, mark: 0, values: Vec::new(), allowed }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.input.get(self.position).copied()
+    }
+
+    fn expect(&mut self, byte: u8) -> Option<()> {
+        match self.peek() == Some(byte) {
+            true => {
+                self.position += 1;
+                Some(())
+            },
+            false => None,
+        }
+    }
+
+    /// Reads everything up to (but not including) `terminator`.
+    fn until(&mut self, terminator: u8) -> Option<&'a str> {
+        let input: &'a [u8] = self.input;
+        let length = input[self.position..].iter().position(|b| *b == terminator)?;
+        let text = std::str::from_utf8(&input[self.position..self.position + length]).ok()?;
+
+        self.position += length + 1;
+        Some(text)
+    }
+
+    fn integer(&mut self, terminator: u8) -> Option<i64> {
+        let text = self.until(terminator)?;
+        let digits = text.strip_prefix(['+', '-']).unwrap_or(text);
+
+        if digits.is_empty() || ! digits.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+
+        text.strip_prefix('+').unwrap_or(text).parse().ok()
+    }
+
+    fn length(&mut self) -> Option<usize> {
+        usize::try_from(self.integer(b':')?).ok()
+    }
+
+    fn float(&mut self) -> Option<f64> {
+        match self.until(b';')? {
+            "INF" => Some(f64::INFINITY),
+            "-INF" => Some(f64::NEG_INFINITY),
+            "NAN" => Some(f64::NAN),
+            text if text.bytes().all(|b| b.is_ascii_digit() || matches!(b, b'+' | b'-' | b'.' | b'e' | b'E')) => text.parse().ok(),
+            _ => None,
+        }
+    }
+
+    /// Reads a `"`-quoted string of exactly `length` bytes. `S:` strings spell some of
+    /// their bytes as `\` and two hex digits, so they're longer in the input.
+    fn string(&mut self, length: usize, escaped: bool) -> Option<ByteString> {
+        self.expect(b'"')?;
+
+        let mut bytes = ByteString::new();
+
+        while bytes.len() < length {
+            match self.peek()? {
+                b'\\' if escaped => {
+                    let hex = self.input.get(self.position + 1..self.position + 3)?;
+                    bytes.push(u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok()?);
+                    self.position += 3;
+                },
+                byte => {
+                    bytes.push(byte);
+                    self.position += 1;
+                },
+            }
+        }
+
+        self.expect(b'"')?;
+
+        Some(bytes)
+    }
+
+    /// Reads an array key, which doesn't count as a value for back-references.
+    fn key(&mut self) -> Option<Key> {
+        let kind = self.peek()?;
+        self.position += 1;
+        self.expect(b':')?;
+
+        let key = match kind {
+            b'i' => Key::Integer(self.integer(b';')?),
+            b's' => {
+                let length = self.length()?;
+                let key = Key::from(self.string(length, false)?.as_bytes());
+                self.expect(b';')?;
+                key
+            },
+            _ => return None,
+        };
+
+        self.mark = self.position;
+        Some(key)
+    }
+
+    fn elements(&mut self, count: usize) -> Option<Array> {
+        self.expect(b'{')?;
+        self.mark = self.position;
+
+        let mut items = Array::new();
+
+        for _ in 0..count {
+            let key = self.key()?;
+            let value = self.value()?;
+
+            items.insert(key, value);
+        }
+
+        self.expect(b'}')?;
+        self.mark = self.position;
+
+        Some(items)
+    }
+
+    fn value(&mut self) -> Option<Object> {
+        let kind = self.peek()?;
+        let start = self.position;
+
+        if kind == b'N' {
+            self.position += 1;
+            self.expect(b';')?;
+            self.mark = self.position;
+            self.values.push(Object::Null);
+            return Some(Object::Null);
+        }
+
+        self.position += 1;
+        self.expect(b':')?;
+
+        // The slot is reserved before anything nested is read, so that back-references
+        // are numbered in the order the values start.
+        let slot = self.values.len();
+
+        if kind != b'R' {
+            self.values.push(Object::Null);
+        }
+
+        let value = match kind {
+            b'b' => match self.until(b';')? {
+                "0" => Object::False,
+                "1" => Object::True,
+                _ => return None,
+            },
+            b'i' => Object::Integer(self.integer(b';')?),
+            b'd' => Object::Float(self.float()?),
+            b's' | b'S' => {
+                let length = self.length()?;
+
+                // PHP reports a string that doesn't fit just after its "s:".
+                self.mark = start + 2;
+
+                let string = self.string(length, kind == b'S')?;
+                self.expect(b';')?;
+                Object::String(string)
+            },
+            b'r' | b'R' => {
+                let number = usize::try_from(self.integer(b';')?).ok()?;
+
+                match self.values.get(number.checked_sub(1)?)? {
+                    Object::Array(items) => Object::from_array(items.borrow().clone()),
+                    value => value.clone(),
+                }
+            },
+            b'a' => {
+                let count = self.length()?;
+                Object::from_array(self.elements(count)?)
+            },
+            b'O' => {
+                let length = self.length()?;
+                let class = self.string(length, false)?;
+                self.expect(b':')?;
+                let count = self.length()?;
+
+                let known = CLASSES.iter().find(|known| known.as_bytes().eq_ignore_ascii_case(&class));
+                let object = match known {
+                    Some(known) if self.allowed.contains(known) => new_instance(*known, Array::new()),
+                    _ => {
+                        let mut properties = Array::new();
+                        properties.insert(Key::from(INCOMPLETE_CLASS_NAME), Object::String(class));
+                        new_instance(INCOMPLETE_CLASS, properties)
+                    },
+                };
+
+                // The object goes in its slot first, so that its properties can refer back to it.
+                self.values[slot] = object.clone();
+
+                let properties = self.elements(count)?;
+
+                if let Object::Instance(instance) = &object {
+                    let mut instance = instance.borrow_mut();
+
+                    for (key, value) in properties {
+                        instance.properties.insert(key, value);
+                    }
+                }
+
+                object
+            },
+            _ => return None,
+        };
+
+        if kind != b'R' {
+            self.values[slot] = value.clone();
+        }
+
+        self.mark = self.position;
+        Some(value)
+    }
+}
+
+pub fn unserialize(_: &mut Machine, args: Vec<Object>) -> Object {
+    arity!(args, 2);
+
+    let input = convert::to_bytes(&args[0]);
+
+    let allowed = match args.get(1) {
+        Some(Object::Array(options)) => match options.borrow().get(&Key::from("allowed_classes")) {
+            None | Some(Object::True) => Allowed::All,
+            Some(Object::False) => Allowed::None,
+            Some(Object::Array(classes)) => Allowed::Only(classes.borrow().values().map(convert::to_string).collect()),
+            Some(other) => error::throw("TypeError", format!("unserialize(): Option \"allowed_classes\" must be an array or of type bool, {} given", other.type_name())),
+        },
+        _ => Allowed::All,
+    };
+
+    let mut unserializer = Unserializer::new(&input, allowed);
+
+    match unserializer.value() {
+        Some(value) => {
+            if unserializer.position < input.len() {
+                error::warning(format!("unserialize(): Extra data starting at offset {} of {} bytes", unserializer.position, input.len()));
+            }
+
+            value
+        },
+        None => {
+            if ! input.is_empty() {
+                error::warning(format!("unserialize(): Error at offset {} of {} bytes", unserializer.mark, input.len()));
+            }
+
+            Object::False
+        },
+    }
+}
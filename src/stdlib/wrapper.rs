@@ -0,0 +1,246 @@
+use super::{Handle, decode_base64, decode_url, reason};
+use indexmap::IndexMap;
+use std::fmt::{Debug, Formatter, Result as FormatResult};
+use std::fs::OpenOptions;
+use std::io::{self, Cursor, Read, Seek, SeekFrom, Write};
+use std::rc::Rc;
+
+/// Opens the URLs for one scheme, like `php://memory` or `data:`. Embedders can add
+/// their own with `Globals::register_wrapper()`, e.g. to give tests an in-memory
+/// filesystem.
+pub trait Wrapper {
+    /// Opens `url` (scheme included) with an `fopen()` mode. The error is the reason
+    /// shown after "Failed to open stream:" in the warning.
+    fn open(&self, url: &str, mode: &str) -> Result<Box<dyn Handle>, String>;
+}
+
+/// The registered wrappers, keyed by scheme in the order they were registered.
+#[derive(Default)]
+pub struct Wrappers(IndexMap<String, Rc<dyn Wrapper>>);
+
+impl Wrappers {
+    pub fn register(&mut self, scheme: &str, wrapper: Rc<dyn Wrapper>) {
+        self.0.insert(scheme.to_lowercase(), wrapper);
+    }
+
+    pub fn get(&self, scheme: &str) -> Option<Rc<dyn Wrapper>> {
+        self.0.get(&scheme.to_lowercase()).cloned()
+    }
+
+    pub fn schemes(&self) -> impl Iterator<Item = &String> {
+        self.0.keys()
+    }
+}
+
+impl Debug for Wrappers {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FormatResult {
+        f.debug_list().entries(self.0.keys()).finish()
+    }
+}
+
+/// Splits the scheme off a URL, the same way `php_stream_locate_url_wrapper()` does.
+/// `data:` is special-cased because RFC 2397 URLs don't need the slashes.
+pub(crate) fn scheme(url: &str) -> Option<&str> {
+    let end = url.find(|c: char| ! (c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.')))?;
+
+    match &url[end..] {
+        rest if rest.starts_with("://") && end > 0 => Some(&url[..end]),
+        rest if rest.starts_with(':') && url[..end].eq_ignore_ascii_case("data") => Some(&url[..end]),
+        _ => None,
+    }
+}
+
+fn bad_descriptor() -> io::Error {
+    io::Error::from_raw_os_error(9)
+}
+
+/// An in-memory stream, used for `php://memory`, `php://temp` and `data:` URLs.
+pub struct Memory {
+    data: Cursor<Vec<u8>>,
+    writable: bool,
+    append: bool,
+}
+
+impl Memory {
+    pub fn new(data: Vec<u8>, writable: bool) -> Self {
+        Self { data: Cursor::new(data), writable, append: false }
+    }
+
+    /// Opens a memory stream the way `php_stream_mode_from_str()` reads the mode, where
+    /// a plain "r" makes the stream read-only.
+    fn with_mode(mode: &str) -> Self {
+        let mut memory = Self::new(Vec::new(), mode.contains(['w', '+', 'a', 'x', 'c']));
+        memory.append = mode.contains('a');
+
+        memory
+    }
+}
+
+impl Handle for Memory {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        self.data.read(buffer)
+    }
+
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        if ! self.writable {
+            return Err(bad_descriptor());
+        }
+
+        if self.append {
+            self.data.seek(SeekFrom::End(0))?;
+        }
+
+        self.data.write_all(bytes).map(|_| bytes.len())
+    }
+
+    fn seek(&mut self, position: SeekFrom) -> io::Result<u64> {
+        let target = match position {
+            SeekFrom::Start(offset) => offset as i64,
+            SeekFrom::Current(offset) => self.data.position() as i64 + offset,
+            SeekFrom::End(offset) => self.data.get_ref().len() as i64 + offset,
+        };
+
+        // Memory streams can't be seeked past their end.
+        if target < 0 || target as usize > self.data.get_ref().len() {
+            return Err(io::Error::from(io::ErrorKind::InvalidInput));
+        }
+
+        self.data.seek(SeekFrom::Start(target as u64))
+    }
+
+    fn truncate(&mut self, size: u64) -> io::Result<()> {
+        if ! self.writable {
+            return Err(bad_descriptor());
+        }
+
+        self.data.get_mut().resize(size as usize, 0);
+
+        Ok(())
+    }
+}
+
+/// `file://` URLs, and any path that doesn't have a scheme.
+pub struct FileWrapper;
+
+impl FileWrapper {
+    /// Turns an `fopen()` mode like "r+" or "wb" into the options to open a file with.
+    fn options(mode: &str) -> Option<OpenOptions> {
+        let mut flags = mode.bytes().filter(|c| ! matches!(c, b'b' | b't' | b'e'));
+        let kind = flags.next()?;
+        let plus = match flags.next() {
+            Some(b'+') => true,
+            Some(_) => return None,
+            None => false,
+        };
+
+        let mut options = OpenOptions::new();
+        options.read(plus);
+
+        match kind {
+            b'r' => options.read(true).write(plus),
+            b'w' => options.write(true).create(true).truncate(true),
+            b'a' => options.append(true).create(true),
+            b'x' => options.write(true).create_new(true),
+            b'c' => options.write(true).create(true),
+            _ => return None,
+        };
+
+        Some(options)
+    }
+}
+
+impl Wrapper for FileWrapper {
+    fn open(&self, url: &str, mode: &str) -> Result<Box<dyn Handle>, String> {
+        let path = match url.get(..7) {
+            Some(prefix) if prefix.eq_ignore_ascii_case("file://") => &url[7..],
+            _ => url,
+        };
+
+        let options = match Self::options(mode) {
+            Some(options) => options,
+            None => return Err(format!("`{}' is not a valid mode for fopen", mode)),
+        };
+
+        match options.open(path) {
+            Ok(file) => Ok(Box::new(file)),
+            Err(e) => Err(reason(&e)),
+        }
+    }
+}
+
+/// The `php://` streams.
+pub struct PhpWrapper;
+
+/// `php://output` goes wherever `echo` does, which is always standard output here.
+struct Output;
+
+impl Handle for Output {
+    fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+        Err(bad_descriptor())
+    }
+
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        let mut stdout = io::stdout();
+
+        stdout.write_all(bytes).map(|_| bytes.len())
+    }
+}
+
+impl Wrapper for PhpWrapper {
+    fn open(&self, url: &str, mode: &str) -> Result<Box<dyn Handle>, String> {
+        let path = url["php://".len()..].to_lowercase();
+
+        Ok(match path.as_str() {
+            "stdin" => Box::new(io::stdin()),
+            "stdout" => Box::new(io::stdout()),
+            "stderr" => Box::new(io::stderr()),
+            "output" => Box::new(Output),
+            // There's no request body on the command line, so php://input is always empty.
+            "input" => Box::new(Memory::new(Vec::new(), false)),
+            "memory" => Box::new(Memory::with_mode(mode)),
+            // PHP moves php://temp into a temporary file once it passes its maxmemory
+            // limit, but that isn't something a script can observe.
+            temp if temp == "temp" || temp.starts_with("temp/maxmemory:") => Box::new(Memory::with_mode(mode)),
+            _ => return Err("Invalid php:// URL specified".into()),
+        })
+    }
+}
+
+/// RFC 2397 `data:` URLs, e.g. `data://text/plain;base64,SGVsbG8=`.
+pub struct DataWrapper;
+
+impl Wrapper for DataWrapper {
+    fn open(&self, url: &str, _: &str) -> Result<Box<dyn Handle>, String> {
+        let url = &url["data:".len()..];
+        let url = url.strip_prefix("//").unwrap_or(url);
+
+        let (header, payload) = match url.split_once(',') {
+            Some(parts) => parts,
+            None => return Err("rfc2397: no comma in URL".into()),
+        };
+
+        let mut parameters = header.split(';');
+        let media_type = parameters.next().unwrap_or("");
+
+        if ! media_type.is_empty() && ! media_type.contains('/') {
+            return Err("rfc2397: illegal media type".into());
+        }
+
+        let mut base64 = false;
+
+        for parameter in parameters {
+            match parameter {
+                "base64" => base64 = true,
+                parameter if parameter.contains('=') && ! base64 => (),
+                _ => return Err("rfc2397: illegal parameter".into()),
+            }
+        }
+
+        let data = match base64 {
+            true => decode_base64(payload.as_bytes(), true).ok_or("rfc2397: unable to decode")?,
+            false => decode_url(payload.as_bytes(), false),
+        };
+
+        Ok(Box::new(Memory::new(data, false)))
+    }
+}
@@ -1,49 +1,60 @@
-use logos::Logos;
+use logos::{Lexer, Logos};
 
 #[derive(Debug, Logos, PartialEq, Clone)]
 pub enum Token<'t> {
-    #[regex(r"\$[a-zA-Z_]+")]
+    #[regex(r"\$[a-zA-Z_\u{80}-\u{10FFFF}][a-zA-Z0-9_\u{80}-\u{10FFFF}]*")]
     Variable(&'t str),
-    #[regex(r"[a-zA-Z_]+")]
+    #[regex(r"[a-zA-Z_\u{80}-\u{10FFFF}][a-zA-Z0-9_\u{80}-\u{10FFFF}]*")]
     Identifier(&'t str),
 
-    #[token("if")]
+    #[token("if", ignore(ascii_case))]
     If,
-    #[token("else")]
+    #[token("else", ignore(ascii_case))]
     Else,
-    #[token("while")]
+    #[token("while", ignore(ascii_case))]
     While,
-    #[token("break")]
+    #[token("break", ignore(ascii_case))]
     Break,
-    #[token("function")]
+    #[token("function", ignore(ascii_case))]
     Function,
-    #[token("return")]
+    #[token("return", ignore(ascii_case))]
     Return,
-    #[token("for")]
+    #[token("for", ignore(ascii_case))]
     For,
-    #[token("const")]
+    #[token("const", ignore(ascii_case))]
     Const,
+    #[token("exit", ignore(ascii_case))]
+    #[token("die", ignore(ascii_case))]
+    Exit,
+    #[token("new", ignore(ascii_case))]
+    New,
 
-    #[token("true")]
+    #[token("true", ignore(ascii_case))]
     True,
-    #[token("false")]
+    #[token("false", ignore(ascii_case))]
     False,
-    #[token("null")]
+    #[token("null", ignore(ascii_case))]
     Null,
 
     #[regex(r##""(?:[^"\\]|\\.)*""##)]
     String(&'t str),
-    #[regex(r"[0-9]+", |l| l.slice().parse())]
-    Integer(i64),
-    #[regex(r"[0-9]+\.[0-9]+", |l| l.slice().parse())]
-    Float(f64),
+    #[regex(r"`[^`]*`")]
+    Backtick(&'t str),
+    #[regex(r"[0-9]+(_[0-9]+)*", |l| integer(l, 10))]
+    #[regex(r"0[xX][0-9a-fA-F]+(_[0-9a-fA-F]+)*", |l| integer(l, 16))]
+    #[regex(r"0[oO][0-7]+(_[0-7]+)*", |l| integer(l, 8))]
+    #[regex(r"0[bB][01]+(_[01]+)*", |l| integer(l, 2))]
+    #[regex(r"[0-9]+(_[0-9]+)*\.([0-9]+(_[0-9]+)*)?([eE][+-]?[0-9]+(_[0-9]+)*)?", float)]
+    #[regex(r"\.[0-9]+(_[0-9]+)*([eE][+-]?[0-9]+(_[0-9]+)*)?", float)]
+    #[regex(r"[0-9]+(_[0-9]+)*[eE][+-]?[0-9]+(_[0-9]+)*", float)]
+    Number(Number),
 
-    #[token("<?php")]
+    #[token("<?php", ignore(ascii_case))]
     OpenTag,
     #[token("#!(.*)")]
     Shebang,
 
-    #[token("echo")]
+    #[token("echo", ignore(ascii_case))]
     Echo,
 
     #[token(";")]
@@ -60,12 +71,35 @@ pub enum Token<'t> {
     Multiply,
     #[token("/")]
     Divide,
+    #[token("%")]
+    Modulo,
     #[token("=")]
     Assign,
+    #[token("!")]
+    Bang,
     #[token("<")]
     LessThan,
     #[token(">")]
     GreaterThan,
+    #[token("<=")]
+    LessThanOrEqual,
+    #[token(">=")]
+    GreaterThanOrEqual,
+    #[token("==")]
+    Equals,
+    #[token("!=")]
+    #[token("<>")]
+    NotEquals,
+    #[token("===")]
+    Identical,
+    #[token("!==")]
+    NotIdentical,
+    #[token("<=>")]
+    Spaceship,
+    #[token("=>")]
+    DoubleArrow,
+    #[token("->")]
+    Arrow,
     #[token("(")]
     LeftParen,
     #[token(")")]
@@ -86,6 +120,39 @@ pub enum Token<'t> {
     Error,
 }
 
-pub fn generate(source: &str) -> Vec<Token> {
+/// Numeric literals are lexed into a single token since an integer literal that
+/// overflows `i64` becomes a float, the same way PHP's lexer handles it.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Number {
+    Integer(i64),
+    Float(f64),
+}
+
+fn integer<'t>(lex: &mut Lexer<'t, Token<'t>>, radix: u32) -> Option<Number> {
+    let digits = lex.slice().replace('_', "");
+
+    let (radix, digits) = match radix {
+        10 if digits.len() > 1 && digits.starts_with('0') => (8, &digits[1..]),
+        10 => (10, &digits[..]),
+        _ => (radix, &digits[2..]),
+    };
+
+    // Legacy octal literals such as `019` are invalid in PHP too.
+    if ! digits.chars().all(|c| c.is_digit(radix)) {
+        return None;
+    }
+
+    Some(match i64::from_str_radix(digits, radix) {
+        Ok(i) => Number::Integer(i),
+        Err(_) if radix == 10 => Number::Float(digits.parse().ok()?),
+        Err(_) => Number::Float(digits.chars().fold(0.0, |f, c| f * radix as f64 + c.to_digit(radix).unwrap() as f64)),
+    })
+}
+
+fn float<'t>(lex: &mut Lexer<'t, Token<'t>>) -> Option<Number> {
+    lex.slice().replace('_', "").parse().ok().map(Number::Float)
+}
+
+pub fn generate(source: &str) -> Vec<Token<'_>> {
     Token::lexer(source).collect()
 }
\ No newline at end of file
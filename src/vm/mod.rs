@@ -1,15 +1,24 @@
-use crate::object::{Object, new_array};
+use crate::object::{Object, Array, ByteString, Key, new_array, convert};
 use crate::compiler::Code;
-use crate::globals::{Globals, InternalFunction};
-use std::collections::HashMap;
+use crate::parser::Cast;
+use crate::globals::{Globals, InternalFunction, UserFunction, function_name};
+use crate::stdlib::{self, Pcre, Processes, Random, Streams};
+use chrono_tz::Tz;
+use crate::error;
+use indexmap::IndexMap;
+use std::cmp::Ordering;
+use std::io::{self, Write};
 
 #[derive(Debug)]
 struct Frame {
     ip: usize,
     instructions: Vec<Code>,
-    environment: HashMap<String, Object>,
+    environment: IndexMap<String, Object>,
     stack: Vec<Object>,
     internal: Option<InternalFunction>,
+    references: Vec<(usize, String)>,
+    // <declared name>, <parameter count>
+    function: Option<(String, usize)>,
 }
 
 impl Frame {
@@ -17,9 +26,18 @@ impl Frame {
         Self {
             ip: 0,
             instructions,
-            environment: HashMap::new(),
+            environment: IndexMap::new(),
             stack: Vec::new(),
             internal: None,
+            references: Vec::new(),
+            function: None,
+        }
+    }
+
+    fn user(function: UserFunction) -> Self {
+        Self {
+            function: Some((function.name, function.parameters)),
+            ..Self::new(function.instructions)
         }
     }
 
@@ -27,9 +45,21 @@ impl Frame {
         Self {
             ip: 0,
             instructions: Vec::new(),
-            environment: HashMap::new(),
+            environment: IndexMap::new(),
             stack: Vec::new(),
             internal: Some(internal),
+            references: Vec::new(),
+            function: None,
+        }
+    }
+
+    /// There are no default values, so a user function needs an argument for every
+    /// parameter it declares.
+    fn check_arguments(&self) {
+        if let Some((name, parameters)) = &self.function {
+            if self.stack.len() < *parameters {
+                error::throw("ArgumentCountError", format!("Too few arguments to function {}(), {} passed and exactly {} expected", name, self.stack.len(), parameters));
+            }
         }
     }
 
@@ -50,8 +80,20 @@ pub struct Machine {
     constants: Vec<Object>,
     frames: Vec<Frame>,
     buffer: Vec<Frame>,
+    // The variables passed by reference to the internal function currently being called.
+    references: Vec<(usize, String)>,
 
     pub globals: Globals,
+    pub random: Random,
+    pub streams: Streams,
+    pub json_last_error: i64,
+    pub pcre: Pcre,
+    pub timezone: Tz,
+    pub processes: Processes,
+    // Variables like `$_SERVER` that can be read from any scope, keyed by name.
+    superglobals: IndexMap<String, Object>,
+    // The status passed to `exit()`, once the script has called it.
+    status: Option<i32>,
 }
 
 impl Machine {
@@ -75,8 +117,118 @@ impl Machine {
         self.frame().ip += 1;
     }
 
-    fn run(&mut self) {
-        while self.frame().ip < self.frame().instructions.len() {
+    /// Writes a value back to a variable that was passed by reference to the current
+    /// internal function. Arguments that weren't variables are silently ignored.
+    pub fn set_reference(&mut self, position: usize, value: Object) {
+        let name = self.references.iter()
+            .find(|(p, _)| *p == position)
+            .map(|(_, name)| name.clone());
+
+        if let Some(name) = name {
+            self.assign(name, value);
+        }
+    }
+
+    /// Reads a variable from the scope that called the current internal function.
+    pub fn get_variable(&mut self, name: &str) -> Option<Object> {
+        self.lookup(name)
+    }
+
+    pub fn set_variable(&mut self, name: impl Into<String>, value: Object) {
+        self.assign(name.into(), value);
+    }
+
+    /// Reads a variable by name for internal functions. Compiled code already knows
+    /// which variables are superglobals and uses `Get` or `GetSuperglobal` instead.
+    fn lookup(&mut self, name: &str) -> Option<Object> {
+        match is_superglobal(name) {
+            true => self.superglobal(name),
+            false => self.frame().get(name).cloned(),
+        }
+    }
+
+    fn assign(&mut self, name: String, value: Object) {
+        if name == "$GLOBALS" {
+            error::fatal("$GLOBALS can only be modified using the $GLOBALS[$name] = $value syntax");
+        }
+
+        if is_superglobal(&name) {
+            self.superglobals.insert(name, value);
+        } else {
+            self.frame().set(name, value);
+        }
+    }
+
+    /// Superglobals are shared by every scope. `$GLOBALS` is a copy of them and the main
+    /// script's variables, in the order they were first assigned.
+    fn superglobal(&self, name: &str) -> Option<Object> {
+        if name == "$GLOBALS" {
+            return Some(Object::from_array(self.superglobals.iter().chain(&self.frames[0].environment)
+                .map(|(name, value)| (Key::from(&name[1..]), value.clone()))
+                .collect()));
+        }
+
+        self.superglobals.get(name).cloned()
+    }
+
+    /// Whether a value can be called with `call()`. Only function names are callable for now.
+    pub fn is_callable(&mut self, callable: &Object) -> bool {
+        match callable {
+            Object::String(name) => self.globals.is_function(&function_name(&name.to_string())),
+            _ => false,
+        }
+    }
+
+    /// Calls a function from inside an internal function and returns its result. User functions
+    /// are run to completion on a new frame before this returns, so it can be used re-entrantly
+    /// (e.g. `usort()` calling a comparison function that itself calls `usort()`).
+    pub fn call(&mut self, callable: &Object, mut args: Vec<Object>) -> Object {
+        let (name, original) = match callable {
+            Object::String(name) => (function_name(&name.to_string()), name.to_string()),
+            _ => error::throw("Error", "Value not callable"),
+        };
+
+        if ! self.globals.is_function(&name) {
+            error::throw("Error", format!("Call to undefined function {}()", original));
+        }
+
+        if ! self.globals.is_user_function(&name) {
+            let callback = self.globals.get_internal_function(&name).callback;
+            let references = std::mem::take(&mut self.references);
+            let result = callback(self, args);
+            self.references = references;
+
+            return result;
+        }
+
+        let mut frame = Frame::user(self.globals.get_user_function(&name));
+
+        // Internal functions decide how many arguments a callback gets, which might be fewer
+        // than it declares. The first argument needs to be at the top of the stack, the same
+        // as `DoUserCall`.
+        args.reverse();
+        frame.stack = args;
+        frame.check_arguments();
+
+        let depth = self.frames.len();
+
+        self.push_frame(frame);
+        self.run(depth);
+
+        // The internal function that made this call can't be unwound, so exiting from
+        // inside a callback ends the process straight away.
+        if let Some(status) = self.status {
+            let _ = io::stdout().flush();
+            std::process::exit(status);
+        }
+
+        self.pop().unwrap_or(Object::Null)
+    }
+
+    /// Runs instructions until the frame stack drops back to `depth` frames (or the main
+    /// script finishes).
+    fn run(&mut self, depth: usize) {
+        while self.frames.len() > depth && self.frame().ip < self.frame().instructions.len() {
             let ip = self.frame().ip;
             let op = self.frame().instructions.get(ip).unwrap().clone();
 
@@ -120,7 +272,7 @@ impl Machine {
                 Code::Echo => {
                     let value = self.pop().unwrap();
 
-                    print!("{}", value);
+                    let _ = io::stdout().write_all(&convert::to_bytes(&value));
 
                     self.next();
                 },
@@ -138,8 +290,29 @@ impl Machine {
                     self.next();
                 },
                 Code::Get(v) => {
-                    let value = self.frame().get(&v).unwrap().clone();
+                    let value = match self.frame().get(&v) {
+                        Some(value) => value.clone(),
+                        None => {
+                            error::warning(format!("Undefined variable {}", v));
+
+                            Object::Null
+                        },
+                    };
+
+                    self.push(value);
+
+                    self.next();
+                },
+                Code::GetSuperglobal(v) => {
+                    let value = self.superglobal(&v).unwrap_or(Object::Null);
+
+                    self.push(value);
+                    self.next();
+                },
+                Code::AssignSuperglobal(v) => {
+                    let value = self.pop().unwrap();
 
+                    self.superglobals.insert(v, value.clone());
                     self.push(value);
 
                     self.next();
@@ -152,66 +325,56 @@ impl Machine {
 
                     self.next();
                 },
-                Code::Add | Code::Subtract | Code::Divide | Code::Multiply |
-                Code::LessThan | Code::GreaterThan | Code::Concat => {
+                Code::Add | Code::Subtract | Code::Divide | Code::Multiply | Code::Modulo | Code::Concat |
+                Code::LessThan | Code::GreaterThan | Code::LessThanOrEqual | Code::GreaterThanOrEqual |
+                Code::Equals | Code::NotEquals | Code::Identical | Code::NotIdentical | Code::Spaceship => {
                     let rhs = self.pop().unwrap();
                     let lhs = self.pop().unwrap();
 
                     self.push(match op {
-                        Code::Add => match (lhs, rhs) {
-                            (Object::Integer(l), Object::Integer(r)) => Object::Integer(l + r),
-                            (Object::Float(l), Object::Integer(r)) => Object::Float(l + r as f64),
-                            (Object::Integer(l), Object::Float(r)) => Object::Float(l as f64 + r),
-                            (Object::Float(l), Object::Float(r)) => Object::Float(l + r),
-                            _ => unreachable!()
-                        },
-                        Code::Subtract => match (lhs, rhs) {
-                            (Object::Integer(l), Object::Integer(r)) => Object::Integer(l - r),
-                            (Object::Float(l), Object::Integer(r)) => Object::Float(l - r as f64),
-                            (Object::Integer(l), Object::Float(r)) => Object::Float(l as f64 - r),
-                            (Object::Float(l), Object::Float(r)) => Object::Float(l - r),
-                            _ => unreachable!()
-                        },
-                        Code::Multiply => match (lhs, rhs) {
-                            (Object::Integer(l), Object::Integer(r)) => Object::Integer(l * r),
-                            (Object::Float(l), Object::Integer(r)) => Object::Float(l * r as f64),
-                            (Object::Integer(l), Object::Float(r)) => Object::Float(l as f64 * r),
-                            (Object::Float(l), Object::Float(r)) => Object::Float(l * r),
-                            _ => unreachable!()
-                        },
-                        Code::Divide => match (lhs, rhs) {
-                            (Object::Integer(l), Object::Integer(r)) => Object::Float(l as f64 / r as f64),
-                            (Object::Float(l), Object::Integer(r)) => Object::Float(l / r as f64),
-                            (Object::Integer(l), Object::Float(r)) => Object::Float(l as f64 / r),
-                            (Object::Float(l), Object::Float(r)) => Object::Float(l / r),
-                            _ => unreachable!()
-                        },
-                        Code::GreaterThan => match (lhs, rhs) {
-                            (Object::Integer(l), Object::Integer(r)) => Object::from_bool(l > r),
-                            (Object::Float(l), Object::Integer(r)) => Object::from_bool(l > r as f64),
-                            (Object::Integer(l), Object::Float(r)) => Object::from_bool(l as f64 > r),
-                            (Object::Float(l), Object::Float(r)) => Object::from_bool(l > r),
-                            _ => unreachable!()
-                        },
-                        Code::LessThan => match (lhs, rhs) {
-                            (Object::Integer(l), Object::Integer(r)) => Object::from_bool(l < r),
-                            (Object::Float(l), Object::Integer(r)) => Object::from_bool(l < r as f64),
-                            (Object::Integer(l), Object::Float(r)) => Object::from_bool((l as f64) < r),
-                            (Object::Float(l), Object::Float(r)) => Object::from_bool(l < r),
-                            _ => unreachable!()
-                        },
-                        Code::Concat => {
-                            Object::String(format!("{}{}", lhs, rhs))
-                        },
-                        _ => todo!("{:?}", op),
+                        Code::Add => convert::add(&lhs, &rhs),
+                        Code::Subtract => convert::subtract(&lhs, &rhs),
+                        Code::Multiply => convert::multiply(&lhs, &rhs),
+                        Code::Divide => convert::divide(&lhs, &rhs),
+                        Code::Modulo => convert::modulo(&lhs, &rhs),
+                        Code::Concat => convert::concat(&lhs, &rhs),
+                        // PHP evaluates `$a > $b` as `$b < $a`, which only matters for uncomparable values.
+                        Code::LessThan => Object::from_bool(convert::compare(&lhs, &rhs) == Ordering::Less),
+                        Code::GreaterThan => Object::from_bool(convert::compare(&rhs, &lhs) == Ordering::Less),
+                        Code::LessThanOrEqual => Object::from_bool(convert::compare(&lhs, &rhs) != Ordering::Greater),
+                        Code::GreaterThanOrEqual => Object::from_bool(convert::compare(&rhs, &lhs) != Ordering::Greater),
+                        Code::Equals => Object::from_bool(convert::loose_equals(&lhs, &rhs)),
+                        Code::NotEquals => Object::from_bool(! convert::loose_equals(&lhs, &rhs)),
+                        Code::Identical => Object::from_bool(convert::strict_equals(&lhs, &rhs)),
+                        Code::NotIdentical => Object::from_bool(! convert::strict_equals(&lhs, &rhs)),
+                        Code::Spaceship => Object::Integer(convert::compare(&lhs, &rhs) as i64),
+                        _ => unreachable!(),
                     });
 
                     self.next();
                 },
+                Code::Cast(cast) => {
+                    let value = self.pop().unwrap();
+
+                    self.push(match cast {
+                        Cast::Int => Object::Integer(convert::to_int(&value)),
+                        Cast::Float => Object::Float(convert::to_float(&value)),
+                        Cast::String => Object::String(convert::to_bytes(&value)),
+                        Cast::Bool => Object::from_bool(value.to_bool()),
+                        Cast::Array => convert::to_array(&value),
+                        Cast::Object => convert::to_instance(&value),
+                    });
+                    self.next();
+                },
+                Code::Not => {
+                    let value = self.pop().unwrap();
+
+                    self.push(Object::from_bool(! value.to_bool()));
+                    self.next();
+                },
                 Code::InitCall(callable) => {
                     let frame = if self.globals.is_user_function(&callable) {
-                        let instructions = self.globals.get_user_function(callable);
-                        Frame::new(instructions)
+                        Frame::user(self.globals.get_user_function(&callable))
                     } else {
                         Frame::internal(self.globals.get_internal_function(&callable))
                     };
@@ -219,8 +382,83 @@ impl Machine {
                     self.push_buffer(frame);
                     self.next();
                 },
+                Code::InitNew(class) => {
+                    let constructor = match self.globals.get_method(&class, "__construct") {
+                        Some(constructor) => constructor,
+                        None => error::throw("Error", format!("Class \"{}\" not found", class)),
+                    };
+
+                    self.push_buffer(Frame::internal(constructor));
+                    self.next();
+                },
+                Code::InitMethodCall(method) => {
+                    let object = self.pop().unwrap();
+
+                    let class = match &object {
+                        Object::Instance(instance) => instance.borrow().class.clone(),
+                        value => error::throw("Error", format!("Call to a member function {}() on {}", method, value.type_name())),
+                    };
+
+                    let mut frame = match self.globals.get_method(&class, &method) {
+                        Some(internal) => Frame::internal(internal),
+                        None => error::throw("Error", format!("Call to undefined method {}::{}()", class, method)),
+                    };
+
+                    // The object is the method's first argument.
+                    frame.stack.push(object);
+
+                    self.push_buffer(frame);
+                    self.next();
+                },
+                Code::GetProperty(name) => {
+                    let object = self.pop().unwrap();
+
+                    let value = match &object {
+                        Object::Instance(instance) => {
+                            let instance = instance.borrow();
+
+                            match instance.properties.get(&Key::String(name.as_str().into())) {
+                                Some(value) => value.clone(),
+                                None => {
+                                    error::warning(format!("Undefined property: {}::${}", instance.class, name));
+                                    Object::Null
+                                },
+                            }
+                        },
+                        value => {
+                            error::warning(format!("Attempt to read property \"{}\" on {}", name, value.type_name()));
+                            Object::Null
+                        },
+                    };
+
+                    self.push(value);
+                    self.next();
+                },
+                Code::AssignToProperty(name) => {
+                    let object = self.pop().unwrap();
+                    let value = self.pop().unwrap();
+
+                    match &object {
+                        // Only `stdClass` takes dynamic properties, since the date classes
+                        // keep their state in theirs.
+                        Object::Instance(instance) if instance.borrow().class == "stdClass" => {
+                            instance.borrow_mut().properties.insert(Key::String(name.as_str().into()), value.clone());
+                        },
+                        Object::Instance(instance) => error::throw("Error", format!("Cannot create dynamic property {}::${}", instance.borrow().class, name)),
+                        object => error::throw("Error", format!("Attempt to assign property \"{}\" on {}", name, object.type_name())),
+                    }
+
+                    self.push(value);
+                    self.next();
+                },
                 Code::DoUserCall => {
-                    let frame = self.pop_buffer();
+                    let mut frame = self.pop_buffer();
+
+                    frame.check_arguments();
+
+                    // Arguments are sent left to right, but the function's parameters are
+                    // assigned by popping them off the stack, so the first one goes on top.
+                    frame.stack.reverse();
 
                     self.next();
                     self.push_frame(frame);
@@ -229,11 +467,12 @@ impl Machine {
                     let frame = self.pop_buffer();
                     
                     let internal = frame.internal.unwrap();
-                    let mut args = frame.stack;
-                    args.reverse();
+                    let args = frame.stack;
                     let callback = internal.callback;
-                    
+
+                    let references = std::mem::replace(&mut self.references, frame.references);
                     let result = callback(self, args);
+                    self.references = references;
 
                     self.push(result);
 
@@ -245,6 +484,28 @@ impl Machine {
                     self.buffer().push(value);
                     self.next();
                 },
+                Code::SendRef(position, v) => {
+                    // Variables passed by reference don't need to exist yet.
+                    let value = self.lookup(&v).unwrap_or(Object::Null);
+
+                    self.buffer().push(value);
+                    self.buffer().references.push((position, v));
+                    self.next();
+                },
+                Code::Exit => {
+                    // An integer is the exit status, anything else is printed first.
+                    let status = match self.pop().unwrap() {
+                        Object::Integer(status) => status as i32,
+                        value => {
+                            let _ = io::stdout().write_all(&convert::to_bytes(&value));
+                            0
+                        },
+                    };
+
+                    // Dropping every frame stops the run loop, and `run()` hands the status to `main()`.
+                    self.status = Some(status);
+                    self.frames.clear();
+                },
                 Code::Return => {            
                     self.pop_frame();
 
@@ -267,9 +528,7 @@ impl Machine {
                     
                     match array.clone() {
                         Some(Object::Array(items)) => {
-                            let len = items.borrow().len();
-
-                            items.borrow_mut().insert(len.to_string(), value);
+                            items.borrow_mut().push(value);
                             
                             self.push(array.unwrap());
                         },
@@ -278,20 +537,46 @@ impl Machine {
 
                     self.next();
                 },
+                Code::AddToArrayWithKey => {
+                    let value = self.pop().unwrap();
+                    let key = self.pop().unwrap();
+                    let array = self.pop().unwrap();
+
+                    array.to_hash().borrow_mut().insert(Key::from_object(&key), value);
+
+                    self.push(array);
+                    self.next();
+                },
                 Code::GetArrayItem => {
                     let index = self.pop().unwrap();
                     let array = self.pop();
 
-                    match array {
+                    let value = match array {
                         Some(Object::Array(items)) => {
-                            let hash = items.borrow();
-                            let value = hash.get(&index.to_string()).unwrap();
+                            let key = Key::from_object(&index);
+
+                            match items.borrow().get(&key) {
+                                Some(value) => value.clone(),
+                                None => {
+                                    match key {
+                                        Key::Integer(i) => error::warning(format!("Undefined array key {}", i)),
+                                        Key::String(s) => error::warning(format!("Undefined array key \"{}\"", s)),
+                                    };
+
+                                    Object::Null
+                                },
+                            }
+                        },
+                        Some(other) => {
+                            error::warning(format!("Trying to access array offset on value of type {}", other.type_name()));
 
-                            self.push(value.clone());
+                            Object::Null
                         },
-                        _ => unreachable!()
+                        None => unreachable!(),
                     };
 
+                    self.push(value);
+
                     self.next();
                 },
                 Code::AssignToIndex => {
@@ -299,7 +584,17 @@ impl Machine {
                     let array = self.pop().unwrap();
                     let value = self.pop().unwrap();
 
-                    array.to_hash().borrow_mut().insert(index.to_string(), value);
+                    array.to_hash().borrow_mut().insert(Key::from_object(&index), value);
+
+                    self.next();
+                },
+                Code::AssignToGlobals => {
+                    let index = self.pop().unwrap();
+                    let value = self.pop().unwrap();
+
+                    // `$GLOBALS['name'] = ...` writes straight to the main script's variable.
+                    self.frames[0].set(format!("${}", Key::from_object(&index)), value.clone());
+                    self.push(value);
 
                     self.next();
                 },
@@ -317,7 +612,6 @@ impl Machine {
                     self.globals.create_constant(name, value);
                     self.next();
                 },
-                _ => todo!("{:?}", op)
             }
         }
     }
@@ -339,12 +633,39 @@ impl Machine {
     }
 }
 
-pub fn run(constants: Vec<Object>, instructions: Vec<Code>, globals: Globals) {
-    let frames = vec![
-        Frame::new(instructions),
-    ];
+/// Variables that can be read from any scope. `$GLOBALS` is built when it's read.
+const SUPERGLOBALS: [&str; 8] = ["$GLOBALS", "$_GET", "$_POST", "$_COOKIE", "$_FILES", "$_REQUEST", "$_SERVER", "$_ENV"];
+
+pub fn is_superglobal(name: &str) -> bool {
+    SUPERGLOBALS.contains(&name)
+}
+
+/// Runs a compiled script and returns the status the process should exit with. The
+/// arguments are the script's path followed by anything passed after it.
+pub fn run(constants: Vec<Object>, instructions: Vec<Code>, globals: Globals, arguments: Vec<ByteString>) -> i32 {
+    let arguments: Array = arguments.into_iter().map(Object::String).collect();
+    let mut main = Frame::new(instructions);
+
+    main.set("$argc".into(), Object::Integer(arguments.len() as i64));
+    main.set("$argv".into(), Object::from_array(arguments.clone()));
+
+    // There's no request on the command line, so the request superglobals are always empty.
+    let mut superglobals = IndexMap::new();
+
+    for name in &SUPERGLOBALS[1..6] {
+        superglobals.insert(name.to_string(), Object::from_array(Array::new()));
+    }
+
+    superglobals.insert("$_SERVER".into(), stdlib::server(arguments));
+    superglobals.insert("$_ENV".into(), stdlib::environment());
+
+    let frames = vec![main];
+
+    let mut machine = Machine { constants, frames, buffer: Vec::new(), references: Vec::new(), globals, random: Random::default(), streams: Streams::default(), json_last_error: 0, pcre: Pcre::default(), timezone: Tz::UTC, processes: Processes::default(), superglobals, status: None };
+
+    machine.run(0);
 
-    let mut machine = Machine { constants, frames, buffer: Vec::new(), globals };
+    let _ = io::stdout().flush();
 
-    machine.run();
+    machine.status.unwrap_or(0)
 }
\ No newline at end of file
@@ -0,0 +1,9 @@
+<?php
+
+function Add($a, $b) {
+    return $a + $b;
+}
+
+var_dump(add(1, 2), ADD(3, 4, 5), call_user_func("aDd", 6, 7));
+
+add(1);
@@ -0,0 +1,28 @@
+<?php
+
+function say($value) {
+    echo $value . " ";
+
+    return $value;
+}
+
+function pair($first, $second) {
+    return $first . "-" . $second;
+}
+
+var_dump(pair(say("a"), say("b")));
+var_dump(str_repeat(say("x"), say(3)));
+var_dump(pair(say("outer"), pair(say("inner1"), say("inner2"))));
+var_dump(max(say(1), say(2), say(3)));
+
+$subject = "banana";
+var_dump(str_replace(say("a"), say("o"), $subject, $count), $count);
+
+var_dump(similar_text(say("World"), say("Word"), $percent), $percent);
+
+$numbers = [3, 1, 2];
+$letters = ["c", "a", "b"];
+var_dump(array_multisort($numbers, $letters), $numbers, $letters);
+
+$value = "12";
+var_dump(settype($value, say("integer")), $value);
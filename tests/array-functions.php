@@ -0,0 +1,62 @@
+<?php
+
+$fruit = ["a" => "apple", "b" => "banana", 5 => "cherry", "date"];
+
+var_dump(array_keys($fruit));
+var_dump(array_values($fruit));
+var_dump(array_keys([1, "1", 2, 1], 1));
+var_dump(array_merge([3 => "a", "x" => "b"], [3 => "c", "x" => "d"]));
+var_dump(array_combine(["one", "two"], [1, 2]));
+var_dump(array_flip(["a", "b"]));
+var_dump(array_slice([1, 2, 3, 4, 5], 1, 3));
+var_dump(array_slice([1, 2, 3, 4, 5], -2, 1, true));
+
+$numbers = [1, 2, 3, 4, 5];
+$removed = array_splice($numbers, 1, 2, ["x", "y", "z"]);
+var_dump($numbers, $removed);
+
+var_dump(array_search("1", [0, 1, 2]));
+var_dump(array_search("1", [0, 1, 2], true));
+var_dump(in_array("abc", [0]));
+var_dump(in_array(null, [0]));
+var_dump(array_key_exists("b", $fruit));
+var_dump(array_key_first($fruit), array_key_last($fruit), array_key_first([]));
+
+var_dump(array_unique([1, "1", 2, 2.0, "a", "A"]));
+var_dump(array_reverse(["x" => 1, 2, 3]));
+var_dump(array_reverse([1, 2, 3], true));
+var_dump(array_fill(-3, 3, "v"));
+var_dump(array_fill_keys(["a", 5], 0));
+var_dump(array_pad([1, 2], 4, 0));
+var_dump(array_pad([1, 2], -4, 0));
+var_dump(array_chunk([1, 2, 3], 2));
+var_dump(array_chunk(["a" => 1, "b" => 2, "c" => 3], 2, true));
+
+$rows = [["id" => 3, "name" => "Ann"], ["id" => 7, "name" => "Bob"], ["name" => "Cid"]];
+var_dump(array_column($rows, "name"));
+var_dump(array_column($rows, "name", "id"));
+
+var_dump(range(1, 4));
+var_dump(range(10, 0, 5));
+var_dump(range("a", "e", 2));
+var_dump(range(0, 1, 0.25));
+
+$city = "Paris";
+$country = "France";
+var_dump(compact("city", ["country"]));
+var_dump(extract(["size" => "large", "city" => "Rome"], EXTR_SKIP));
+var_dump($size, $city);
+
+var_dump(array_sum([1, 2, 3.5, "4"]));
+var_dump(array_product([2, "3", 4]));
+var_dump(array_product([]));
+
+$stack = [1, 2];
+var_dump(array_push($stack, 3, 4));
+var_dump(array_pop($stack));
+var_dump(array_shift($stack));
+var_dump(array_unshift($stack, "z"));
+var_dump($stack);
+
+var_dump(count([1, [2, 3], [4, [5]]], COUNT_RECURSIVE));
+var_dump(sizeof([1, 2]));
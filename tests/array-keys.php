@@ -0,0 +1,17 @@
+<?php
+
+$array = ["b" => 1, "a" => 2, 10 => "ten", "five"];
+
+var_dump($array["b"], $array["a"], $array[10], $array[11]);
+
+$array["12"] = "integer key";
+$array["012"] = "string key";
+$array[true] = "one";
+
+var_dump($array[12], $array["012"], $array[1], count($array));
+
+var_dump($array["missing"]);
+var_dump($array[99]);
+
+$number = 5;
+var_dump($number[0]);
@@ -0,0 +1,63 @@
+<?php
+
+function double($n) {
+    return $n * 2;
+}
+
+function add($a, $b) {
+    return $a + $b;
+}
+
+function is_even($n) {
+    return $n / 2 == (int) ($n / 2);
+}
+
+function by_length($a, $b) {
+    return strlen($a) - strlen($b);
+}
+
+function describe($value, $key) {
+    echo $key . "=" . $value . ";";
+}
+
+function sort_nested($a, $b) {
+    $inner = [$b, $a];
+    usort($inner, "strcmp");
+    return strcmp($a, $b);
+}
+
+var_dump(array_map("double", ["a" => 1, "b" => 2]));
+var_dump(array_map("add", [1, 2, 3], [10, 20]));
+var_dump(array_map(null, [1, 2], ["x", "y"]));
+var_dump(array_map("strtoupper", ["hello", "world"]));
+
+var_dump(array_filter([1, 0, 2, null, 3]));
+var_dump(array_filter([1, 2, 3, 4, 5, 6], "is_even"));
+var_dump(array_filter(["a" => 1, "b" => 2], "is_string", ARRAY_FILTER_USE_KEY));
+var_dump(array_filter([5, 6, 7], "add", ARRAY_FILTER_USE_BOTH));
+
+var_dump(array_reduce([1, 2, 3, 4], "add", 10));
+var_dump(array_walk(["x" => 1, "y" => 2], "describe"));
+echo "
+";
+
+$words = ["banana", "fig", "apple", "kiwi"];
+usort($words, "by_length");
+var_dump($words);
+
+$scores = ["ann" => "ccc", "bob" => "a", "cid" => "bb"];
+uasort($scores, "by_length");
+var_dump(array_keys($scores));
+uksort($scores, "strcmp");
+var_dump(array_keys($scores));
+
+$letters = ["c", "a", "b"];
+usort($letters, "sort_nested");
+var_dump($letters);
+
+var_dump(call_user_func("add", 2, 3));
+var_dump(call_user_func_array("str_replace", ["a", "o", "banana"]));
+var_dump(is_callable("double"), is_callable("strlen"), is_callable("nope"));
+
+var_dump(array_map("add", [1, 2], [3, 4]));
+var_dump(array_map("add", [1, 2]));
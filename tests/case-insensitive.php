@@ -0,0 +1,32 @@
+<?PHP
+
+FUNCTION item2($value2) {
+    RETURN $value2;
+}
+
+$item2 = "lower";
+$Item2 = "upper";
+
+ECHO Item2($item2);
+echo ITEM2($Item2);
+
+if (TRUE) {
+    Var_Dump(NULL);
+} ELSE {
+    var_dump(False);
+}
+
+const café = "unicode";
+
+echo café;
+
+
+function Äbc() {
+    return "upper";
+}
+
+function äbc() {
+    return "lower";
+}
+
+var_dump(ÄBC(), äBC(), call_user_func("äbc"), is_callable("ÄBC"), is_callable("äBc"), is_callable("STRLEN"));
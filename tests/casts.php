@@ -0,0 +1,40 @@
+<?php
+
+var_dump((int) "12abc");
+var_dump((int) "1e3");
+var_dump((INT) 3.99);
+var_dump((int) 1e20);
+var_dump((float) "1.5 apples");
+var_dump((string) 1.0);
+var_dump((string) false);
+var_dump((bool) "0");
+var_dump((bool) []);
+var_dump((array) "hello");
+var_dump((object) ["a" => 1]);
+
+var_dump(intval("42", 8));
+var_dump(intval("0x1A", 16));
+var_dump(intval("0x1A", 0));
+var_dump(intval("012", 0));
+var_dump(floatval("1.5e3"));
+var_dump(boolval("0.0"));
+
+var_dump(gettype(1.5));
+var_dump(gettype(null));
+var_dump(get_debug_type(1.5));
+var_dump(get_debug_type((object) []));
+
+$value = "123";
+settype($value, "integer");
+var_dump($value);
+
+settype($undefined, "array");
+var_dump($undefined);
+
+var_dump(is_int(1));
+var_dump(is_float(1));
+var_dump(is_numeric(" 1.5 "));
+var_dump(is_numeric("1.5abc"));
+var_dump(is_scalar([]));
+var_dump(is_callable("strlen"));
+var_dump(is_callable("STRVAL"));
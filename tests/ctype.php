@@ -0,0 +1,31 @@
+<?php
+
+var_dump(ctype_digit("12345"), ctype_digit("12.5"), ctype_digit(""), ctype_digit("١٢"));
+var_dump(ctype_alpha("Hello"), ctype_alpha("Hello1"), ctype_alnum("abc123"), ctype_alnum("abc 123"));
+var_dump(ctype_upper("ABC"), ctype_upper("AbC"), ctype_lower("abc"), ctype_lower("abc1"));
+var_dump(ctype_space(" " . hex2bin("090a0b0c0d")), ctype_space(" x "), ctype_punct("!@#$%"), ctype_punct("!a"));
+var_dump(ctype_xdigit("DEADbeef09"), ctype_xdigit("0xff"), ctype_cntrl(hex2bin("0009")), ctype_graph("a!b"), ctype_graph("a b"), ctype_print("a b"));
+var_dump(ctype_digit(53), ctype_digit(5), ctype_digit(1000), ctype_alpha(-191), ctype_digit(null), ctype_digit([]));
+
+var_dump(ord("A"), ord("abc"), ord(""), ord(hex2bin("ff")), chr(65), chr(321), chr(-1) === hex2bin("ff"), ord(chr(0)));
+var_dump(lcfirst("Hello World"), lcfirst("ABC"), lcfirst(""), ucfirst(lcfirst("Mixed")));
+
+var_dump(strtr("Hi all, I said hello", "ai", "eo"), strtr("abc", "ab", "x"), strtr("Hi all", []));
+var_dump(strtr("Hi all, I said hello", ["Hi" => "Hello", "hello" => "hi", "Hello" => "Bye"]), strtr("aaa", ["a" => "b", "aa" => "c"]), strtr("1 2 3", [1 => "one", "" => "x"]));
+
+var_dump(strspn("42 is the answer", "1234567890"), strspn("foo", "o", 1, 2), strspn("foo", "o", -2), strspn("abc", "abc", 5));
+var_dump(strcspn("abcd", "cd"), strcspn("hello", "l", 1), strcspn("hello", "x", 0, -2), strcspn("", "x"));
+
+$text = "Hello fri3nd, you're looking good today!";
+
+var_dump(str_word_count($text), str_word_count($text, 1), str_word_count($text, 2), str_word_count($text, 1, "0..9"));
+var_dump(str_word_count("-a- b- 'c'"), str_word_count("-a- b- 'c'", 1), str_word_count("-a-", 1, "-"));
+
+var_dump(soundex("Robert"), soundex("Rupert"), soundex("Tymczak"), soundex("Pfister"), soundex("Ashcraft"), soundex("Lloyd"), soundex(""), soundex("123"));
+var_dump(metaphone("Thompson"), metaphone("Knight"), metaphone("Asterix", 5), metaphone("Schmidt"), metaphone("Xavier"), metaphone("Wright"), metaphone("laugh"), metaphone("science"), metaphone("judge"), metaphone("1234"));
+
+var_dump(quotemeta("1 + 1 = 2?"), quotemeta("[a-z]*$"), quotemeta(""));
+var_dump(addcslashes("foo[bar]", "A..Z"), addcslashes("zoo['.']", "z..A"), addcslashes("tab" . hex2bin("09") . "new" . hex2bin("0a") . "bell" . hex2bin("07ff"), hex2bin("00") . ".." . hex2bin("1f7f") . ".." . hex2bin("ff")));
+var_dump(chunk_split("abcdefg", 3, "|"), chunk_split("abc", 5, "|"), chunk_split("", 2, "|"), strlen(chunk_split(str_repeat("x", 100))));
+
+chunk_split("abc", 0);
@@ -0,0 +1,39 @@
+<?php
+
+date_default_timezone_set("UTC");
+
+$date = new DateTime("2024-01-31 10:00:00");
+echo $date->format("Y-m-d H:i:s");
+echo "
+";
+
+$date->modify("+1 day");
+echo $date->format(DATE_ATOM);
+echo "
+";
+
+$immutable = new DateTimeImmutable("2024-03-10 12:30:00", new DateTimeZone("Europe/Amsterdam"));
+$later = $immutable->add(new DateInterval("P1M2DT3H"));
+var_dump($immutable->format("c"), $later->format("c"));
+var_dump($immutable->getTimezone()->getName(), $later->getTimestamp());
+
+$interval = $immutable->diff($later);
+var_dump($interval->m, $interval->d, $interval->h, $interval->days);
+echo $interval->format("%m month, %d days, %h hours");
+echo "
+";
+
+var_dump((new DateInterval("P2W3D"))->d);
+var_dump($date->setDate(2020, 2, 29)->setTime(23, 59)->format("D, d M Y H:i"));
+var_dump($date->setTimezone(new DATETIMEZONE("+05:30"))->FORMAT("H:i P"));
+var_dump((new DateTime)->getTimestamp() > 0);
+
+var_dump($date->missing);
+$number = 5;
+var_dump($number->missing);
+
+$object = (object) ["a" => 1];
+$copy = $object->b = $object->a + 1;
+var_dump($object, $copy);
+
+new DateInterval("P1X");
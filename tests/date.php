@@ -0,0 +1,82 @@
+<?php
+
+var_dump(date_default_timezone_get());
+var_dump(time() > 1700000000, is_float(microtime(true)), is_string(microtime()), is_int(hrtime(true)), count(hrtime()));
+
+$ts = 1704462245;
+var_dump(date("Y-m-d H:i:s", $ts), date("D, d M Y", $ts), date("l jS F Y g:ia", $ts));
+var_dump(date("N w z W t L o y n G h A", $ts), date("U e T P O p Z I", $ts), date("c", $ts), date("r", $ts));
+var_dump(date("\T\o\d\a\y \i\s l", $ts), date(DATE_ATOM, $ts), date(DATE_RFC7231, $ts));
+var_dump(date("jS", 1704067200), date("jS", 1704153600), date("jS", 1704240000), date("jS", 1704931200), date("jS", 1705795200));
+
+var_dump(date_default_timezone_set("America/New_York"), date_default_timezone_get());
+var_dump(date("Y-m-d H:i:s T P I", $ts), date("Y-m-d H:i:s T I", 1720000000), gmdate("Y-m-d H:i:s T", $ts));
+var_dump(date_default_timezone_set("Mars/Olympus"));
+var_dump(date_default_timezone_set("europe/london"), date_default_timezone_get());
+date_default_timezone_set("UTC");
+
+var_dump(mktime(0, 0, 0, 1, 1, 2024), mktime(12, 30, 0, 2, 30, 2024), mktime(0, 0, 0, 13, 1, 2023), mktime(0, 0, 0, 1, 0, 2024));
+var_dump(gmmktime(0, 0, 0, 7, 4, 99), date("Y-m-d", mktime(0, 0, 0, 3, 1, 24)));
+var_dump(checkdate(2, 29, 2024), checkdate(2, 29, 2023), checkdate(13, 1, 2024), checkdate(4, 31, 2024));
+
+function show($text, $base) {
+    $result = strtotime($text, $base);
+    if ($result === false) {
+        return $text . " => false";
+    }
+    return $text . " => " . date("D Y-m-d H:i:s", $result);
+}
+
+$texts1 = ["2024-03-15", "2024-03-15 14:30:00", "2024-03-15T14:30:00Z", "2024-03-15 14:30:00 +02:00", "03/15/2024", "15-03-2024", "15.03.2024", "20240315"];
+for ($i = 0; $i < count($texts1); $i = $i + 1) {
+    var_dump(show($texts1[$i], $ts));
+}
+$texts2 = ["March 15, 2024", "15 March 2024", "Mar 15 2024 3pm", "15th March", "January 2024", "10:45", "3:15 pm", "noon", "midnight"];
+for ($i = 0; $i < count($texts2); $i = $i + 1) {
+    var_dump(show($texts2[$i], $ts));
+}
+$texts3 = ["now", "today", "tomorrow", "yesterday", "+1 day", "-2 weeks", "+1 week 2 days 4 hours", "3 days ago", "+1 month", "next month", "last year"];
+for ($i = 0; $i < count($texts3); $i = $i + 1) {
+    var_dump(show($texts3[$i], $ts));
+}
+$texts4 = ["monday", "next monday", "last monday", "friday", "next friday", "last friday", "this friday", "first day of next month", "last day of this month", "last day of february 2024"];
+for ($i = 0; $i < count($texts4); $i = $i + 1) {
+    var_dump(show($texts4[$i], $ts));
+}
+$texts5 = ["@86400", "2024-01-31 +1 month", "tomorrow noon", "2024-03-15 Europe/Paris", "garbage", "2024-13-01", "25:00"];
+for ($i = 0; $i < count($texts5); $i = $i + 1) {
+    var_dump(show($texts5[$i], $ts));
+}
+
+$date = date_create("2024-01-31 10:00:00");
+var_dump($date, date_format($date, "Y-m-d H:i:s.u"));
+var_dump(date_modify($date, "+1 month"), date_format($date, "Y-m-d"));
+var_dump(date_modify($date, "nonsense"));
+var_dump(date_timestamp_get($date), date_format(date_timestamp_set($date, 0), "c"));
+
+$immutable = date_create_immutable("2024-01-05 08:00", timezone_open("Asia/Tokyo"));
+$later = date_modify($immutable, "+10 days");
+var_dump(date_format($immutable, "Y-m-d H:i T"), date_format($later, "Y-m-d H:i T"));
+var_dump(timezone_name_get(date_timezone_get($later)), date_create("garbage"));
+
+$paris = date_create("2024-06-01 12:00", timezone_open("Europe/Paris"));
+date_timezone_set($paris, timezone_open("America/Los_Angeles"));
+var_dump(date_format($paris, "Y-m-d H:i T e"));
+var_dump(date_format(date_create("2024-06-01 12:00 +05:30"), "Y-m-d H:i e T"), timezone_open("+05:30"));
+var_dump(date_format(date_date_set(date_create("2024-01-01 09:15"), 2020, 2, 29), "Y-m-d H:i"));
+var_dump(date_format(date_time_set(date_create("2024-01-01 09:15"), 23, 59, 59), "Y-m-d H:i:s"));
+var_dump(date_format(date_create("2024-03-10 02:30", timezone_open("America/New_York")), "Y-m-d H:i T"));
+
+$diff = date_diff(date_create("2024-01-31"), date_create("2024-03-01 06:30:15"));
+var_dump($diff, date_interval_format($diff, "%y years %m months %d days %h:%I:%S, %a total, %R"));
+$diff = date_diff(date_create("2025-06-15"), date_create("2024-01-01"));
+var_dump(date_interval_format($diff, "%R%y-%M-%D (%a days) %r"), date_interval_format(date_diff(date_create("2025-06-15"), date_create("2024-01-01"), true), "%R %%"));
+
+$interval = date_interval_create_from_date_string("1 year 2 months 3 days");
+var_dump($interval, date_interval_format($interval, "%y %m %d %a"));
+var_dump(date_format(date_add(date_create("2024-01-01"), $interval), "Y-m-d"));
+var_dump(date_format(date_sub(date_create("2024-01-01"), $interval), "Y-m-d"));
+var_dump(date_interval_create_from_date_string("soon"));
+var_dump(strtotime(""));
+
+date_format("2024-01-01", "Y");
@@ -0,0 +1,47 @@
+<?php
+
+$dir = sys_get_temp_dir() . "/microphp-directories";
+mkdir($dir);
+file_put_contents($dir . "/a.txt", "aaa");
+file_put_contents($dir . "/b.txt", "bbbbbb");
+mkdir($dir . "/sub");
+
+$handle = opendir($dir);
+var_dump($handle);
+$entries = [];
+while (($entry = readdir($handle)) !== false) {
+    array_push($entries, $entry);
+}
+sort($entries);
+var_dump($entries);
+rewinddir($handle);
+var_dump(readdir($handle));
+var_dump(readdir(), closedir($handle), is_resource($handle));
+
+var_dump(is_readable($dir . "/a.txt"), is_writable($dir . "/a.txt"), is_readable($dir . "/missing"));
+
+var_dump(chmod($dir . "/a.txt", 0640));
+var_dump(sprintf("%o", fileperms($dir . "/a.txt")), sprintf("%o", fileperms($dir . "/sub")));
+
+$stat = stat($dir . "/b.txt");
+var_dump(count($stat), $stat["size"], $stat[7], $stat["mtime"] == filemtime($dir . "/b.txt"));
+
+var_dump(symlink($dir . "/b.txt", $dir . "/link"), is_link($dir . "/link"), is_link($dir . "/b.txt"));
+var_dump(readlink($dir . "/link") == $dir . "/b.txt", file_get_contents($dir . "/link"));
+$link = lstat($dir . "/link");
+$target = stat($dir . "/link");
+var_dump($link["size"] == strlen($dir . "/b.txt"), $target["size"]);
+
+var_dump(is_float(disk_free_space($dir)), disk_free_space($dir) > 0);
+var_dump(clearstatcache());
+
+var_dump(opendir($dir . "/missing"));
+var_dump(stat($dir . "/missing"), lstat($dir . "/missing"), readlink($dir . "/a.txt"), symlink($dir . "/a.txt", $dir . "/b.txt"));
+
+unlink($dir . "/link");
+unlink($dir . "/a.txt");
+unlink($dir . "/b.txt");
+rmdir($dir . "/sub");
+rmdir($dir);
+
+readdir(STDIN);
@@ -0,0 +1,6 @@
+<?php
+
+var_dump(putenv("MICROPHP_BYTES=" . hex2bin("e9")), getenv("MICROPHP_BYTES") === hex2bin("e9"));
+var_dump(putenv("MICROPHP_BYTES"), getenv("MICROPHP_BYTES"));
+
+putenv("MICROPHP_NUL=x" . chr(0));
@@ -0,0 +1,43 @@
+<?php
+
+var_dump(dirname("/usr/local/lib/php"), dirname("/usr/local/lib/php", 2), dirname("file.txt"), dirname("/"));
+var_dump(basename("/etc/sudoers.d/", ".d"), basename("/tmp/archive.tar.gz", ".gz"));
+var_dump(pathinfo("/www/htdocs/inc/lib.inc.php"));
+var_dump(pathinfo("/www/htdocs/inc/lib.inc.php", PATHINFO_EXTENSION));
+
+$dir = sys_get_temp_dir() . "/microphp-filesystem";
+var_dump(mkdir($dir . "/a/b", 0777, true), is_dir($dir . "/a/b"));
+
+$file = $dir . "/notes.txt";
+var_dump(file_put_contents($file, "one
+two
+
+three
+"));
+var_dump(file_put_contents($file, ["four", "
+"], FILE_APPEND), file_exists($file), is_file($file), is_dir($file));
+var_dump(file_get_contents($file), file_get_contents($file, false, null, 4, 3));
+var_dump(file($file, FILE_IGNORE_NEW_LINES + FILE_SKIP_EMPTY_LINES));
+var_dump(filesize($file), filemtime($file) > 0);
+
+var_dump(touch($dir . "/empty", 86400), filemtime($dir . "/empty"), filesize($dir . "/empty"));
+var_dump(copy($file, $dir . "/copy.txt"), rename($dir . "/copy.txt", $dir . "/moved.txt"));
+var_dump(scandir($dir), scandir($dir, SCANDIR_SORT_DESCENDING));
+var_dump(glob($dir . "/*.txt"), glob($dir . "/{empty,a}", GLOB_BRACE + GLOB_MARK));
+var_dump(realpath($dir . "/a/../notes.txt") == $file, realpath($dir . "/missing"));
+
+$temp = tempnam($dir, "tmp");
+var_dump(strpos($temp, $dir . "/tmp") === 0, file_exists($temp));
+
+var_dump(file_get_contents($dir . "/missing"));
+var_dump(unlink($dir . "/missing"));
+var_dump(rmdir($dir));
+var_dump(filesize($dir . "/missing"));
+
+unlink($temp);
+unlink($file);
+unlink($dir . "/moved.txt");
+unlink($dir . "/empty");
+rmdir($dir . "/a/b");
+rmdir($dir . "/a");
+var_dump(rmdir($dir), file_exists($dir));
@@ -0,0 +1,5 @@
+<?php
+echo "never";
+function f() {
+    $GLOBALS = [];
+}
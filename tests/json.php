@@ -0,0 +1,36 @@
+<?php
+
+$quote = file_get_contents("data:,%22");
+function json($text) {
+    return str_replace("'", file_get_contents("data:,%22"), $text);
+}
+
+var_dump(json_encode([1, 2, 3]), json_encode(["a" => 1, "b" => [true, false, null]]));
+var_dump(json_encode([1 => "a", 2 => "b"]), json_encode([]), json_encode([], JSON_FORCE_OBJECT));
+var_dump(json_encode(1.0), json_encode(1.0, JSON_PRESERVE_ZERO_FRACTION), json_encode(0.1), json_encode(1e25));
+var_dump(json_encode("a/b"), json_encode("a/b", JSON_UNESCAPED_SLASHES));
+var_dump(json_encode("héllo ☃ 😀"), json_encode("héllo ☃ 😀", JSON_UNESCAPED_UNICODE));
+var_dump(json_encode("<a href=" . $quote . "x" . $quote . ">&</a>", JSON_HEX_TAG + JSON_HEX_AMP + JSON_HEX_QUOT));
+var_dump(json_encode(["12", "1.5", "abc"], JSON_NUMERIC_CHECK));
+echo json_encode(["name" => "microphp", "tags" => ["php", "rust"], "meta" => [], "nested" => ["deep" => [1]]], JSON_PRETTY_PRINT) . "
+";
+
+var_dump(json_encode(NAN), json_last_error(), json_last_error_msg());
+var_dump(json_encode([1, NAN], JSON_PARTIAL_OUTPUT_ON_ERROR), json_last_error());
+var_dump(json_encode([[1]], 0, 1), json_last_error_msg());
+var_dump(json_encode(STDIN), json_last_error_msg());
+
+var_dump(json_decode(json("{'a':1,'b':[1,2.5,'x'],'c':{'d':null}}"), true));
+var_dump(json_decode(json("{'a':1,'b':{'c':true}}")));
+var_dump(json_decode(json("{'0':'zero','1':'one'}"), true));
+var_dump(json_decode("[1,2]", false, 512, JSON_OBJECT_AS_ARRAY));
+var_dump(json_decode("12345678901234567890"), json_decode("12345678901234567890", false, 512, JSON_BIGINT_AS_STRING));
+var_dump(json_decode(json("'\u00e9\ud83d\ude00'")), json_decode(" true "), json_decode("-0"), json_decode("1e2"));
+var_dump(json_decode(""), json_last_error_msg());
+var_dump(json_decode("[1,]"), json_last_error_msg());
+var_dump(json_decode("[[1]]", true, 1), json_last_error_msg());
+var_dump(json_decode(json("'\ud83d'")), json_last_error_msg());
+var_dump(json_decode("01"), json_last_error());
+var_dump(json_decode("[1]"), json_last_error());
+
+json_decode("{", false, 512, JSON_THROW_ON_ERROR);
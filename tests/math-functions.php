@@ -0,0 +1,21 @@
+<?php
+
+var_dump(abs(-5), abs(-2.5), abs("-3"), abs(PHP_INT_MIN));
+var_dump(ceil(4.1), floor(-4.1), ceil(5));
+var_dump(round(3.14159, 2), round(1.955, 2), round(1.005, 2), round(-2.5), round(1234, -2));
+var_dump(round(2.5, 0, PHP_ROUND_HALF_EVEN), round(3.5, 0, PHP_ROUND_HALF_EVEN), round(2.5, 0, PHP_ROUND_HALF_DOWN), round(2.5, 0, PHP_ROUND_HALF_ODD));
+var_dump(intdiv(7, 2), intdiv(-7, 2));
+var_dump(7 % 3, -7 % 3, 7 % -3, 5.7 % 2);
+var_dump(fmod(10, 3), fmod(-7.5, 2));
+var_dump(sqrt(16), pow(2, 10), pow(2, 63), pow(2, -1), pow("3", 2), pow(2.0, 3));
+var_dump(exp(1), log(M_E), log(8, 2), log(100, 10), log10(1000));
+var_dump(sin(0), cos(M_PI), tan(M_PI_4), asin(1), acos(1), atan(1), atan2(1, 1));
+var_dump(pi(), M_PI, M_SQRT2);
+var_dump(min(3, 1, 2), max([4, 9, 2]), max("apple", "banana"), min([0, -0.5]), max(1, "1"));
+var_dump(base_convert("ff", 16, 2), base_convert("zz", 36, 10));
+var_dump(bindec("1101"), decbin(13), hexdec("1A"), dechex(255), octdec("777"), decoct(8), dechex(-1));
+var_dump(hexdec("ffffffffffffffff"));
+var_dump(is_nan(NAN), is_finite(INF), is_infinite(-INF), is_nan(1.0));
+var_dump(deg2rad(180), rad2deg(M_PI), hypot(3, 4));
+
+var_dump(intdiv(1, 0));
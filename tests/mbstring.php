@@ -0,0 +1,32 @@
+<?php
+
+$word = "Größe";
+$latin1 = hex2bin("47728e6665");
+$invalid = hex2bin("61ff62");
+
+var_dump(strlen($word), mb_strlen($word), mb_strlen($invalid), mb_strlen($word, "ASCII"), mb_strlen($word, "ISO-8859-1"));
+var_dump(mb_substr($word, 1, 3), mb_substr($word, -2), mb_substr($word, 1, -1), mb_substr($word, 10), substr($word, 2, 1) === hex2bin("c3"));
+var_dump(mb_strpos("日本語のテキスト", "テ"), mb_strpos($word, "e"), mb_strpos($word, "x"), mb_strpos($word, "ö", -4), strpos($word, "e"));
+var_dump(mb_strtoupper("größe ünd straße"), mb_strtolower("ÀÉÎÕÜ"), strtoupper($word));
+var_dump(mb_convert_case("hello wörld it’s o.k.", MB_CASE_TITLE), mb_convert_case("straße", MB_CASE_UPPER_SIMPLE), mb_convert_case("ÄB", MB_CASE_LOWER));
+var_dump(mb_str_split("añb日"), mb_str_split("añb日", 2), mb_str_split(""));
+var_dump(mb_str_pad("日本", 5, "ü", STR_PAD_BOTH), mb_str_pad("日本", 4, "-", STR_PAD_LEFT), mb_str_pad("日本", 1));
+var_dump(mb_check_encoding($word), mb_check_encoding($invalid), mb_check_encoding($word, "ASCII"), mb_check_encoding(["ä" => "ö"]), mb_check_encoding([1 => $invalid]));
+var_dump(bin2hex(mb_convert_encoding($word, "ISO-8859-1")), mb_convert_encoding(hex2bin("4772e9"), "UTF-8", "ISO-8859-1"), mb_convert_encoding($word, "ASCII"), mb_convert_encoding($invalid, "UTF-8"));
+var_dump(mb_convert_encoding($word, "UTF-8", "ASCII, UTF-8"), mb_convert_encoding(["k" => $word], "ISO-8859-1", "UTF-8") === ["k" => hex2bin("4772f6df65")]);
+var_dump(iconv("UTF-8", "ISO-8859-1", $word) === hex2bin("4772f6df65"), iconv("ISO-8859-1", "UTF-8", hex2bin("4772f6df65")), iconv("UTF-8", "ASCII//TRANSLIT", $word), iconv("UTF-8", "ASCII//IGNORE", $word));
+var_dump(iconv("UTF-8", "ASCII", $word), iconv("UTF-8", "UTF-8", $invalid), iconv("UTF-8", "EBCDIC", $word));
+
+$binary = hex2bin("00ff80c3fe0d0a1a") . random_bytes(4096);
+
+$path = sys_get_temp_dir() . "/microphp-binary.bin";
+
+var_dump(strlen($binary), file_put_contents($path, $binary), filesize($path));
+
+$read = file_get_contents($path);
+
+var_dump(strlen($read), $read === $binary, md5($read) === md5($binary), bin2hex(substr($read, 0, 8)));
+var_dump(strlen(strrev($binary)), strrev(strrev($binary)) === $binary, bin2hex(str_replace(hex2bin("00"), "", substr($binary, 0, 3))));
+var_dump(unserialize(serialize($binary)) === $binary, json_encode($invalid), var_export($latin1, true) === "'" . $latin1 . "'");
+
+unlink($path);
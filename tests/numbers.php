@@ -0,0 +1,17 @@
+<?php
+
+var_dump(0x1F);
+var_dump(0b101);
+var_dump(0o17);
+var_dump(017);
+var_dump(1_000_000);
+var_dump(1e3);
+var_dump(.5);
+var_dump(1.5e-3);
+var_dump(PHP_INT_MAX);
+var_dump(PHP_INT_MAX + 1);
+var_dump(-PHP_INT_MAX - 1);
+var_dump(PHP_INT_MIN * 2);
+var_dump(9223372036854775808);
+var_dump(0xFFFFFFFFFFFFFFFF);
+var_dump(PHP_FLOAT_EPSILON);
@@ -0,0 +1,17 @@
+<?php
+
+$binary = hex2bin("ff78c2a0e284aa00c3a9");
+
+var_dump(bin2hex(preg_replace("/x/", "y", $binary)));
+var_dump(preg_match("/x/", $binary, $matches, PREG_OFFSET_CAPTURE), $matches);
+var_dump(preg_match("/\x00(.)/", $binary, $matches, PREG_OFFSET_CAPTURE, 3), bin2hex($matches[1][0]), $matches[1][1]);
+var_dump(preg_match_all("/[^\x00-\x7f]/", $binary), bin2hex(preg_replace("/\s/", "_", $binary)));
+var_dump(preg_split("/x/", $binary) === [hex2bin("ff"), hex2bin("c2a0e284aa00c3a9")]);
+
+var_dump(preg_match("/^.$/", "é"), preg_match("/^..$/", "é"), preg_match("/^.$/u", "é"));
+var_dump(preg_match("/^\w+$/", "é"), preg_match("/^\w+$/u", "é"), preg_match("/\bb/", "éb"));
+var_dump(preg_match("/[a-z]/i", hex2bin("e284aa")), preg_match("/k/iu", hex2bin("e284aa")));
+var_dump(preg_match("/é/i", "É"), preg_match("/é/iu", "É"));
+var_dump(preg_match_all("/\w+/", "été", $matches, PREG_OFFSET_CAPTURE), $matches);
+var_dump(preg_replace("/t/", "d", "été"), preg_split("/t/", "été", -1, PREG_SPLIT_OFFSET_CAPTURE));
+var_dump(preg_match("/a/u", $binary), preg_last_error_msg());
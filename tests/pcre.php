@@ -0,0 +1,67 @@
+<?php
+
+var_dump(preg_match("/(\d+)-(\d+)/", "order 12-345 shipped", $matches), $matches);
+var_dump(preg_match("/(?<year>\d{4})-(?<month>\d{2})/", "Date: 2024-05", $matches), $matches);
+var_dump(preg_match("/HELLO/i", "say hello", $matches), $matches);
+var_dump(preg_match("/x(y)?(z)?/", "x", $matches), $matches);
+var_dump(preg_match("/(a)(b)?/", "a", $matches, PREG_UNMATCHED_AS_NULL), $matches);
+var_dump(preg_match("/b+/", "aabbb", $matches, PREG_OFFSET_CAPTURE), $matches);
+var_dump(preg_match("/a/", "banana", $matches, 0, 2), $matches);
+var_dump(preg_match("/nope/", "banana", $matches), $matches);
+var_dump(preg_match("/^end$/", "end
+"), preg_match("/^end$/D", "end
+"), preg_match("/^b$/m", "a
+b
+c"));
+var_dump(preg_match("/a.c/s", "a
+c"), preg_match("/a.c/", "a
+c"), preg_match("/a b c/x", "abc"));
+var_dump(preg_match("#é+#u", "café"), preg_match("{a{2}}", "baab"), preg_match("(\w+)", "word"));
+
+var_dump(preg_match_all("/\d/", "a1b2c3", $matches), $matches);
+var_dump(preg_match_all("/(?<key>\w)=(\d)/", "a=1, b=2", $matches), $matches);
+var_dump(preg_match_all("/(\w)=(\d)?/", "a=1, b=", $matches, PREG_SET_ORDER), $matches);
+var_dump(preg_match_all("/o/", "foo", $matches, PREG_OFFSET_CAPTURE), $matches);
+var_dump(preg_match_all("/x*/", "axb", $matches), $matches);
+
+var_dump(preg_replace("/(\w+) (\w+)/", "$2 ${1}!", "hello world"));
+var_dump(preg_replace("/a/", "b", "banana", 2, $count), $count);
+var_dump(preg_replace(["/a/", "/b/"], ["b", "c"], "ab"));
+var_dump(preg_replace(["/a/", "/e/"], "_", ["x" => "apple", "y" => "pear"]));
+var_dump(preg_replace("/(\d)/", "\1\1", "a1b2"), preg_replace("/o/", "\$0", "foo"));
+function double($m) {
+    return $m[0] * 2;
+}
+
+function shout($m) {
+    return strtoupper($m["word"]);
+}
+
+var_dump(preg_replace_callback("/\d+/", "double", "3 apples and 10 pears", -1, $count), $count);
+var_dump(preg_replace_callback("/(?<word>\w+)/", "shout", "up with this"));
+
+var_dump(preg_split("/[\s,]+/", "a, b  c,d"));
+var_dump(preg_split("//", "abc", -1, PREG_SPLIT_NO_EMPTY));
+var_dump(preg_split("/(-)/", "a-b-c", 2, PREG_SPLIT_DELIM_CAPTURE));
+var_dump(preg_split("/ /", "a b", -1, PREG_SPLIT_OFFSET_CAPTURE));
+var_dump(preg_split("/,/", ",a,,b,", -1, PREG_SPLIT_NO_EMPTY));
+
+var_dump(preg_quote("Hello.World?(1+1=2) [x]"), preg_quote("a/b#c", "/"));
+
+var_dump(preg_grep("/^\d+$/", ["1", "a", "22", "b3"]));
+var_dump(preg_grep("/^\d+$/", ["1", "a", "22", "b3"], PREG_GREP_INVERT));
+
+var_dump(preg_match("", "a"));
+var_dump(preg_match("abc", "a"));
+var_dump(preg_match("/abc", "a"));
+var_dump(preg_match("/abc/k", "a"));
+var_dump(preg_match("/(abc/", "a"));
+var_dump(preg_last_error(), preg_last_error_msg());
+var_dump(preg_match("/a/", "a", $matches, 0, 10), preg_last_error(), preg_last_error_msg());
+var_dump(preg_match("/./u", "é", $matches, 0, 1), preg_last_error_msg());
+var_dump(preg_match("/(?:a+)+$/", str_repeat("a", 30) . "b"), preg_last_error(), preg_last_error_msg());
+var_dump(preg_match("/a/", "a"), preg_last_error());
+
+var_dump(preg_match("/a/u", hex2bin("61ff")), preg_last_error_msg(), preg_match("/a/", hex2bin("61ff")), bin2hex(preg_replace("/b/", "c", hex2bin("ff62ff"))));
+
+preg_replace("/a/", ["b"], "a");
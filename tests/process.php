@@ -0,0 +1,44 @@
+<?php
+
+var_dump(PHP_EOL, PHP_OS, PHP_OS_FAMILY, PHP_VERSION, PHP_MAJOR_VERSION, PHP_VERSION_ID >= 80000, php_sapi_name());
+var_dump(php_uname("s"), php_uname("n") === gethostname(), getmypid() > 0, is_string(php_uname()));
+
+var_dump(putenv("MICROPHP_TEST=hello"), getenv("MICROPHP_TEST"), getenv("MICROPHP_MISSING"), is_array(getenv()));
+var_dump(putenv("MICROPHP_TEST"), getenv("MICROPHP_TEST"), array_key_exists("PATH", $_ENV));
+
+$output = ["first"];
+var_dump(exec("printf 'one\ntwo  \nthree\n'; exit 3", $output, $code), $output, $code);
+var_dump(exec("true", $empty), $empty);
+
+var_dump(shell_exec("echo hi"), shell_exec("true"), `echo backticks`);
+
+echo "before ";
+var_dump(system("echo one; echo two", $code), $code);
+passthru("echo passed; exit 2", $code);
+var_dump($code);
+
+var_dump(escapeshellarg("it's here"), escapeshellarg(""), escapeshellcmd("ls *.php; rm -rf ~"), escapeshellcmd("echo 'paired' 'unpaired"));
+var_dump(shell_exec("echo " . escapeshellarg("a 'quoted' word")));
+
+$process = proc_open("tr a-z A-Z; echo oops >&2; exit 4", [0 => ["pipe", "r"], 1 => ["pipe", "w"], 2 => ["pipe", "w"]], $pipes);
+fwrite($pipes[0], "shout" . PHP_EOL);
+fclose($pipes[0]);
+var_dump(fgets($pipes[1]), fgets($pipes[2]));
+fclose($pipes[1]);
+fclose($pipes[2]);
+var_dump(proc_close($process));
+
+$process = proc_open(["printf", "%s-%s", "a", "b"], [1 => ["pipe", "w"]], $pipes);
+var_dump(fgets($pipes[1]));
+$status = proc_get_status($process);
+var_dump($status["command"], proc_close($process));
+
+var_dump(sleep(0), usleep(1));
+
+function stop($status) {
+    echo "stopping";
+    exit($status);
+}
+
+stop(7);
+echo "unreachable";
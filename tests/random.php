@@ -0,0 +1,26 @@
+<?php
+
+mt_srand(1);
+var_dump(mt_rand(), mt_rand(), mt_rand(1, 100), mt_rand(-1000, 1000));
+
+mt_srand(42);
+var_dump(rand(), rand(10, 1), mt_rand(0, PHP_INT_MAX));
+
+mt_srand(3);
+$deck = ["a", "b", "c", "d", "e"];
+shuffle($deck);
+var_dump($deck);
+var_dump(str_shuffle("abcdef"));
+var_dump(array_rand(["x" => 1, "y" => 2, "z" => 3]));
+var_dump(array_rand([10, 20, 30, 40], 3));
+
+mt_srand(1, MT_RAND_PHP);
+var_dump(mt_rand(1, 10));
+
+var_dump(mt_getrandmax(), getrandmax());
+
+$n = random_int(5, 10);
+var_dump($n >= 5, $n <= 10);
+var_dump(strlen(random_bytes(1)) > 0);
+$lcg = lcg_value();
+var_dump($lcg > 0, $lcg < 1);
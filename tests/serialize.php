@@ -0,0 +1,45 @@
+<?php
+
+$quote = file_get_contents("data:,%22");
+function php($text) {
+    return str_replace("'", file_get_contents("data:,%22"), $text);
+}
+
+$value = ["name" => "microphp", "tags" => ["php", "rust"], "version" => 1.5, "stable" => false, "meta" => null, 7 => (object) ["a" => 1]];
+
+print_r($value);
+echo print_r("scalar", true) . "
+";
+var_dump(print_r([], true), print_r(1.0, true));
+
+var_export($value);
+echo "
+";
+var_export([1.0, 0.1, -0.0, 1e25, INF, PHP_INT_MIN, "it's a \ test"]);
+echo "
+";
+var_dump(var_export(true, true), var_export(null, true), var_export([], true), var_export(STDIN, true));
+var_export(date_create("2024-01-02 03:04:05"));
+echo "
+";
+
+var_dump(serialize($value));
+var_dump(serialize([true, 1, 0.1, -0.0, 1e25, NAN, -INF, STDIN]));
+
+$object = (object) ["a" => 1];
+var_dump(serialize([$object, $object]));
+
+var_dump(unserialize(serialize($value)));
+var_dump(unserialize(php("a:2:{i:0;O:8:'stdClass':1:{s:1:'a';i:1;}i:1;r:2;}")));
+var_dump(unserialize(php("a:3:{s:1:'5';b:1;s:1:'x';d:0.5;i:-3;S:3:'\41bc';}")));
+var_dump(unserialize(php("O:3:'Foo':1:{s:1:'a';i:1;}")));
+var_dump(serialize(unserialize(php("O:3:'Foo':1:{s:1:'a';i:1;}"))));
+var_dump(unserialize(php("O:8:'stdClass':0:{}"), ["allowed_classes" => false]));
+var_dump(unserialize(php("O:8:'stdClass':0:{}"), ["allowed_classes" => ["STDCLASS"]]));
+var_dump(date_format(unserialize(serialize(date_create("2024-01-02 03:04:05"))), "Y-m-d H:i"));
+
+var_dump(unserialize("b:0;"), unserialize("N;"), unserialize(""));
+var_dump(unserialize("foo"));
+var_dump(unserialize("a:2:{i:0;i:1;}"));
+var_dump(unserialize(php("s:5:'abc';")));
+var_dump(unserialize("i:5;xyz"));
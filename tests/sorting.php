@@ -0,0 +1,54 @@
+<?php
+
+$numbers = [3, "10", 1, "2", 2.5];
+sort($numbers);
+var_dump($numbers);
+
+sort($numbers, SORT_STRING);
+var_dump($numbers);
+
+rsort($numbers);
+var_dump($numbers);
+
+$mixed = ["b" => 2, "a" => 1, "c" => 1];
+asort($mixed);
+var_dump(array_keys($mixed));
+arsort($mixed);
+var_dump(array_keys($mixed));
+
+$keys = [10 => "a", "9" => "b", "x" => "c", 1 => "d"];
+ksort($keys);
+var_dump(array_keys($keys));
+krsort($keys);
+var_dump(array_keys($keys));
+
+$files = ["img12.png", "img10.png", "IMG2.png", "img1.png"];
+natsort($files);
+var_dump($files);
+natcasesort($files);
+var_dump($files);
+
+$words = ["Banana", "apple", "cherry"];
+sort($words, SORT_STRING + SORT_FLAG_CASE);
+var_dump($words);
+sort($words, SORT_NATURAL);
+var_dump($words);
+
+$data = [3, 1, 3, 2];
+$labels = ["c", "a", "d", "b"];
+array_multisort($data, SORT_DESC, $labels);
+var_dump($data, $labels);
+
+$a = [3, 1, 2];
+$b = $a;
+$c = $a;
+$d = $a;
+$e = $a;
+$f = $a;
+$g = $a;
+$h = $a;
+$i = $a;
+$j = ["c", "a", "b"];
+$k = ["z", "x", "y"];
+array_multisort($a, $b, $c, $d, $e, $f, $g, $h, $i, $j, $k);
+var_dump(implode(",", $i), implode(",", $j), implode(",", $k));
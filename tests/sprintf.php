@@ -0,0 +1,26 @@
+<?php
+
+var_dump(sprintf("%s is %d years old", "Ada", 36));
+var_dump(sprintf("%05d|%-5d|%+d|%+d", -42, 7, 3, -3));
+var_dump(sprintf("%u", -1));
+var_dump(sprintf("%x %X %o %b %c", 255, 255, 8, 5, 65));
+var_dump(sprintf("%.2f %F %.3e %E", 3.14159, 1.5, 12345.678, 0.00012));
+var_dump(sprintf("%g %g %G %g", 0.00001234, 1e20, 123456789.0, 100.0));
+var_dump(sprintf("%2$s %1$s %1$s", "world", "hello"));
+var_dump(sprintf("[%'*10s] [%-10s] [%10.3s]", "pad", "left", "truncate"));
+var_dump(sprintf("[%*d] [%.*f]", 6, 42, 2, 2.71828));
+var_dump(sprintf("%d%%", 50));
+var_dump(vsprintf("%s-%s", ["a", "b"]));
+
+$length = printf("%s has %d items", "cart", 3);
+var_dump($length);
+vprintf("%04.1f", [9.96]);
+echo "
+";
+
+var_dump(number_format(1234567.891));
+var_dump(number_format(1234567.891, 2));
+var_dump(number_format(1234567.891, 2, ",", "."));
+var_dump(number_format(1.005, 2));
+var_dump(number_format(-0.4));
+var_dump(number_format(1000, 3, ".", " "));
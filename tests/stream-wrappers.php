@@ -0,0 +1,46 @@
+<?php
+
+var_dump(stream_get_wrappers());
+
+$memory = fopen("php://memory", "w+");
+fwrite($memory, "alpha
+beta
+gamma");
+rewind($memory);
+var_dump(fgets($memory), fread($memory, 100), ftell($memory));
+fseek($memory, 6);
+fwrite($memory, "BETA");
+rewind($memory);
+var_dump(fread($memory, 100));
+var_dump(fseek($memory, 100), fclose($memory));
+
+$readonly = fopen("php://memory", "r");
+var_dump(fwrite($readonly, "nope"));
+
+$temp = fopen("php://temp/maxmemory:1024", "r+");
+fputcsv($temp, ["a", "b c"]);
+rewind($temp);
+var_dump(fgetcsv($temp));
+
+var_dump(file_get_contents("data://text/plain;base64,SGVsbG8sIFdvcmxkIQ=="));
+var_dump(file_get_contents("data:text/plain,hello%20there+friend"));
+var_dump(file_get_contents("data://text/plain;base64,SGVsbG8sIFdvcmxkIQ==", false, null, 7, 5));
+var_dump(file("data://text/plain;base64,b25lCnR3bwp0aHJlZQ==", FILE_IGNORE_NEW_LINES));
+var_dump(file_get_contents("php://input"));
+
+$path = sys_get_temp_dir() . "/microphp-wrappers.txt";
+var_dump(file_put_contents("file://" . $path, "via file://"), file_get_contents("file://" . $path));
+unlink($path);
+
+$output = fopen("php://output", "w");
+fwrite($output, "written to php://output
+");
+fprintf(fopen("php://stdout", "w"), "written to php://%s
+", "stdout");
+fwrite(fopen("php://stderr", "w"), "written to php://stderr
+");
+
+var_dump(file_get_contents("data://text/plain;base64,!!!"));
+var_dump(file_get_contents("data://text/plain"));
+var_dump(fopen("php://nothing", "r"));
+var_dump(file_get_contents("bogus://thing"));
@@ -0,0 +1,47 @@
+<?php
+
+$path = sys_get_temp_dir() . "/microphp-streams.txt";
+
+$handle = fopen($path, "w+");
+var_dump($handle, is_resource($handle), get_debug_type($handle));
+var_dump(fwrite($handle, "first line
+second line
+"), fputs($handle, "third", 3));
+var_dump(ftell($handle), rewind($handle), ftell($handle));
+var_dump(fgets($handle), fgetc($handle), fread($handle, 5), ftell($handle));
+var_dump(fgets($handle), feof($handle), fgets($handle), feof($handle));
+var_dump(fseek($handle, -3, SEEK_END), fread($handle, 100), feof($handle));
+var_dump(fseek($handle, -100), fflush($handle));
+var_dump(flock($handle, LOCK_EX, $blocked), $blocked, flock($handle, LOCK_UN));
+var_dump(fclose($handle), is_resource($handle), gettype($handle), $handle);
+
+$csv = fopen($path, "w");
+var_dump(fputcsv($csv, ["id", "name", "notes"]));
+var_dump(fputcsv($csv, [1, "Ryan Chandler", "says \"hi\""]));
+var_dump(fputcsv($csv, [2, "plain", "multi
+line"], ";"));
+fclose($csv);
+
+echo file_get_contents($path);
+
+$csv = fopen($path, "r");
+var_dump(fgetcsv($csv), fgetcsv($csv), fgetcsv($csv, null, ";"), fgetcsv($csv));
+fclose($csv);
+
+$readonly = fopen($path, "r");
+var_dump(fwrite($readonly, "nope"));
+fclose($readonly);
+
+var_dump(fopen($path . ".missing", "r"));
+var_dump(fopen($path, "q"));
+unlink($path);
+
+var_dump(STDIN, STDOUT, STDERR);
+fwrite(STDOUT, "to stdout
+");
+fprintf(STDOUT, "%s has %d items
+", "list", 3);
+fwrite(STDERR, "to stderr
+");
+
+fclose($handle);
@@ -0,0 +1,55 @@
+<?php
+
+var_dump(strlen("hello"));
+var_dump(substr("abcdef", 1, 3));
+var_dump(substr("abcdef", -2));
+var_dump(substr("abcdef", 2, -1));
+var_dump(substr("abc", 5));
+
+var_dump(strpos("hello world", "o"));
+var_dump(strpos("hello world", "o", 5));
+var_dump(strrpos("hello world", "o"));
+var_dump(stripos("Hello World", "WORLD"));
+var_dump(strpos("hello", "z"));
+var_dump(str_contains("haystack", "st"));
+var_dump(str_starts_with("haystack", "hay"));
+var_dump(str_ends_with("haystack", "stack"));
+
+var_dump(str_replace("l", "L", "hello", $count));
+var_dump($count);
+var_dump(str_replace(["a", "b"], ["b", "c"], "ab"));
+var_dump(str_ireplace("WORLD", "there", "Hello world"));
+
+var_dump(explode(",", "a,b,,c"));
+var_dump(explode(",", "a,b,c", 2));
+var_dump(explode(",", "a,b,c", -1));
+var_dump(implode(", ", ["x", 1, 2.5, true]));
+var_dump(join("-", ["a", "b"]));
+
+var_dump(trim("  padded  "));
+var_dump(ltrim("xxhixx", "x"));
+var_dump(rtrim("abc123", "0..9"));
+
+var_dump(strtoupper("Hello"));
+var_dump(strtolower("HeLLo"));
+var_dump(ucfirst("hello"));
+var_dump(ucwords("hello big-world", " -"));
+
+var_dump(str_repeat("ab", 3));
+var_dump(str_pad("5", 3, "0", STR_PAD_LEFT));
+var_dump(str_pad("x", 6, "ab", STR_PAD_BOTH));
+var_dump(str_split("abcde", 2));
+var_dump(strrev("stressed"));
+var_dump(wordwrap("The quick brown fox", 10, "<br>", true));
+var_dump(wordwrap("A very long woooooooooooord.", 8, "-", true));
+
+var_dump(strcmp("a", "b"));
+var_dump(strcasecmp("HELLO", "hello"));
+var_dump(strncmp("abcd", "abef", 2));
+var_dump(strnatcmp("img12", "img10"));
+var_dump(strnatcasecmp("IMG2", "img10"));
+
+var_dump(substr_count("hello hello", "ll"));
+var_dump(similar_text("World", "Word", $percent));
+var_dump($percent);
+var_dump(levenshtein("kitten", "sitting"));
@@ -0,0 +1,40 @@
+<?php
+
+var_dump($argc, count($argv), $argv[0] === $_SERVER["argv"][0], $_SERVER["argc"] === $argc);
+var_dump($_SERVER["PHP_SELF"] === $argv[0], $_SERVER["SCRIPT_NAME"] === $argv[0], $_SERVER["REQUEST_TIME"] > 1600000000, is_float($_SERVER["REQUEST_TIME_FLOAT"]));
+var_dump($_GET, $_POST, $_SERVER["PATH"] === getenv("PATH"), $_ENV["PATH"] === getenv("PATH"));
+
+$name = "global";
+
+function scoped() {
+    var_dump(count($_SERVER["argv"]) > 0, array_key_exists("PATH", $_ENV));
+    var_dump($GLOBALS["name"], array_key_exists("_SERVER", $GLOBALS), array_key_exists("argv", $GLOBALS));
+
+    $_SERVER["FROM_FUNCTION"] = "set";
+}
+
+scoped();
+var_dump($_SERVER["FROM_FUNCTION"]);
+
+function write_globals() {
+    $GLOBALS["written"] = 10;
+    $GLOBALS["name"] = "changed";
+    $local = "local";
+}
+
+write_globals();
+var_dump($written, $name, array_key_exists("local", $GLOBALS));
+
+$zebra = 1;
+$apple = 2;
+$keys = array_keys($GLOBALS);
+var_dump(array_slice($keys, array_search("argv", $keys)));
+
+$chained = $GLOBALS["also"] = 5;
+var_dump($chained, $also);
+
+$_SERVER["argv"] = ["script.php", "-a", "-bvalue", "-c", "next", "-d=equals", "-xyz", "--verbose", "--level=3", "--name", "long", "--opt", "-a", "rest", "-b", "ignored"];
+var_dump(getopt("ab:c:d:xy::z", ["verbose", "level:", "name:", "opt::"], $index), $index);
+
+$_SERVER["argv"] = ["script.php", "-v", "-v", "-1", "--", "-a"];
+var_dump(getopt("v1a", [], $index), $index);
@@ -0,0 +1,37 @@
+<?php
+
+var_dump(! -1);
+var_dump(! -0.5);
+var_dump(! 0.0);
+var_dump(! "0");
+var_dump(! "0.0");
+var_dump(! "");
+var_dump(! []);
+var_dump(! [0]);
+var_dump(! null);
+
+echo 1e20;
+echo " ";
+echo 0.1 + 0.2;
+echo " ";
+echo -0.0;
+echo " ";
+echo 0.00001;
+echo " ";
+echo 1.5;
+echo " ";
+echo 100.0;
+echo " ";
+echo PHP_INT_MAX + 1;
+echo " ";
+echo 1e15;
+echo " ";
+echo 123456789012345.678;
+echo " ";
+echo [1, 2];
+
+var_dump(0.1 + 0.2);
+var_dump(1e20);
+var_dump(-0.0);
+var_dump(1 / 3);
+var_dump(PHP_INT_MAX + 1);
@@ -0,0 +1,30 @@
+<?php
+
+var_dump(1 + "1");
+var_dump("1.5" + 1);
+var_dump(" 10 " * "2");
+var_dump("10 apples" + 5);
+var_dump(null + 1);
+var_dump(true + true);
+var_dump(4 / 2);
+var_dump(3 / 2);
+var_dump(PHP_INT_MAX + 1);
+
+var_dump(["a" => 1, "b" => 2] + ["b" => 3, "c" => 4]);
+var_dump([1, 2] == [1, 2]);
+var_dump([1, 2] === [2 => 1]);
+
+var_dump(0 == "a");
+var_dump("1" == "01");
+var_dump("10" == "1e1");
+var_dump(100 == "1e2");
+var_dump("abc" == 0);
+var_dump(null == false);
+var_dump(null == "");
+var_dump("abc" <=> "abd");
+var_dump(2 <=> 1);
+var_dump(1 <= 1.0);
+var_dump(1 === 1.0);
+var_dump("1" !== 1);
+
+var_dump([1] + 1);
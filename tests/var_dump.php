@@ -9,4 +9,6 @@ var_dump(null);
 var_dump([
     "testing",
     "another",
-]);
\ No newline at end of file
+]);
+var_dump(["a" => 1, "nested" => ["b" => [2.0, -0.0]], 5 => (object) ["x" => "y"]]);
+var_dump([], (object) []);